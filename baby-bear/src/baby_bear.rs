@@ -109,6 +109,7 @@ impl BinomialExtensionData<5> for BabyBearParameters {
 
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
     use core::array;
 
     use p3_field::{PrimeField32, PrimeField64, TwoAdicField};
@@ -223,6 +224,13 @@ mod tests {
         assert_eq!(m2, m2_deserialized);
     }
 
+    #[test]
+    fn non_canonical_values_fail_to_deserialize() {
+        assert!(serde_json::from_str::<F>("2013265920").is_ok());
+        assert!(serde_json::from_str::<F>("2013265921").is_err());
+        assert!(serde_json::from_str::<F>(&u32::MAX.to_string()).is_err());
+    }
+
     test_field!(crate::BabyBear);
     test_two_adic_field!(crate::BabyBear);
 
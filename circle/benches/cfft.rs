@@ -3,6 +3,7 @@ use criterion::{criterion_group, criterion_main, BenchmarkGroup, BenchmarkId, Cr
 use p3_baby_bear::BabyBear;
 use p3_circle::{CircleDomain, CircleEvaluations};
 use p3_dft::{Radix2Bowers, Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::extension::BinomialExtensionField;
 use p3_field::TwoAdicField;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_mersenne_31::Mersenne31;
@@ -22,6 +23,43 @@ fn bench_lde(c: &mut Criterion) {
     lde_twoadic::<BabyBear, Radix2Bowers, _>(&mut g, log_n, log_w);
 }
 
+fn bench_cfft(c: &mut Criterion) {
+    type F = Mersenne31;
+    let log_n = 20;
+    let log_w = 6;
+    let domain = CircleDomain::<F>::standard(log_n);
+    let m = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, 1 << log_w);
+    let params = format!("log_n={log_n},log_w={log_w}");
+
+    let mut g = c.benchmark_group("cfft");
+    g.sample_size(10);
+    g.bench_with_input(BenchmarkId::new("interpolate", &params), &m, |b, m| {
+        b.iter_batched(
+            || m.clone(),
+            |m| CircleEvaluations::from_natural_order(domain, m).interpolate(),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+    g.bench_with_input(BenchmarkId::new("evaluate", &params), &m, |b, m| {
+        b.iter_batched(
+            || m.clone(),
+            |m| CircleEvaluations::evaluate(domain, m),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+
+    // Extension field rows of a similar size, interpolated without flattening them.
+    type EF = BinomialExtensionField<F, 3>;
+    let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_n, 1 << (log_w - 2));
+    g.bench_with_input(BenchmarkId::new("cfft_algebra", &params), &m, |b, m| {
+        b.iter_batched(
+            || m.clone(),
+            |m| domain.cfft_algebra(m),
+            criterion::BatchSize::LargeInput,
+        )
+    });
+}
+
 fn lde_cfft<M: Measurement>(g: &mut BenchmarkGroup<M>, log_n: usize, log_w: usize) {
     type F = Mersenne31;
     let m = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, 1 << log_w);
@@ -67,5 +105,5 @@ fn lde_twoadic<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>, M: Measurement>(
     );
 }
 
-criterion_group!(benches, bench_lde);
+criterion_group!(benches, bench_lde, bench_cfft);
 criterion_main!(benches);
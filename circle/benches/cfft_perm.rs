@@ -0,0 +1,53 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_circle::CfftPerm;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_mersenne_31::Mersenne31;
+use rand::thread_rng;
+
+type F = Mersenne31;
+
+/// Reading every row of a matrix through a cfft-ordered view, one index lookup per row, as a
+/// commitment of the view does, against materializing the permuted matrix in one pass.
+fn bench_cfft_perm(c: &mut Criterion) {
+    let mut g = c.benchmark_group("cfft_perm");
+    g.sample_size(10);
+    for log_n in [16, 20] {
+        let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, 1);
+
+        g.bench_with_input(BenchmarkId::new("rows", log_n), &mat, |b, mat| {
+            b.iter(|| {
+                let view = CfftPerm::new_view(mat.as_view());
+                (0..view.height()).map(|r| view.get(r, 0)).sum::<F>()
+            })
+        });
+        g.bench_with_input(
+            BenchmarkId::new("to_row_major_matrix", log_n),
+            &mat,
+            |b, mat| b.iter(|| CfftPerm::new_view(mat.as_view()).to_row_major_matrix()),
+        );
+    }
+}
+
+/// Materializing a large cfft-ordered view on one thread, which swaps rows in place, against the
+/// default thread pool, which gathers rows in parallel. Only meaningful with `parallel` enabled.
+fn bench_cfft_perm_threads(c: &mut Criterion) {
+    let mut g = c.benchmark_group("cfft_perm_threads");
+    g.sample_size(10);
+    let log_n = 22;
+    let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, 1);
+    let one_thread = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    g.bench_with_input(BenchmarkId::new("serial", log_n), &mat, |b, mat| {
+        b.iter(|| one_thread.install(|| CfftPerm::new_view(mat.as_view()).to_row_major_matrix()))
+    });
+    g.bench_with_input(BenchmarkId::new("parallel", log_n), &mat, |b, mat| {
+        b.iter(|| CfftPerm::new_view(mat.as_view()).to_row_major_matrix())
+    });
+}
+
+criterion_group!(benches, bench_cfft_perm, bench_cfft_perm_threads);
+criterion_main!(benches);
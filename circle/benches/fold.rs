@@ -0,0 +1,82 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use itertools::Itertools;
+use p3_circle::FoldingTwiddles;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::{AbstractExtensionField, Field};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use p3_mersenne_31::Mersenne31;
+use rand::{random, thread_rng};
+
+type F = Mersenne31;
+type EF = BinomialExtensionField<F, 3>;
+
+/// Folding a large extension field codeword, as in each FRI commit phase round. The twiddles are
+/// base field elements, and the fold runs on packed coordinates. "scalar" repeats the same
+/// arithmetic one pair at a time, to show what packing saves, and "lifted" also embeds the
+/// twiddles in the extension first, to show what keeping them in the base field saves.
+fn bench_fold(c: &mut Criterion) {
+    let mut g = c.benchmark_group("circle_fold");
+    g.sample_size(10);
+    for log_n in [16, 20] {
+        let evals = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << (log_n - 1), 2);
+        let beta: EF = random();
+        let twiddles = FoldingTwiddles::<F>::default();
+        // Warm up the twiddle cache, as every proof after the first would.
+        twiddles.fold_y(beta, evals.as_view());
+
+        g.bench_with_input(BenchmarkId::new("fold_y", log_n), &evals, |b, evals| {
+            b.iter(|| twiddles.fold_y(beta, evals.as_view()))
+        });
+
+        let ts = (0..evals.height()).map(|_| random::<F>()).collect_vec();
+        g.bench_with_input(BenchmarkId::new("scalar", log_n), &evals, |b, evals| {
+            b.iter(|| {
+                evals
+                    .rows()
+                    .zip(&ts)
+                    .map(|(mut row, &t)| {
+                        let (lo, hi) = row.next_tuple().unwrap();
+                        let diff = (lo - hi) * t;
+                        (lo + hi + beta * diff).halve()
+                    })
+                    .collect_vec()
+            })
+        });
+
+        g.bench_with_input(BenchmarkId::new("lifted", log_n), &evals, |b, evals| {
+            b.iter(|| {
+                evals
+                    .rows()
+                    .zip(&ts)
+                    .map(|(mut row, &t)| {
+                        let (lo, hi) = row.next_tuple().unwrap();
+                        let diff = (lo - hi) * EF::from_base(t);
+                        (lo + hi + beta * diff).halve()
+                    })
+                    .collect_vec()
+            })
+        });
+    }
+}
+
+/// A one-off `fold_y` of a large domain, which builds and caches the twiddles of the whole domain,
+/// against `fold_y_streaming`, which only ever holds a chunk of them. The cached twiddles take
+/// 2 MiB at `log_n = 20`; the streamed ones, a few KiB.
+fn bench_fold_y_streaming(c: &mut Criterion) {
+    let mut g = c.benchmark_group("circle_fold_y_streaming");
+    g.sample_size(10);
+    let log_n = 20;
+    let evals = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << (log_n - 1), 2);
+    let beta: EF = random();
+
+    g.bench_with_input(BenchmarkId::new("cold_cache", log_n), &evals, |b, evals| {
+        b.iter(|| FoldingTwiddles::<F>::default().fold_y(beta, evals.as_view()))
+    });
+    g.bench_with_input(BenchmarkId::new("streaming", log_n), &evals, |b, evals| {
+        b.iter(|| FoldingTwiddles::<F>::default().fold_y_streaming(beta, evals.as_view()))
+    });
+}
+
+criterion_group!(benches, bench_fold, bench_fold_y_streaming);
+criterion_main!(benches);
@@ -0,0 +1,66 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use itertools::Itertools;
+use p3_challenger::{HashChallenger, SerializingChallenger32};
+use p3_circle::{CircleDomain, CirclePcs};
+use p3_commit::{ExtensionMmcs, Pcs};
+use p3_field::extension::BinomialExtensionField;
+use p3_fri::FriConfig;
+use p3_keccak::Keccak256Hash;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_merkle_tree::MerkleTreeMmcs;
+use p3_mersenne_31::Mersenne31;
+use p3_symmetric::{CompressionFunctionFromHasher, SerializingHasher32};
+use rand::{random, thread_rng};
+
+type Val = Mersenne31;
+type Challenge = BinomialExtensionField<Val, 3>;
+
+type ByteHash = Keccak256Hash;
+type FieldHash = SerializingHasher32<ByteHash>;
+type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+type MyPcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
+
+/// Opening a committed matrix at a growing number of points. The deep quotient reduction streams
+/// the matrix once however many points there are, so only the interpolation at each point should
+/// grow with their number.
+fn bench_pcs_open(c: &mut Criterion) {
+    let byte_hash = ByteHash {};
+    let val_mmcs = ValMmcs::new(FieldHash::new(byte_hash), MyCompress::new(byte_hash));
+    let fri_config = FriConfig {
+        log_blowup: 1,
+        log_final_poly_len: 0,
+        num_queries: 100,
+        proof_of_work_bits: 0,
+        omit_zero_bit_pow: false,
+        mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+    };
+    let pcs = MyPcs::new(val_mmcs, fri_config);
+
+    let mut g = c.benchmark_group("circle_pcs_open");
+    g.sample_size(10);
+    let log_n = 16;
+    let evals = RowMajorMatrix::<Val>::rand(&mut thread_rng(), 1 << log_n, 64);
+    let (_, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(
+        &pcs,
+        vec![(CircleDomain::standard(log_n), evals)],
+    );
+    for num_points in [1, 2, 4] {
+        let points = (0..num_points).map(|_| random::<Challenge>()).collect_vec();
+        g.bench_with_input(
+            BenchmarkId::new(format!("log_n={log_n}"), num_points),
+            &points,
+            |b, points| {
+                b.iter(|| {
+                    let mut challenger = Challenger::from_hasher(vec![], byte_hash);
+                    pcs.open(vec![(&data, vec![points.clone()])], &mut challenger)
+                })
+            },
+        );
+    }
+}
+
+criterion_group!(benches, bench_pcs_open);
+criterion_main!(benches);
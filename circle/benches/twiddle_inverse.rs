@@ -0,0 +1,31 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_field::extension::ComplexExtendable;
+use p3_field::{batch_multiplicative_inverse, batch_multiplicative_inverse_packed, AbstractField};
+use p3_mersenne_31::Mersenne31;
+
+/// Inverting the y coordinates of a circle domain, as is done to compute the folding twiddles.
+fn bench_twiddle_inverse(c: &mut Criterion) {
+    type F = Mersenne31;
+
+    let mut g = c.benchmark_group("twiddle_inverse");
+    g.sample_size(10);
+    for log_n in [16, 18, 20] {
+        // A coset of the subgroup of order 2^log_n, shifted off the subgroup so that no point has
+        // y = 0.
+        let ys = F::circle_two_adic_generator(log_n)
+            .shifted_powers(F::circle_two_adic_generator(log_n + 1))
+            .take(1 << log_n)
+            .map(|p| p.imag())
+            .collect::<Vec<_>>();
+
+        g.bench_with_input(BenchmarkId::new("scalar", log_n), &ys, |b, ys| {
+            b.iter(|| batch_multiplicative_inverse(ys))
+        });
+        g.bench_with_input(BenchmarkId::new("packed", log_n), &ys, |b, ys| {
+            b.iter(|| batch_multiplicative_inverse_packed(ys))
+        });
+    }
+}
+
+criterion_group!(benches, bench_twiddle_inverse);
+criterion_main!(benches);
@@ -5,16 +5,20 @@ use itertools::{iterate, izip, Itertools};
 use p3_commit::PolynomialSpace;
 use p3_dft::{divide_by_height, Butterfly, DifButterfly, DitButterfly};
 use p3_field::extension::ComplexExtendable;
-use p3_field::{batch_multiplicative_inverse, ExtensionField, Field};
+use p3_field::{
+    batch_multiplicative_inverse, AbstractExtensionField, AbstractField, ExtensionField, Field,
+};
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
 use p3_maybe_rayon::prelude::*;
-use p3_util::{log2_ceil_usize, log2_strict_usize, reverse_slice_index_bits};
+use p3_util::{log2_strict_usize, reverse_slice_index_bits};
 use tracing::{debug_span, instrument};
 
 use crate::domain::CircleDomain;
+use crate::packing::apply_to_ext_rows;
 use crate::point::{compute_lagrange_den_batched, Point};
-use crate::{cfft_permute_index, cfft_permute_slice, CfftPermutable, CfftView};
+use crate::{cfft_permute_slice_in_place, CfftPermutable, CfftView};
 
 #[derive(Clone)]
 pub struct CircleEvaluations<F, M = RowMajorMatrix<F>> {
@@ -23,7 +27,9 @@ pub struct CircleEvaluations<F, M = RowMajorMatrix<F>> {
 }
 
 impl<F: Copy + Send + Sync, M: Matrix<F>> CircleEvaluations<F, M> {
-    pub(crate) fn from_cfft_order(domain: CircleDomain<F>, values: M) -> Self {
+    /// Wrap evaluations already in cfft order, such as a committed LDE. The transforms run on this
+    /// order directly, so e.g. `interpolate` needs no permuted copy of `values` first.
+    pub fn from_cfft_order(domain: CircleDomain<F>, values: M) -> Self {
         assert_eq!(1 << domain.log_n, values.height());
         Self { domain, values }
     }
@@ -44,47 +50,26 @@ impl<F: Copy + Send + Sync, M: Matrix<F>> CircleEvaluations<F, M> {
 impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
     #[instrument(skip_all, fields(dims = %self.values.dimensions()))]
     pub fn interpolate(self) -> RowMajorMatrix<F> {
+        let log_n = self.domain.log_n;
+        self.interpolate_with_room_for(log_n)
+    }
+
+    /// Interpolate into a buffer which already has capacity for `2^log_height` rows, so that the
+    /// coefficients can be extended to that height without reallocating.
+    fn interpolate_with_room_for(self, log_height: usize) -> RowMajorMatrix<F> {
         let CircleEvaluations { domain, values } = self;
         let mut values = debug_span!("to_rmm").in_scope(|| values.to_row_major_matrix());
+        // Growing the buffer now, while it holds nothing but the input, means there is never an
+        // input-sized matrix alive alongside the output.
+        let width = values.width();
+        values
+            .values
+            .reserve_exact((width << log_height) - values.values.len());
 
-        let mut twiddles = debug_span!("twiddles").in_scope(|| {
-            compute_twiddles(domain)
-                .into_iter()
-                .map(|ts| {
-                    batch_multiplicative_inverse(&ts)
-                        .into_iter()
-                        .map(|t| DifButterfly(t))
-                        .collect_vec()
-                })
-                .peekable()
+        interpolate_layers(domain, &mut values, |t, lo, hi| {
+            DifButterfly(t).apply_to_rows(lo, hi)
         });
 
-        assert_eq!(twiddles.len(), domain.log_n);
-
-        let par_twiddles = twiddles
-            .peeking_take_while(|ts| ts.len() >= desired_num_jobs())
-            .collect_vec();
-        if let Some(min_blks) = par_twiddles.last().map(|ts| ts.len()) {
-            let max_blk_sz = values.height() / min_blks;
-            debug_span!("par_layers", log_min_blks = log2_strict_usize(min_blks)).in_scope(|| {
-                values
-                    .par_row_chunks_exact_mut(max_blk_sz)
-                    .enumerate()
-                    .for_each(|(chunk_i, submat)| {
-                        for ts in &par_twiddles {
-                            let twiddle_chunk_sz = ts.len() / min_blks;
-                            let twiddle_chunk = &ts
-                                [(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
-                            serial_layer(submat.values, twiddle_chunk);
-                        }
-                    });
-            });
-        }
-
-        for ts in twiddles {
-            par_within_blk_layer(&mut values.values, &ts);
-        }
-
         // TODO: omit this?
         divide_by_height(&mut values);
         values
@@ -96,26 +81,15 @@ impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
         target_domain: CircleDomain<F>,
     ) -> CircleEvaluations<F, RowMajorMatrix<F>> {
         assert!(target_domain.log_n >= self.domain.log_n);
-        CircleEvaluations::<F>::evaluate(target_domain, self.interpolate())
+        let coeffs = self.interpolate_with_room_for(target_domain.log_n);
+        CircleEvaluations::<F>::evaluate(target_domain, coeffs)
     }
 
     pub fn evaluate_at_point<EF: ExtensionField<F>>(&self, point: Point<EF>) -> Vec<EF> {
-        // Compute z_H
-        let lagrange_num = self.domain.zeroifier(point);
-
         // Permute the domain to get it into the right format.
-        let permuted_points = cfft_permute_slice(&self.domain.points().collect_vec());
-
-        // Compute the lagrange denominators. This is batched as it lets us make use of batched_multiplicative_inverse.
-        let lagrange_den = compute_lagrange_den_batched(&permuted_points, point, self.domain.log_n);
-
-        // The columnwise_dot_product here consumes about 5% of the runtime for example prove_poseidon2_m31_keccak.
-        // Definately something worth optimising further.
-        self.values
-            .columnwise_dot_product(&lagrange_den)
-            .into_iter()
-            .map(|x| x * lagrange_num)
-            .collect_vec()
+        let mut permuted_points = self.domain.points().collect_vec();
+        cfft_permute_slice_in_place(&mut permuted_points);
+        barycentric_evaluate(&self.values, self.domain, &permuted_points, point)
     }
 
     #[cfg(test)]
@@ -136,95 +110,300 @@ impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
 impl<F: ComplexExtendable> CircleEvaluations<F, RowMajorMatrix<F>> {
     #[instrument(skip_all, fields(dims = %coeffs.dimensions()))]
     pub fn evaluate(domain: CircleDomain<F>, mut coeffs: RowMajorMatrix<F>) -> Self {
-        let log_n = log2_strict_usize(coeffs.height());
-        assert!(log_n <= domain.log_n);
-
-        if log_n < domain.log_n {
-            // We could simply pad coeffs like this:
-            // coeffs.pad_to_height(target_domain.size(), F::ZERO);
-            // But the first `added_bits` layers will simply fill out the zeros
-            // with the lower order values. (In `DitButterfly`, `x_2` is 0, so
-            // both `x_1` and `x_2` are set to `x_1`).
-            // So instead we directly repeat the coeffs and skip the initial layers.
-            debug_span!("extend coeffs").in_scope(|| {
-                coeffs.values.reserve(domain.size() * coeffs.width());
-                for _ in log_n..domain.log_n {
-                    coeffs.values.extend_from_within(..);
-                }
-            });
-        }
-        assert_eq!(coeffs.height(), 1 << domain.log_n);
-
-        let mut twiddles = debug_span!("twiddles").in_scope(|| {
-            compute_twiddles(domain)
-                .into_iter()
-                .map(|ts| ts.into_iter().map(|t| DitButterfly(t)).collect_vec())
-                .rev()
-                .skip(domain.log_n - log_n)
-                .peekable()
+        let log_n = repeat_coeffs(domain, &mut coeffs);
+        evaluate_layers(domain, &mut coeffs, log_n, |t, lo, hi| {
+            DitButterfly(t).apply_to_rows(lo, hi)
         });
+        Self::from_cfft_order(domain, coeffs)
+    }
 
-        for ts in twiddles.peeking_take_while(|ts| ts.len() < desired_num_jobs()) {
-            par_within_blk_layer(&mut coeffs.values, &ts);
-        }
+    /// Split these evaluations, e.g. of a quotient over a domain larger than the trace, into those
+    /// over each of the `num_chunks` twin-cosets from `split_domains`, each in cfft order.
+    pub fn split(self, num_chunks: usize) -> Vec<Self> {
+        let domain = self.domain;
+        let evals = self.to_natural_order().to_row_major_matrix();
+        izip!(
+            domain.split_domains(num_chunks),
+            domain.split_evals(num_chunks, evals)
+        )
+        .map(|(d, evals)| Self::from_cfft_order(d, evals.cfft_perm_rows().to_row_major_matrix()))
+        .collect()
+    }
 
-        let par_twiddles = twiddles.collect_vec();
-        if let Some(min_blks) = par_twiddles.first().map(|ts| ts.len()) {
-            let max_blk_sz = coeffs.height() / min_blks;
-            debug_span!("par_layers", log_min_blks = log2_strict_usize(min_blks)).in_scope(|| {
-                coeffs
-                    .par_row_chunks_exact_mut(max_blk_sz)
-                    .enumerate()
-                    .for_each(|(chunk_i, submat)| {
-                        for ts in &par_twiddles {
-                            let twiddle_chunk_sz = ts.len() / min_blks;
-                            let twiddle_chunk = &ts
-                                [(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
-                            serial_layer(submat.values, twiddle_chunk);
-                        }
-                    });
-            });
-        }
+    /// The inverse of `split`, recombining the chunks into evaluations over `domain`.
+    pub fn recombine(domain: CircleDomain<F>, chunks: Vec<Self>) -> Self {
+        assert_eq!(
+            chunks.iter().map(|c| c.domain).collect_vec(),
+            domain.split_domains(chunks.len()),
+            "chunks must be over the split domains of `domain`"
+        );
+        let chunks = chunks
+            .into_iter()
+            .map(|c| c.to_natural_order().to_row_major_matrix())
+            .collect();
+        let evals = domain.combine_evals(chunks);
+        Self::from_cfft_order(domain, evals.cfft_perm_rows().to_row_major_matrix())
+    }
+}
 
-        Self::from_cfft_order(domain, coeffs)
+/// Run the interpolation butterflies over `values`, given in cfft order, leaving the coefficients
+/// scaled up by the height. `dif` applies a DIF butterfly with the given twiddle to a pair of rows.
+fn interpolate_layers<F: ComplexExtendable, T: Clone + Send + Sync>(
+    domain: CircleDomain<F>,
+    values: &mut RowMajorMatrix<T>,
+    dif: impl Fn(F, &mut [T], &mut [T]) + Sync,
+) {
+    let mut twiddles = debug_span!("twiddles").in_scope(|| {
+        compute_twiddles(domain)
+            .into_iter()
+            .map(|ts| batch_multiplicative_inverse(&ts))
+            .collect_vec()
+    });
+
+    assert_eq!(twiddles.len(), domain.log_n);
+
+    // The layers run from the smallest blocks to the largest.
+    let mid = domain.log_n.div_ceil(2);
+    let second_half_twiddles = twiddles.split_off(mid);
+    first_half(values, mid, &twiddles, &dif);
+    if !second_half_twiddles.is_empty() {
+        reverse_matrix_index_bits(values);
+        second_half(values, mid, second_half_twiddles, &dif);
+        reverse_matrix_index_bits(values);
+    }
+}
+
+/// Repeat `coeffs` up to the size of `domain`, returning the log of their original height.
+fn repeat_coeffs<F, T: Clone + Send + Sync>(
+    domain: CircleDomain<F>,
+    coeffs: &mut RowMajorMatrix<T>,
+) -> usize {
+    let log_n = log2_strict_usize(coeffs.height());
+    assert!(log_n <= domain.log_n);
+
+    if log_n < domain.log_n {
+        // We could simply pad coeffs like this:
+        // coeffs.pad_to_height(target_domain.size(), Padding::Zero);
+        // But the first `added_bits` layers will simply fill out the zeros
+        // with the lower order values. (In `DitButterfly`, `x_2` is 0, so
+        // both `x_1` and `x_2` are set to `x_1`).
+        // So instead we directly repeat the coeffs and skip the initial layers.
+        debug_span!("extend coeffs").in_scope(|| {
+            let lde_len = coeffs.width() << domain.log_n;
+            coeffs.values.reserve_exact(lde_len - coeffs.values.len());
+            for _ in log_n..domain.log_n {
+                coeffs.values.extend_from_within(..);
+            }
+        });
+    }
+    assert_eq!(coeffs.height(), 1 << domain.log_n);
+    log_n
+}
+
+/// Run the evaluation butterflies over `coeffs`, the first `2^log_n` coefficients repeated by
+/// `repeat_coeffs`, leaving the evaluations in cfft order. `dit` applies a DIT butterfly with the
+/// given twiddle to a pair of rows.
+fn evaluate_layers<F: ComplexExtendable, T: Clone + Send + Sync>(
+    domain: CircleDomain<F>,
+    coeffs: &mut RowMajorMatrix<T>,
+    log_n: usize,
+    dit: impl Fn(F, &mut [T], &mut [T]) + Sync,
+) {
+    let mut twiddles = debug_span!("twiddles").in_scope(|| {
+        compute_twiddles(domain)
+            .into_iter()
+            // The layers for the repeated coefficients have nothing left to do.
+            .take(log_n)
+            .collect_vec()
+    });
+
+    // The layers run from the largest blocks to the smallest.
+    let mid = domain.log_n.div_ceil(2);
+    let mut second_half_twiddles = twiddles.split_off(mid.min(log_n));
+    if !second_half_twiddles.is_empty() {
+        second_half_twiddles.reverse();
+        reverse_matrix_index_bits(coeffs);
+        second_half(coeffs, mid, second_half_twiddles, &dit);
+        reverse_matrix_index_bits(coeffs);
     }
+    twiddles.reverse();
+    first_half(coeffs, mid, &twiddles, &dit);
+}
+
+/// Apply the layers whose blocks span at most `2^mid` rows, in the given order. Each thread takes
+/// a contiguous chunk of `2^mid` rows through all of them, like `Radix2DitParallel`.
+#[instrument(level = "debug", skip_all)]
+fn first_half<F: Field, T: Clone + Send + Sync>(
+    mat: &mut RowMajorMatrix<T>,
+    mid: usize,
+    layers: &[Vec<F>],
+    butterfly: &(impl Fn(F, &mut [T], &mut [T]) + Sync),
+) {
+    let log_chunks = log2_strict_usize(mat.height()) - mid;
+    mat.par_row_chunks_mut(1 << mid)
+        .enumerate()
+        .for_each(|(chunk_i, submat)| {
+            for ts in layers {
+                let twiddle_chunk_sz = ts.len() >> log_chunks;
+                let twiddle_chunk =
+                    &ts[(twiddle_chunk_sz * chunk_i)..(twiddle_chunk_sz * (chunk_i + 1))];
+                serial_layer(submat.values, twiddle_chunk, butterfly);
+            }
+        });
+}
+
+/// Apply the layers whose blocks span more than `2^mid` rows, in the given order, to a matrix whose
+/// rows have been bit-reversed.
+///
+/// Bit-reversing the rows turns each of these layers into one whose blocks span at most
+/// `2^(log_h - mid)` rows, so each thread can again take a contiguous chunk through all of them.
+/// The twiddle now depends on a row's position within its block rather than on the block, and is
+/// found by bit-reversing that position.
+#[instrument(level = "debug", skip_all)]
+fn second_half<F: Field, T: Clone + Send + Sync>(
+    mat: &mut RowMajorMatrix<T>,
+    mid: usize,
+    layers: Vec<Vec<F>>,
+    butterfly: &(impl Fn(F, &mut [T], &mut [T]) + Sync),
+) {
+    let log_h = log2_strict_usize(mat.height());
+    let width = mat.width();
+    let layers = layers
+        .into_iter()
+        .map(|mut ts| {
+            reverse_slice_index_bits(&mut ts);
+            ts
+        })
+        .collect_vec();
+    mat.par_row_chunks_mut(1 << (log_h - mid))
+        .for_each(|submat| {
+            for ts in &layers {
+                bitrev_layer(submat.values, width, ts, butterfly);
+            }
+        });
 }
 
 #[inline]
-fn serial_layer<F: Field, B: Butterfly<F>>(values: &mut [F], twiddles: &[B]) {
+fn serial_layer<F: Field, T>(
+    values: &mut [T],
+    twiddles: &[F],
+    butterfly: &impl Fn(F, &mut [T], &mut [T]),
+) {
     let blk_sz = values.len() / twiddles.len();
     for (&t, blk) in izip!(twiddles, values.chunks_exact_mut(blk_sz)) {
         let (lo, hi) = blk.split_at_mut(blk_sz / 2);
-        t.apply_to_rows(lo, hi);
+        butterfly(t, lo, hi);
     }
 }
 
+/// A layer on bit-reversed rows, where blocks span twice as many rows as there are twiddles, and
+/// the `i`th row of each block's lower half is paired with the `i`th row of its upper half using
+/// the `i`th twiddle.
 #[inline]
-#[instrument(level = "debug", skip_all, fields(log_blks = log2_strict_usize(twiddles.len())))]
-fn par_within_blk_layer<F: Field, B: Butterfly<F>>(values: &mut [F], twiddles: &[B]) {
-    let blk_sz = values.len() / twiddles.len();
-    for (&t, blk) in izip!(twiddles, values.chunks_exact_mut(blk_sz)) {
-        let (lo, hi) = blk.split_at_mut(blk_sz / 2);
-        let job_sz = core::cmp::max(1, lo.len() >> log2_ceil_usize(desired_num_jobs()));
-        lo.par_chunks_mut(job_sz)
-            .zip(hi.par_chunks_mut(job_sz))
-            .for_each(|(lo_job, hi_job)| t.apply_to_rows(lo_job, hi_job));
+fn bitrev_layer<F: Field, T>(
+    values: &mut [T],
+    width: usize,
+    twiddles: &[F],
+    butterfly: &impl Fn(F, &mut [T], &mut [T]),
+) {
+    let half_blk_sz = twiddles.len() * width;
+    for blk in values.chunks_exact_mut(2 * half_blk_sz) {
+        let (lo, hi) = blk.split_at_mut(half_blk_sz);
+        for (&t, lo, hi) in izip!(
+            twiddles,
+            lo.chunks_exact_mut(width),
+            hi.chunks_exact_mut(width)
+        ) {
+            butterfly(t, lo, hi);
+        }
     }
 }
 
+/// A DIF butterfly on rows of extension field elements, with a twiddle from the base field. Runs
+/// on packed coordinates where the rows are wide enough.
+fn dif_algebra<F: Field, EF: ExtensionField<F>>(t: F, row_1: &mut [EF], row_2: &mut [EF]) {
+    let packed_t = F::Packing::from(t);
+    apply_to_ext_rows::<F, EF>(
+        row_1,
+        row_2,
+        |x_1, x_2| dif(x_1, x_2, packed_t),
+        |x_1, x_2| dif(x_1, x_2, t),
+    );
+}
+
+/// A DIT butterfly on rows of extension field elements, with a twiddle from the base field. Runs
+/// on packed coordinates where the rows are wide enough.
+fn dit_algebra<F: Field, EF: ExtensionField<F>>(t: F, row_1: &mut [EF], row_2: &mut [EF]) {
+    let packed_t = F::Packing::from(t);
+    apply_to_ext_rows::<F, EF>(
+        row_1,
+        row_2,
+        |x_1, x_2| dit(x_1, x_2, packed_t),
+        |x_1, x_2| dit(x_1, x_2, t),
+    );
+}
+
+#[inline]
+fn dif<B: AbstractField, A: AbstractExtensionField<B>>(x_1: A, x_2: A, t: B) -> (A, A) {
+    (x_1.clone() + x_2.clone(), (x_1 - x_2) * t)
+}
+
 #[inline]
-fn desired_num_jobs() -> usize {
-    16 * current_num_threads()
+fn dit<B: AbstractField, A: AbstractExtensionField<B>>(x_1: A, x_2: A, t: B) -> (A, A) {
+    let x_2_twiddle = x_2 * t;
+    (x_1.clone() + x_2_twiddle.clone(), x_1 - x_2_twiddle)
 }
 
 impl<F: ComplexExtendable> CircleDomain<F> {
+    /// Like `CircleEvaluations::interpolate`, for evaluations of extension field elements given in
+    /// cfft order. The butterflies run on the `EF` entries directly, with twiddles in `F`, so
+    /// there's no need to flatten the matrix into `F` columns first.
+    #[instrument(skip_all, fields(dims = %values.dimensions()))]
+    pub fn cfft_algebra<EF: ExtensionField<F>>(
+        &self,
+        mut values: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        assert_eq!(1 << self.log_n, values.height());
+        interpolate_layers(*self, &mut values, dif_algebra::<F, EF>);
+        let inv_height = F::from_canonical_usize(values.height()).inverse();
+        values.values.par_iter_mut().for_each(|x| *x *= inv_height);
+        values
+    }
+
+    /// Like `CircleEvaluations::evaluate`, for coefficients in an extension field. The evaluations
+    /// are returned in cfft order.
+    #[instrument(skip_all, fields(dims = %coeffs.dimensions()))]
+    pub fn icfft_algebra<EF: ExtensionField<F>>(
+        &self,
+        mut coeffs: RowMajorMatrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        let log_n = repeat_coeffs(*self, &mut coeffs);
+        evaluate_layers(*self, &mut coeffs, log_n, dit_algebra::<F, EF>);
+        coeffs
+    }
+
+    /// Like `CircleEvaluations::extrapolate`, for evaluations of extension field elements over
+    /// this domain, given in cfft order. The evaluations over `target_domain` are returned in cfft
+    /// order.
+    pub fn lde_algebra<EF: ExtensionField<F>>(
+        &self,
+        values: RowMajorMatrix<EF>,
+        target_domain: Self,
+    ) -> RowMajorMatrix<EF> {
+        assert!(target_domain.log_n >= self.log_n);
+        target_domain.icfft_algebra(self.cfft_algebra(values))
+    }
+
     pub(crate) fn y_twiddles(&self) -> Vec<F> {
         let mut ys = self.coset0().map(|p| p.y).collect_vec();
         reverse_slice_index_bits(&mut ys);
         ys
     }
+    /// Single twiddles are looked up through `FoldingTwiddles`; this is the reference it is
+    /// tested against.
+    #[cfg(test)]
     pub(crate) fn nth_y_twiddle(&self, index: usize) -> F {
-        self.nth_point(cfft_permute_index(index << 1, self.log_n)).y
+        self.point_at_cfft(index << 1).y
     }
     pub(crate) fn x_twiddles(&self, layer: usize) -> Vec<F> {
         let gen = self.gen() * (1 << layer);
@@ -236,18 +415,36 @@ impl<F: ComplexExtendable> CircleDomain<F> {
         reverse_slice_index_bits(&mut xs);
         xs
     }
+    /// Single twiddles are looked up through `FoldingTwiddles`; this is the reference it is
+    /// tested against.
+    #[cfg(test)]
     pub(crate) fn nth_x_twiddle(&self, index: usize) -> F {
         (self.shift + self.gen() * index).x
     }
+    /// `y_twiddles()` and `x_twiddles(0)` together, from a single walk over the first coset and a
+    /// single bit reversal of its points. The x twiddles are empty when `log_n = 1`.
+    pub(crate) fn y_and_x_twiddles(&self) -> (Vec<F>, Vec<F>) {
+        let mut pts = self.coset0().collect_vec();
+        reverse_slice_index_bits(&mut pts);
+        let ys = pts.iter().map(|p| p.y).collect_vec();
+        // The first half of the coset, which the x twiddles walk over, sits at the even indices
+        // once bit reversed.
+        let xs = pts
+            .iter()
+            .step_by(2)
+            .take(pts.len() / 2)
+            .map(|p| p.x)
+            .collect_vec();
+        (ys, xs)
+    }
 }
 
 fn compute_twiddles<F: ComplexExtendable>(domain: CircleDomain<F>) -> Vec<Vec<F>> {
     assert!(domain.log_n >= 1);
-    let mut pts = domain.coset0().collect_vec();
-    reverse_slice_index_bits(&mut pts);
-    let mut twiddles = vec![pts.iter().map(|p| p.y).collect_vec()];
+    let (ys, xs) = domain.y_and_x_twiddles();
+    let mut twiddles = vec![ys];
     if domain.log_n >= 2 {
-        twiddles.push(pts.iter().step_by(2).map(|p| p.x).collect_vec());
+        twiddles.push(xs);
         for i in 0..(domain.log_n - 2) {
             let prev = twiddles.last().unwrap();
             assert_eq!(prev.len(), 1 << (domain.log_n - 2 - i));
@@ -262,6 +459,92 @@ fn compute_twiddles<F: ComplexExtendable>(domain: CircleDomain<F>) -> Vec<Vec<F>
     twiddles
 }
 
+/// Evaluate the polynomials whose values over `domain`, in natural order, are the columns of
+/// `evals`, at the out-of-domain `point = (x, y)`, using the circle barycentric formula.
+///
+/// If `point` happens to lie in `domain`, its row of `evals` is returned exactly.
+pub fn interpolate_circle_domain<F: ComplexExtendable, EF: ExtensionField<F>>(
+    evals: &impl Matrix<F>,
+    domain: CircleDomain<F>,
+    point: (EF, EF),
+) -> Vec<EF> {
+    let points = domain.points().collect_vec();
+    barycentric_evaluate(evals, domain, &points, Point::new(point.0, point.1))
+}
+
+/// Row `i` of `evals` holds the values at `points[i]`, which must enumerate `domain` in some
+/// order. The rows are only read once, top to bottom, so `evals` may be a permuted view.
+fn barycentric_evaluate<F: ComplexExtendable, EF: ExtensionField<F>>(
+    evals: &impl Matrix<F>,
+    domain: CircleDomain<F>,
+    points: &[Point<F>],
+    at: Point<EF>,
+) -> Vec<EF> {
+    assert_eq!(evals.height(), points.len());
+
+    // Within the domain both z_H and some denominators vanish, so read the value off directly.
+    if let Some(i) = points
+        .iter()
+        .position(|p| at.x == EF::from_base(p.x) && at.y == EF::from_base(p.y))
+    {
+        return evals.row(i).map(EF::from_base).collect();
+    }
+
+    // Compute z_H
+    let lagrange_num = domain.vanishing_poly_at_point(at);
+
+    // Compute the lagrange denominators. This is batched as it lets us make use of batched_multiplicative_inverse.
+    let lagrange_den = compute_lagrange_den_batched(points, at, domain.log_n);
+
+    // The columnwise_dot_product here consumes about 5% of the runtime for example prove_poseidon2_m31_keccak.
+    // Definately something worth optimising further.
+    evals
+        .columnwise_dot_product(&lagrange_den)
+        .into_iter()
+        .map(|x| x * lagrange_num)
+        .collect_vec()
+}
+
+/// Evaluate the polynomial with the given coefficients in the circle basis, as produced by
+/// `interpolate`, at `point`.
+///
+/// Basis element `i` is the product of `y` if bit 0 of `i` is set, and of `π^(k-1)(x)` for each
+/// higher bit `k` that is set, where `π(x) = 2x^2 - 1`. The coefficients are folded one bit at a
+/// time, starting from the lowest, so this takes a single pass without building the basis.
+pub fn evaluate_cfft_poly<F: Field, EF: ExtensionField<F>>(coeffs: &[F], point: Point<EF>) -> EF {
+    assert!(coeffs.len().is_power_of_two());
+    if coeffs.len() == 1 {
+        return EF::from_base(coeffs[0]);
+    }
+    let mut folded = coeffs
+        .chunks_exact(2)
+        .map(|c| point.y * c[1] + c[0])
+        .collect_vec();
+    let mut x = point.x;
+    while folded.len() > 1 {
+        let half = folded.len() / 2;
+        for i in 0..half {
+            folded[i] = folded[2 * i] + x * folded[2 * i + 1];
+        }
+        folded.truncate(half);
+        x = x.square().double() - EF::ONE;
+    }
+    folded[0]
+}
+
+/// Evaluate each column of `coeffs`, in the circle basis, at `point`. See [`evaluate_cfft_poly`].
+pub fn evaluate_cfft_poly_batched<F: Field, EF: ExtensionField<F>>(
+    coeffs: &impl Matrix<F>,
+    point: Point<EF>,
+) -> Vec<EF> {
+    let height = coeffs.height();
+    assert!(height.is_power_of_two());
+    if height == 1 {
+        return coeffs.row(0).map(EF::from_base).collect();
+    }
+    coeffs.columnwise_dot_product(&circle_basis(point, log2_strict_usize(height)))
+}
+
 pub fn circle_basis<F: Field>(p: Point<F>, log_n: usize) -> Vec<F> {
     let mut b = vec![F::ONE, p.y];
     let mut x = p.x;
@@ -279,6 +562,8 @@ pub fn circle_basis<F: Field>(p: Point<F>, log_n: usize) -> Vec<F> {
 mod tests {
     use itertools::iproduct;
     use p3_field::extension::BinomialExtensionField;
+    use p3_field::{AbstractExtensionField, PackedValue};
+    use p3_matrix::dense::Padding;
     use p3_mersenne_31::Mersenne31;
     use rand::{random, thread_rng};
 
@@ -287,6 +572,139 @@ mod tests {
     type F = Mersenne31;
     type EF = BinomialExtensionField<F, 3>;
 
+    /// The inverse CFFT one layer at a time over the whole matrix, without any reordering.
+    fn serial_interpolate(
+        domain: CircleDomain<F>,
+        mut values: RowMajorMatrix<F>,
+    ) -> RowMajorMatrix<F> {
+        for ts in compute_twiddles(domain) {
+            serial_layer(
+                &mut values.values,
+                &batch_multiplicative_inverse(&ts),
+                &|t, lo, hi| DifButterfly(t).apply_to_rows(lo, hi),
+            );
+        }
+        divide_by_height(&mut values);
+        values
+    }
+
+    /// The CFFT one layer at a time over the whole matrix, without any reordering.
+    fn serial_evaluate(
+        domain: CircleDomain<F>,
+        mut coeffs: RowMajorMatrix<F>,
+    ) -> RowMajorMatrix<F> {
+        coeffs.pad_to_height(domain.size(), Padding::Zero);
+        for ts in compute_twiddles(domain).into_iter().rev() {
+            serial_layer(&mut coeffs.values, &ts, &|t, lo, hi| {
+                DitButterfly(t).apply_to_rows(lo, hi)
+            });
+        }
+        coeffs
+    }
+
+    #[test]
+    fn parallel_layers_match_serial() {
+        for (log_n, width) in iproduct!(1..12, [1, 3, 16]) {
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (random::<usize>() | 1);
+            let domain = CircleDomain::<F>::new(log_n, shift);
+            let values = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, width);
+
+            let coeffs = CircleEvaluations::from_cfft_order(domain, values.clone()).interpolate();
+            assert_eq!(coeffs, serial_interpolate(domain, values));
+
+            for added_bits in 0..3 {
+                let lde_domain = CircleDomain::<F>::new(log_n + added_bits, shift);
+                assert_eq!(
+                    CircleEvaluations::evaluate(lde_domain, coeffs.clone()).to_cfft_order(),
+                    serial_evaluate(lde_domain, coeffs.clone()),
+                    "log_n = {log_n}, width = {width}, added_bits = {added_bits}",
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn y_and_x_twiddles_match_separate() {
+        for log_n in 1..10 {
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (random::<usize>() | 1);
+            for domain in [
+                CircleDomain::<F>::standard(log_n),
+                CircleDomain::new(log_n, shift),
+            ] {
+                let (ys, xs) = domain.y_and_x_twiddles();
+                assert_eq!(ys, domain.y_twiddles());
+                if log_n == 1 {
+                    assert!(xs.is_empty());
+                } else {
+                    assert_eq!(xs, domain.x_twiddles(0));
+                }
+            }
+        }
+    }
+
+    /// Reinterpret each run of `EF::D` base field columns as one extension field column.
+    fn unflatten(mat: RowMajorMatrix<F>) -> RowMajorMatrix<EF> {
+        let width = mat.width() / <EF as AbstractExtensionField<F>>::D;
+        RowMajorMatrix::new(
+            mat.values
+                .chunks_exact(<EF as AbstractExtensionField<F>>::D)
+                .map(EF::from_base_slice)
+                .collect(),
+            width,
+        )
+    }
+
+    #[test]
+    fn packed_algebra_butterflies_match_scalar() {
+        let packing_width = <F as Field>::Packing::WIDTH;
+        for len in 0..=3 * packing_width + 1 {
+            let t: F = random();
+            let row_1: Vec<EF> = (0..len).map(|_| random()).collect();
+            let row_2: Vec<EF> = (0..len).map(|_| random()).collect();
+
+            let (mut dif_1, mut dif_2) = (row_1.clone(), row_2.clone());
+            dif_algebra(t, &mut dif_1, &mut dif_2);
+            let (mut dit_1, mut dit_2) = (row_1.clone(), row_2.clone());
+            dit_algebra(t, &mut dit_1, &mut dit_2);
+
+            for (i, (&x_1, &x_2)) in izip!(&row_1, &row_2).enumerate() {
+                assert_eq!((dif_1[i], dif_2[i]), (x_1 + x_2, (x_1 - x_2) * t));
+                assert_eq!((dit_1[i], dit_2[i]), (x_1 + x_2 * t, x_1 - x_2 * t));
+            }
+        }
+    }
+
+    #[test]
+    fn algebra_matches_flattened() {
+        // Widths both narrower and wider than any packing.
+        for (log_n, width, shifted) in iproduct!(1..9, [1, 5, 17], [false, true]) {
+            let domain = if shifted {
+                let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (random::<usize>() | 1);
+                CircleDomain::<F>::new(log_n, shift)
+            } else {
+                CircleDomain::<F>::standard(log_n)
+            };
+            let values = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_n, width);
+
+            let coeffs = domain.cfft_algebra(values.clone());
+            let flat_coeffs =
+                CircleEvaluations::from_cfft_order(domain, values.flatten_to_base::<F>())
+                    .interpolate();
+            assert_eq!(coeffs, unflatten(flat_coeffs.clone()));
+
+            assert_eq!(domain.icfft_algebra(coeffs), values);
+
+            for log_blowup in 1..3 {
+                let lde_domain = CircleDomain::standard(log_n + log_blowup);
+                let flat_lde = CircleEvaluations::evaluate(lde_domain, flat_coeffs.clone());
+                assert_eq!(
+                    domain.lde_algebra(values.clone(), lde_domain),
+                    unflatten(flat_lde.to_cfft_order()),
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_cfft_icfft() {
         for (log_n, width) in iproduct!(2..5, [1, 4, 11]) {
@@ -311,16 +729,34 @@ mod tests {
         }
     }
 
+    #[test]
+    fn interpolate_from_cfft_order() {
+        for (log_n, width) in iproduct!(1..9, [1, 4, 11]) {
+            let domain = CircleDomain::<F>::standard(log_n);
+            let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, width);
+            let cfft_ordered = evals.clone().cfft_perm_rows().to_row_major_matrix();
+            assert_eq!(
+                CircleEvaluations::from_cfft_order(domain, cfft_ordered).interpolate(),
+                CircleEvaluations::from_natural_order(domain, evals).interpolate(),
+                "log_n = {log_n}, width = {width}"
+            );
+        }
+    }
+
     #[test]
     fn test_extrapolation() {
-        for (log_n, log_blowup) in iproduct!(2..5, [1, 2, 3]) {
+        for (log_n, log_blowup, shifted) in iproduct!(2..5, [1, 2, 3], [false, true]) {
             let evals = CircleEvaluations::<F>::from_natural_order(
                 CircleDomain::standard(log_n),
                 RowMajorMatrix::rand(&mut thread_rng(), 1 << log_n, 11),
             );
-            let lde = evals
-                .clone()
-                .extrapolate(CircleDomain::standard(log_n + log_blowup));
+            let target_domain = if shifted {
+                let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (random::<usize>() | 1);
+                CircleDomain::new(log_n + log_blowup, shift)
+            } else {
+                CircleDomain::standard(log_n + log_blowup)
+            };
+            let lde = evals.clone().extrapolate(target_domain);
 
             let coeffs = evals.interpolate();
             let lde_coeffs = lde.interpolate();
@@ -334,6 +770,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn extrapolation_matches_zero_padding() {
+        for (log_n, log_blowup) in iproduct!(2..8, [1, 2, 3]) {
+            let domain = CircleDomain::<F>::standard(log_n);
+            let target_domain = CircleDomain::standard(log_n + log_blowup);
+            let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, 5);
+
+            let lde = CircleEvaluations::from_natural_order(domain, evals.clone())
+                .extrapolate(target_domain)
+                .to_cfft_order();
+            // The LDE fills the buffer it was interpolated in, without spare room.
+            assert_eq!(lde.values.len(), lde.values.capacity());
+
+            // Evaluating the zero-padded coefficients runs every layer, rather than skipping the
+            // ones which only repeat the coefficients.
+            let mut coeffs = CircleEvaluations::from_natural_order(domain, evals).interpolate();
+            coeffs.pad_to_height(target_domain.size(), Padding::Zero);
+            assert_eq!(
+                lde,
+                CircleEvaluations::evaluate(target_domain, coeffs).to_cfft_order(),
+                "log_n = {log_n}, log_blowup = {log_blowup}"
+            );
+        }
+    }
+
     #[test]
     fn eval_at_point_matches_cfft() {
         for (log_n, width) in iproduct!(2..5, [1, 4, 11]) {
@@ -378,4 +839,103 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn evaluate_cfft_poly_on_domain() {
+        for (log_n, width) in iproduct!(2..6, [1, 4]) {
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * random();
+            let domain = CircleDomain::<F>::new(log_n, shift);
+            let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, width);
+            let coeffs = CircleEvaluations::from_natural_order(domain, evals.clone()).interpolate();
+            let columns = (0..width)
+                .map(|c| {
+                    coeffs
+                        .rows()
+                        .map(|mut row| row.nth(c).unwrap())
+                        .collect_vec()
+                })
+                .collect_vec();
+
+            for (i, pt) in domain.points().enumerate() {
+                let pt = Point::new(EF::from_base(pt.x), EF::from_base(pt.y));
+                let expected = evals.row(i).map(EF::from_base).collect_vec();
+                for (col, &y) in columns.iter().zip(&expected) {
+                    assert_eq!(evaluate_cfft_poly(col, pt), y, "log_n = {log_n}");
+                }
+                assert_eq!(evaluate_cfft_poly_batched(&coeffs, pt), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn evaluate_cfft_poly_off_domain() {
+        for (log_n, width) in iproduct!(2..8, [1, 4, 11]) {
+            let evals = CircleEvaluations::<F>::from_natural_order(
+                CircleDomain::standard(log_n),
+                RowMajorMatrix::rand(&mut thread_rng(), 1 << log_n, width),
+            );
+            let coeffs = evals.clone().interpolate();
+            let pt = Point::<EF>::from_projective_line(random());
+
+            let expected = evals.evaluate_at_point(pt);
+            for (c, &y) in expected.iter().enumerate() {
+                let col = coeffs
+                    .rows()
+                    .map(|mut row| row.nth(c).unwrap())
+                    .collect_vec();
+                assert_eq!(evaluate_cfft_poly(&col, pt), y, "log_n = {log_n}");
+            }
+            assert_eq!(evaluate_cfft_poly_batched(&coeffs, pt), expected);
+        }
+    }
+
+    #[test]
+    fn split_and_recombine() {
+        for (log_n, log_chunks) in iproduct!(3..8, 0..3) {
+            let domain = CircleDomain::<F>::standard(log_n);
+            let evals = CircleEvaluations::<F>::from_cfft_order(
+                domain,
+                RowMajorMatrix::rand(&mut thread_rng(), 1 << log_n, 3),
+            );
+            let chunks = evals.clone().split(1 << log_chunks);
+            assert_eq!(chunks.len(), 1 << log_chunks);
+            // Each chunk pairs its rows with the points of its domain in cfft order.
+            for chunk in &chunks {
+                assert_eq!(chunk.domain.log_n, log_n - log_chunks);
+                for (i, row) in chunk.values.rows().enumerate() {
+                    let j = domain.index_of_cfft(chunk.domain.point_at_cfft(i)).unwrap();
+                    assert!(row.eq(evals.values.row(j)));
+                }
+            }
+            assert_eq!(
+                CircleEvaluations::recombine(domain, chunks).to_cfft_order(),
+                evals.to_cfft_order(),
+                "log_n = {log_n}, log_chunks = {log_chunks}"
+            );
+        }
+    }
+
+    #[test]
+    fn interpolate_circle_domain_matches_cfft() {
+        for (log_n, width) in iproduct!(2..7, [1, 4, 11]) {
+            let domain = CircleDomain::standard(log_n);
+            let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, width);
+            let coeffs = CircleEvaluations::from_natural_order(domain, evals.clone()).interpolate();
+
+            let pt = Point::<EF>::from_projective_line(random());
+            assert_eq!(
+                interpolate_circle_domain(&evals, domain, (pt.x, pt.y)),
+                coeffs.columnwise_dot_product(&circle_basis(pt, log_n))
+            );
+
+            // Points in the domain give back their row exactly.
+            for (i, p) in domain.points().enumerate() {
+                let pt = (EF::from_base(p.x), EF::from_base(p.y));
+                assert_eq!(
+                    interpolate_circle_domain(&evals, domain, pt),
+                    evals.row(i).map(EF::from_base).collect_vec()
+                );
+            }
+        }
+    }
 }
@@ -10,7 +10,7 @@ use tracing::instrument;
 
 use crate::domain::CircleDomain;
 use crate::point::Point;
-use crate::{cfft_permute_slice, CircleEvaluations};
+use crate::{cfft_permute_slice_in_place, CircleEvaluations};
 
 /// Compute numerator and denominator of the "vanishing part" of the DEEP quotient
 /// Section 6, Remark 21 of Circle Starks (page 30 of first edition PDF)
@@ -46,29 +46,69 @@ pub(crate) fn deep_quotient_reduce_row<F: ComplexExtendable, EF: ExtensionField<
 
 impl<F: ComplexExtendable, M: Matrix<F>> CircleEvaluations<F, M> {
     /// Same as `deep_quotient_reduce_row`, but reduces a whole matrix into a column, taking advantage of batch inverses.
-    #[instrument(skip_all, fields(dims = %self.values.dimensions()))]
+    #[cfg(test)]
     pub(crate) fn deep_quotient_reduce<EF: ExtensionField<F>>(
         &self,
         alpha: EF,
         zeta: Point<EF>,
         ps_at_zeta: &[EF],
     ) -> Vec<EF> {
+        self.deep_quotient_reduce_multi(alpha, &[(zeta, ps_at_zeta.to_vec())], &[EF::ONE])
+    }
+
+    /// The sum of `deep_quotient_reduce` at each of several points, scaled by the matching entry
+    /// of `scales`.
+    ///
+    /// The matrix is reduced with powers of α once for all the points, and the denominators at
+    /// every point are inverted in one batch, so the work is O(height × (width + points)) rather
+    /// than O(height × width × points).
+    #[instrument(skip_all, fields(dims = %self.values.dimensions(), points = openings.len()))]
+    pub(crate) fn deep_quotient_reduce_multi<EF: ExtensionField<F>>(
+        &self,
+        alpha: EF,
+        openings: &[(Point<EF>, Vec<EF>)],
+        scales: &[EF],
+    ) -> Vec<EF> {
+        assert!(!openings.is_empty(), "at least one point is needed");
+        assert_eq!(openings.len(), scales.len());
+        let num_points = openings.len();
+
         let alpha_pow_width = alpha.exp_u64(self.values.width() as u64);
-        let points = cfft_permute_slice(&self.domain.points().collect_vec());
+        let mut points = self.domain.points().collect_vec();
+        cfft_permute_slice_in_place(&mut points);
+        // Laid out by row, then by opening point.
         let (vp_nums, vp_denoms): (Vec<_>, Vec<_>) = points
             .into_iter()
-            .map(|x| deep_quotient_vanishing_part(x, zeta, alpha_pow_width))
+            .flat_map(|x| {
+                openings
+                    .iter()
+                    .map(move |&(zeta, _)| deep_quotient_vanishing_part(x, zeta, alpha_pow_width))
+            })
             .unzip();
         let vp_denom_invs = batch_multiplicative_inverse(&vp_denoms);
 
-        let alpha_reduced_ps_at_zeta: EF = dot_product(alpha.powers(), ps_at_zeta.iter().copied());
+        let alpha_reduced_ps_at_zetas = openings
+            .iter()
+            .map(|(_, ps_at_zeta)| {
+                dot_product::<EF, _, _>(alpha.powers(), ps_at_zeta.iter().copied())
+            })
+            .collect_vec();
 
         self.values
             .dot_ext_powers(alpha)
-            .zip(vp_nums.into_par_iter())
-            .zip(vp_denom_invs.into_par_iter())
-            .map(|((reduced_ps_at_x, vp_num), vp_denom_inv)| {
-                vp_num * vp_denom_inv * (reduced_ps_at_x - alpha_reduced_ps_at_zeta)
+            .zip(vp_nums.par_chunks_exact(num_points))
+            .zip(vp_denom_invs.par_chunks_exact(num_points))
+            .map(|((reduced_ps_at_x, vp_nums), vp_denom_invs)| {
+                izip!(vp_nums, vp_denom_invs, &alpha_reduced_ps_at_zetas, scales)
+                    .map(
+                        |(&vp_num, &vp_denom_inv, &alpha_reduced_ps_at_zeta, &scale)| {
+                            scale
+                                * vp_num
+                                * vp_denom_inv
+                                * (reduced_ps_at_x - alpha_reduced_ps_at_zeta)
+                        },
+                    )
+                    .sum()
             })
             .collect()
     }
@@ -106,8 +146,8 @@ pub fn extract_lambda<F: ComplexExtendable, EF: ExtensionField<F>>(
     // This formula was determined experimentally...
     let v_d_2 = F::TWO.exp_u64(log_lde_size as u64 - 1);
 
-    let v_d = v_d.take(lde.len()).collect_vec();
-    let v_d = cfft_permute_slice(&v_d);
+    let mut v_d = v_d.take(lde.len()).collect_vec();
+    cfft_permute_slice_in_place(&mut v_d);
 
     let lambda =
         dot_product::<EF, _, _>(lde.iter().copied(), v_d.iter().copied()) * v_d_2.inverse();
@@ -125,11 +165,12 @@ mod tests {
 
     use p3_field::extension::BinomialExtensionField;
     use p3_field::AbstractField;
-    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::dense::{Padding, RowMajorMatrix};
     use p3_mersenne_31::Mersenne31;
     use rand::{random, thread_rng};
 
     use super::*;
+    use crate::cfft_permute_slice;
 
     type F = Mersenne31;
     type EF = BinomialExtensionField<F, 3>;
@@ -158,6 +199,38 @@ mod tests {
         assert_eq!(cfft_permute_slice(&mat_reduced), row_reduced);
     }
 
+    #[test]
+    fn reduce_multi_same_as_reduce_each() {
+        let domain = CircleDomain::standard(6);
+        let evals = CircleEvaluations::from_cfft_order(
+            domain,
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << domain.log_n, 5),
+        );
+
+        let alpha: EF = random();
+        let openings = (0..3)
+            .map(|_| {
+                let zeta: Point<EF> = Point::from_projective_line(random());
+                (zeta, evals.evaluate_at_point(zeta))
+            })
+            .collect_vec();
+        let scales: Vec<EF> = (0..3).map(|_| random()).collect();
+
+        let mut expected = vec![EF::ZERO; 1 << domain.log_n];
+        for ((zeta, ps_at_zeta), &scale) in izip!(&openings, &scales) {
+            for (e, ro) in izip!(
+                &mut expected,
+                evals.deep_quotient_reduce(alpha, *zeta, ps_at_zeta)
+            ) {
+                *e += scale * ro;
+            }
+        }
+        assert_eq!(
+            evals.deep_quotient_reduce_multi(alpha, &openings, &scales),
+            expected
+        );
+    }
+
     #[test]
     fn reduce_evaluations_low_degree() {
         let log_n = 5;
@@ -229,7 +302,7 @@ mod tests {
         let log_n = 5;
         for log_blowup in [1, 2, 3] {
             let mut coeffs = RowMajorMatrix::<F>::rand(&mut thread_rng(), (1 << log_n) + 1, 1);
-            coeffs.pad_to_height(1 << (log_n + log_blowup), F::ZERO);
+            coeffs.pad_to_height(1 << (log_n + log_blowup), Padding::Zero);
 
             let domain = CircleDomain::standard(log_n + log_blowup);
             let mut lde = CircleEvaluations::evaluate(domain, coeffs.clone()).values;
@@ -1,15 +1,16 @@
 use alloc::vec;
 use alloc::vec::Vec;
 
-use itertools::{iterate, Itertools};
+use itertools::{iterate, izip, Itertools};
 use p3_commit::{LagrangeSelectors, PolynomialSpace};
 use p3_field::extension::ComplexExtendable;
-use p3_field::ExtensionField;
+use p3_field::{batch_multiplicative_inverse, ExtensionField};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use p3_util::{log2_ceil_usize, log2_strict_usize};
 use tracing::instrument;
 
+use crate::cfft_permute_index;
 use crate::point::Point;
 
 /// A twin-coset of the circle group on F. It has a power-of-two size and an arbitrary shift.
@@ -54,9 +55,20 @@ impl<F: ComplexExtendable> CircleDomain<F> {
             shift: Point::generator(log_n + 1),
         }
     }
-    fn is_standard(&self) -> bool {
+    /// Whether this is the standard position twin-coset of its size, i.e. the one shifted by a
+    /// generator of the subgroup of twice its size.
+    pub fn is_standard(&self) -> bool {
         self.shift == Point::generator(self.log_n + 1)
     }
+    pub const fn shift(&self) -> Point<F> {
+        self.shift
+    }
+    /// The domain of size `2^log_n` which this one lands on after being folded down. Each fold
+    /// doubles every point, which halves the domain and doubles its shift.
+    pub(crate) fn folded(&self, log_n: usize) -> Self {
+        assert!(log_n <= self.log_n);
+        Self::new(log_n, self.shift * (1 << (self.log_n - log_n)))
+    }
     pub(crate) fn gen(&self) -> Point<F> {
         Point::generator(self.log_n - 1)
     }
@@ -71,7 +83,9 @@ impl<F: ComplexExtendable> CircleDomain<F> {
     pub(crate) fn points(&self) -> impl Iterator<Item = Point<F>> {
         self.coset0().interleave(self.coset1())
     }
-    pub(crate) fn nth_point(&self, idx: usize) -> Point<F> {
+    /// The point at `idx` in `points()`, found with `O(log n)` group operations rather than by
+    /// walking the domain.
+    pub fn point_at(&self, idx: usize) -> Point<F> {
         let (idx, lsb) = (idx >> 1, idx & 1);
         if lsb == 0 {
             self.shift + self.gen() * idx
@@ -79,17 +93,119 @@ impl<F: ComplexExtendable> CircleDomain<F> {
             -self.shift + self.gen() * (idx + 1)
         }
     }
+    /// The point at `idx` in cfft order, the order in which evaluations are committed.
+    pub fn point_at_cfft(&self, idx: usize) -> Point<F> {
+        self.point_at(cfft_permute_index(idx, self.log_n))
+    }
+    /// The index of `p` in `points()`, or `None` if `p` is not in this domain. The inverse of
+    /// `point_at`, found with `O(log^2 n)` group operations.
+    pub fn index_of(&self, p: Point<F>) -> Option<usize> {
+        let log_half = self.log_n - 1;
+        if let Some(k) = subgroup_log(p - self.shift, log_half) {
+            // p = shift + k g
+            Some(2 * k)
+        } else {
+            // p = -shift + (k + 1) g, with k + 1 taken mod the order of g.
+            let k = subgroup_log(p + self.shift, log_half)?;
+            Some(2 * ((k + (1 << log_half) - 1) % (1 << log_half)) + 1)
+        }
+    }
+    /// The index of `p` in cfft order, or `None` if `p` is not in this domain. The inverse of
+    /// `point_at_cfft`.
+    pub fn index_of_cfft(&self, p: Point<F>) -> Option<usize> {
+        // The cfft permutation is an involution.
+        self.index_of(p)
+            .map(|idx| cfft_permute_index(idx, self.log_n))
+    }
+    /// Whether `p` is one of the points of this domain.
+    pub fn contains(&self, p: Point<F>) -> bool {
+        self.index_of(p).is_some()
+    }
+    /// The inverse of `split_evals`: interleave the evaluations of each of the domains from
+    /// `split_domains(chunks.len())`, in natural order, back into those of this domain.
+    pub fn combine_evals(&self, chunks: Vec<RowMajorMatrix<F>>) -> RowMajorMatrix<F> {
+        let num_chunks = chunks.len();
+        assert!(num_chunks.is_power_of_two());
+        let width = chunks[0].width();
+        assert!(
+            chunks
+                .iter()
+                .all(|c| c.width() == width && c.height() * num_chunks == self.size()),
+            "each chunk must cover an equal share of the domain"
+        );
+        let mut chunk_rows = chunks.iter().map(|c| c.rows()).collect_vec();
+        let values = (0..self.size())
+            .flat_map(|i| {
+                chunk_rows[forward_backward_index(i, num_chunks)]
+                    .next()
+                    .unwrap()
+            })
+            .collect();
+        RowMajorMatrix::new(values, width)
+    }
 
-    pub(crate) fn zeroifier<EF: ExtensionField<F>>(&self, at: Point<EF>) -> EF {
+    /// Evaluate the vanishing polynomial of this domain at `at`, i.e. `v_n(at) - v_n(shift)`.
+    ///
+    /// `v_n = π^(n-1)(x)` takes a single value on any twin-coset of size `2^n`, and is zero on the
+    /// standard position one, so for a standard domain this is just `v_n(at)`.
+    pub fn vanishing_poly_at_point<EF: ExtensionField<F>>(&self, at: Point<EF>) -> EF {
         at.v_n(self.log_n) - self.shift.v_n(self.log_n)
     }
 
+    /// The Lagrange selectors of this domain at `at`, with `at` given on the circle rather than
+    /// the projective line as in `PolynomialSpace::selectors_at_point`.
+    pub fn selectors_at_circle_point<EF: ExtensionField<F>>(
+        &self,
+        at: Point<EF>,
+    ) -> LagrangeSelectors<EF> {
+        LagrangeSelectors {
+            is_first_row: self.s_p(self.shift, at),
+            is_last_row: self.s_p(-self.shift, at),
+            is_transition: EF::ONE - self.s_p_normalized(-self.shift, at),
+            inv_zeroifier: self.vanishing_poly_at_point(at).inverse(),
+        }
+    }
+
+    /// The Lagrange selectors of this domain at every point of `lde_domain`, in natural order.
+    /// `lde_domain` must be disjoint from this domain.
+    ///
+    /// Dividing by `ṽ_p(q) = (q - p).y / ((q - p).x + 1)` only needs the `y` coordinates inverted,
+    /// so all the inverses are computed in three batches.
+    #[instrument(skip_all, fields(log_n = %lde_domain.log_n))]
+    pub fn selectors_on_domain(&self, lde_domain: Self) -> LagrangeSelectors<Vec<F>> {
+        let points = lde_domain.points().collect_vec();
+        let zs = points
+            .iter()
+            .map(|&p| self.vanishing_poly_at_point(p))
+            .collect_vec();
+        let s_p = |p: Point<F>| {
+            let diffs = points.iter().map(|&q| q - p).collect_vec();
+            let inv_ys = batch_multiplicative_inverse(&diffs.iter().map(|d| d.y).collect_vec());
+            izip!(&zs, diffs, inv_ys)
+                .map(|(&z, d, inv_y)| z * (d.x + F::ONE) * inv_y)
+                .collect_vec()
+        };
+        let is_first_row = s_p(self.shift);
+        let is_last_row = s_p(-self.shift);
+        let inv_last_normalizer = (-self.shift).s_p_at_p(self.log_n).inverse();
+        let is_transition = is_last_row
+            .iter()
+            .map(|&s| F::ONE - s * inv_last_normalizer)
+            .collect();
+        LagrangeSelectors {
+            is_first_row,
+            is_last_row,
+            is_transition,
+            inv_zeroifier: batch_multiplicative_inverse(&zs),
+        }
+    }
+
     pub(crate) fn s_p<EF: ExtensionField<F>>(&self, p: Point<F>, at: Point<EF>) -> EF {
-        self.zeroifier(at) / p.v_tilde_p(at)
+        self.vanishing_poly_at_point(at) / p.v_tilde_p(at)
     }
 
     pub(crate) fn s_p_normalized<EF: ExtensionField<F>>(&self, p: Point<F>, at: Point<EF>) -> EF {
-        self.zeroifier(at) / (p.v_tilde_p(at) * p.s_p_at_p(self.log_n))
+        self.vanishing_poly_at_point(at) / (p.v_tilde_p(at) * p.s_p_at_p(self.log_n))
     }
 }
 
@@ -168,20 +284,14 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
     }
 
     fn zp_at_point<Ext: ExtensionField<Self::Val>>(&self, point: Ext) -> Ext {
-        self.zeroifier(Point::from_projective_line(point))
+        self.vanishing_poly_at_point(Point::from_projective_line(point))
     }
 
     fn selectors_at_point<Ext: ExtensionField<Self::Val>>(
         &self,
         point: Ext,
     ) -> LagrangeSelectors<Ext> {
-        let point = Point::from_projective_line(point);
-        LagrangeSelectors {
-            is_first_row: self.s_p(self.shift, point),
-            is_last_row: self.s_p(-self.shift, point),
-            is_transition: Ext::ONE - self.s_p_normalized(-self.shift, point),
-            inv_zeroifier: self.zeroifier(point).inverse(),
-        }
+        self.selectors_at_circle_point(Point::from_projective_line(point))
     }
 
     /*
@@ -200,21 +310,28 @@ impl<F: ComplexExtendable> PolynomialSpace for CircleDomain<F> {
     chunks=2: 0 1 1 0 0 1 1 0 0 1 1 0 0 1 1 0
     chunks=4: 0 1 2 3 3 2 1 0 0 1 2 3 3 2 1 0
     */
-    // wow, really slow!
-    // todo: batch inverses
-    #[instrument(skip_all, fields(log_n = %coset.log_n))]
     fn selectors_on_coset(&self, coset: Self) -> LagrangeSelectors<Vec<Self::Val>> {
-        let sels = coset
-            .points()
-            .map(|p| self.selectors_at_point(p.to_projective_line().unwrap()))
-            .collect_vec();
-        LagrangeSelectors {
-            is_first_row: sels.iter().map(|s| s.is_first_row).collect(),
-            is_last_row: sels.iter().map(|s| s.is_last_row).collect(),
-            is_transition: sels.iter().map(|s| s.is_transition).collect(),
-            inv_zeroifier: sels.iter().map(|s| s.inv_zeroifier).collect(),
+        self.selectors_on_domain(coset)
+    }
+}
+
+/// The `k < 2^log_n` with `p = k * Point::generator(log_n)`, if `p` lies in the subgroup of that
+/// order. Each bit of `k` is found in turn, by checking whether what remains of `p` survives enough
+/// doublings to tell its order.
+fn subgroup_log<F: ComplexExtendable>(p: Point<F>, log_n: usize) -> Option<usize> {
+    let identity = Point::new(F::ONE, F::ZERO);
+    let mut g = Point::generator(log_n);
+    let mut rem = p;
+    let mut k = 0;
+    for i in 0..log_n {
+        // rem = p - k g is a multiple of 2^i g, and the next bit of k is set iff it is an odd one.
+        if iterate(rem, |q| q.double()).nth(log_n - i - 1).unwrap() != identity {
+            k |= 1 << i;
+            rem = rem - g;
         }
+        g = g.double();
     }
+    (rem == identity).then_some(k)
 }
 
 // 0 1 2 .. len-1 len len len-1 .. 1 0 0 1 ..
@@ -232,13 +349,12 @@ mod tests {
     use core::iter;
 
     use hashbrown::HashSet;
-    use itertools::izip;
-    use p3_field::{batch_multiplicative_inverse, AbstractField};
+    use p3_field::{AbstractField, Field};
     use p3_mersenne_31::Mersenne31;
-    use rand::thread_rng;
+    use rand::{random, thread_rng};
 
     use super::*;
-    use crate::CircleEvaluations;
+    use crate::{cfft_permute_slice_in_place, CircleEvaluations};
 
     fn assert_is_twin_coset<F: ComplexExtendable>(d: CircleDomain<F>) {
         let pts = d.points().collect_vec();
@@ -248,6 +364,78 @@ mod tests {
         }
     }
 
+    #[test]
+    fn point_at_matches_points() {
+        type F = Mersenne31;
+        for log_n in 1..=12 {
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (random::<usize>() | 1);
+            for d in [
+                CircleDomain::<F>::standard(log_n),
+                CircleDomain::new(log_n, shift),
+            ] {
+                let mut points = d.points().collect_vec();
+                for (i, &p) in points.iter().enumerate() {
+                    assert_eq!(d.point_at(i), p, "log_n = {log_n}, i = {i}");
+                }
+                cfft_permute_slice_in_place(&mut points);
+                for (i, &p) in points.iter().enumerate() {
+                    assert_eq!(d.point_at_cfft(i), p, "log_n = {log_n}, i = {i}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn index_of_inverts_point_at() {
+        type F = Mersenne31;
+        for log_n in 1..=8 {
+            // Every point of a subgroup containing both domains.
+            let g = Point::<F>::generator(log_n + 3);
+            let subgroup = (0..1 << (log_n + 3)).map(|i| g * i).collect_vec();
+            for d in [
+                CircleDomain::<F>::standard(log_n),
+                CircleDomain::new(log_n, g * 3),
+            ] {
+                for i in 0..d.size() {
+                    assert_eq!(d.index_of(d.point_at(i)), Some(i), "log_n = {log_n}");
+                    assert_eq!(
+                        d.index_of_cfft(d.point_at_cfft(i)),
+                        Some(i),
+                        "log_n = {log_n}"
+                    );
+                }
+
+                let points: HashSet<Point<F>> = d.points().collect();
+                for &p in &subgroup {
+                    assert_eq!(d.contains(p), points.contains(&p), "log_n = {log_n}");
+                }
+                // A point outside the subgroup altogether.
+                assert_eq!(d.index_of(Point::generator(log_n + 4)), None);
+            }
+        }
+    }
+
+    #[test]
+    fn folded_domain_is_doubled_domain() {
+        type F = Mersenne31;
+        for log_n in 2..6 {
+            let standard = CircleDomain::<F>::standard(log_n);
+            assert_eq!(
+                standard.folded(log_n - 1),
+                CircleDomain::standard(log_n - 1)
+            );
+            assert_eq!(standard.folded(log_n), standard);
+
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (random::<usize>() | 1);
+            let d = CircleDomain::<F>::new(log_n, shift);
+            assert!(!d.is_standard());
+            assert_eq!(d.shift(), shift);
+            let doubled: HashSet<Point<F>> = d.points().map(Point::double).collect();
+            let folded: HashSet<Point<F>> = d.folded(log_n - 1).points().collect();
+            assert_eq!(doubled, folded);
+        }
+    }
+
     fn do_test_circle_domain(log_n: usize, width: usize) {
         let n = 1 << log_n;
 
@@ -258,8 +446,8 @@ mod tests {
         let p0 = d.first_point();
         let mut p1 = p0;
         for i in 0..(n - 1) {
-            // nth_point is correct
-            assert_eq!(Point::from_projective_line(p1), d.nth_point(i));
+            // point_at is correct
+            assert_eq!(Point::from_projective_line(p1), d.point_at(i));
             p1 = d.next_point(p1).unwrap();
             assert_ne!(p1, p0);
         }
@@ -300,6 +488,11 @@ mod tests {
             assert_eq!(sds.len(), num_chunks);
             let ses = d.split_evals(num_chunks, evals.clone());
             assert_eq!(ses.len(), num_chunks);
+            assert_eq!(
+                d.combine_evals(ses.clone()),
+                evals,
+                "combining undoes splitting"
+            );
             for (sd, se) in izip!(sds, ses) {
                 // Split domains are twin cosets
                 assert_is_twin_coset(sd);
@@ -396,6 +589,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn vanishing_poly_zero_exactly_on_domain() {
+        type F = Mersenne31;
+        for log_n in 1..6 {
+            // Every point of a subgroup containing both domains.
+            let g = Point::<F>::generator(log_n + 3);
+            let subgroup = (0..1 << (log_n + 3)).map(|i| g * i).collect_vec();
+            for d in [
+                CircleDomain::<F>::standard(log_n),
+                CircleDomain::new(log_n, g * 3),
+            ] {
+                let points: HashSet<Point<F>> = d.points().collect();
+                for &p in &subgroup {
+                    assert_eq!(
+                        d.vanishing_poly_at_point(p).is_zero(),
+                        points.contains(&p),
+                        "log_n = {log_n}, p = {p:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn selectors_on_shifted_domain() {
+        type F = Mersenne31;
+        for log_n in 2..6 {
+            // Its points have order 2^(log_n + 4), so it is disjoint from the standard LDE domain.
+            let d = CircleDomain::<F>::new(log_n, Point::generator(log_n + 4) * 3);
+            let lde_domain = CircleDomain::standard(log_n + 1);
+            let sels = d.selectors_on_domain(lde_domain);
+            for (i, p) in lde_domain.points().enumerate() {
+                let pt_sels = d.selectors_at_circle_point(p);
+                assert_eq!(sels.is_first_row[i], pt_sels.is_first_row);
+                assert_eq!(sels.is_last_row[i], pt_sels.is_last_row);
+                assert_eq!(sels.is_transition[i], pt_sels.is_transition);
+                assert_eq!(sels.inv_zeroifier[i], pt_sels.inv_zeroifier);
+            }
+        }
+    }
+
     #[test]
     fn test_circle_domain() {
         do_test_circle_domain(4, 8);
@@ -1,30 +1,62 @@
+use alloc::collections::BTreeMap;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::cell::RefCell;
 use core::fmt::Debug;
 use core::marker::PhantomData;
 
-use itertools::Itertools;
+use itertools::{iterate, Itertools};
 use p3_commit::Mmcs;
 use p3_field::extension::ComplexExtendable;
-use p3_field::{batch_multiplicative_inverse, ExtensionField};
+use p3_field::{
+    batch_multiplicative_inverse_packed, AbstractExtensionField, ExtensionField, Field, PackedValue,
+};
 use p3_fri::FriGenericConfig;
+use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
-use p3_util::{log2_strict_usize, reverse_bits_len};
+use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
 
 use crate::domain::CircleDomain;
+use crate::packing::{pack_ext, unpack_ext};
+use crate::point::Point;
 use crate::{CircleInputProof, InputError};
 
-pub(crate) struct CircleFriGenericConfig<F, InputProof, InputError>(
-    pub(crate) PhantomData<(F, InputProof, InputError)>,
-);
+pub(crate) struct CircleFriGenericConfig<'a, F, InputProof, InputError> {
+    twiddles: &'a FoldingTwiddles<F>,
+    _phantom: PhantomData<(InputProof, InputError)>,
+}
+
+impl<'a, F, InputProof, InputError> CircleFriGenericConfig<'a, F, InputProof, InputError> {
+    pub(crate) const fn new(twiddles: &'a FoldingTwiddles<F>) -> Self {
+        Self {
+            twiddles,
+            _phantom: PhantomData,
+        }
+    }
+}
 
-pub(crate) type CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> = CircleFriGenericConfig<
+/// The log arity of the commit phase round folding a codeword of height `2^log_height`: as close
+/// to `log_max_arity` as possible, without folding below `2^log_next_height`, which is the height
+/// of the next input to roll in, or the blowup once there are none left.
+///
+/// The prover and verifier both pick each round's arity this way, so it needn't be in the proof.
+pub(crate) fn log_round_arity(
+    log_max_arity: usize,
+    log_height: usize,
+    log_next_height: usize,
+) -> usize {
+    log_max_arity.min(log_height - log_next_height)
+}
+
+pub(crate) type CircleFriConfig<'a, Val, Challenge, InputMmcs, FriMmcs> = CircleFriGenericConfig<
+    'a,
     Val,
     CircleInputProof<Val, Challenge, InputMmcs, FriMmcs>,
     InputError<<InputMmcs as Mmcs<Val>>::Error, <FriMmcs as Mmcs<Challenge>>::Error>,
 >;
 
 impl<F: ComplexExtendable, EF: ExtensionField<F>, InputProof, InputError: Debug>
-    FriGenericConfig<EF> for CircleFriGenericConfig<F, InputProof, InputError>
+    FriGenericConfig<EF> for CircleFriGenericConfig<'_, F, InputProof, InputError>
 {
     type InputProof = InputProof;
     type InputError = InputError;
@@ -40,124 +72,627 @@ impl<F: ComplexExtendable, EF: ExtensionField<F>, InputProof, InputError: Debug>
         beta: EF,
         evals: impl Iterator<Item = EF>,
     ) -> EF {
-        fold_x_row(index, log_folded_height, beta, evals)
+        self.twiddles
+            .fold_x_row(index, log_folded_height, beta, evals)
     }
 
     fn fold_matrix<M: Matrix<EF>>(&self, beta: EF, m: M) -> Vec<EF> {
-        fold_x(beta, m)
+        self.twiddles.fold_x(beta, m)
     }
 }
 
-fn fold<F: ComplexExtendable, EF: ExtensionField<F>>(
-    evals: impl Matrix<EF>,
-    beta: EF,
-    twiddles: &[F],
-) -> Vec<EF> {
-    evals
-        .rows()
-        .zip(twiddles)
-        .map(|(mut row, &t)| {
-            let (lo, hi) = row.next_tuple().unwrap();
-            let sum = lo + hi;
-            let diff = (lo - hi) * t;
-            (sum + beta * diff).halve()
-        })
-        .collect_vec()
+/// The inverse twiddles used to fold whole matrices, memoized by the log size of the domain they
+/// come from, since they depend on nothing else. Computing them takes a batch inversion over the
+/// whole domain, which is otherwise repeated for every proof.
+///
+/// The twiddles stay in the base field, so each fold multiplies an extension element by a base
+/// element and only `beta` costs a full extension multiplication. The default folds standard
+/// position domains.
+#[derive(Debug, Default)]
+pub struct FoldingTwiddles<F> {
+    /// The largest domain being folded. Every smaller domain is its image under repeated folding.
+    /// `None` folds standard position domains, which is all the PCS ever commits to.
+    domain: Option<CircleDomain<F>>,
+    /// Inverse y twiddles (for the bivariate fold) of the domain of size `2^log_n`.
+    inv_y_twiddles: RefCell<BTreeMap<usize, Vec<F>>>,
+    /// Inverse x twiddles (for the univariate folds) of the first layer of the domain of size
+    /// `2^log_n`.
+    inv_x_twiddles: RefCell<BTreeMap<usize, Vec<F>>>,
+    /// What single row folds need to find their twiddle in the domain of size `2^log_n`. The
+    /// verifier folds one row per query per round, so this saves recomputing the domain's
+    /// generator every time.
+    row_fold_points: RefCell<BTreeMap<usize, RowFoldPoints<F>>>,
 }
 
-pub(crate) fn fold_y<F: ComplexExtendable, EF: ExtensionField<F>>(
-    beta: EF,
-    evals: impl Matrix<EF>,
-) -> Vec<EF> {
-    assert_eq!(evals.width(), 2);
-    let log_n = log2_strict_usize(evals.height()) + 1;
-    fold(
-        evals,
-        beta,
-        &batch_multiplicative_inverse(&CircleDomain::standard(log_n).y_twiddles()),
-    )
+/// The first coset of a domain, prepared for looking up individual points: its shift, and its
+/// generator doubled `0, 1, ..., log_n - 2` times, so that any multiple of the generator is a sum
+/// of these without further doublings.
+#[derive(Debug)]
+struct RowFoldPoints<F> {
+    shift: Point<F>,
+    gen_doublings: Vec<Point<F>>,
 }
 
-pub(crate) fn fold_y_row<F: ComplexExtendable, EF: ExtensionField<F>>(
-    index: usize,
-    log_folded_height: usize,
-    beta: EF,
-    evals: impl Iterator<Item = EF>,
-) -> EF {
-    let evals = evals.collect_vec();
-    assert_eq!(evals.len(), 2);
-    let t = CircleDomain::<F>::standard(log_folded_height + 1)
-        .nth_y_twiddle(index)
-        .inverse();
-    let sum = evals[0] + evals[1];
-    let diff = (evals[0] - evals[1]) * t;
+impl<F: ComplexExtendable> RowFoldPoints<F> {
+    fn new(domain: CircleDomain<F>) -> Self {
+        Self {
+            shift: domain.shift,
+            gen_doublings: iterate(domain.gen(), |p| p.double())
+                .take(domain.log_n - 1)
+                .collect(),
+        }
+    }
+
+    /// The same as `domain.shift + domain.gen() * i`.
+    fn coset0_point(&self, i: usize) -> Point<F> {
+        debug_assert!(i < 1 << self.gen_doublings.len());
+        self.gen_doublings
+            .iter()
+            .enumerate()
+            .filter(|&(bit, _)| (i >> bit) & 1 == 1)
+            .fold(self.shift, |acc, (_, &p)| acc + p)
+    }
+}
+
+impl<F: ComplexExtendable> FoldingTwiddles<F> {
+    /// Twiddles for folding evaluations over `domain` and each of the smaller domains it folds
+    /// down onto.
+    pub fn new(domain: CircleDomain<F>) -> Self {
+        Self {
+            domain: Some(domain),
+            inv_y_twiddles: Default::default(),
+            inv_x_twiddles: Default::default(),
+            row_fold_points: Default::default(),
+        }
+    }
+
+    /// The domain of size `2^log_n` being folded.
+    fn domain(&self, log_n: usize) -> CircleDomain<F> {
+        self.domain
+            .map_or_else(|| CircleDomain::standard(log_n), |d| d.folded(log_n))
+    }
+
+    /// The `i`th point of the first coset of the domain of size `2^log_n`.
+    fn coset0_point(&self, log_n: usize, i: usize) -> Point<F> {
+        self.row_fold_points
+            .borrow_mut()
+            .entry(log_n)
+            .or_insert_with(|| RowFoldPoints::new(self.domain(log_n)))
+            .coset0_point(i)
+    }
+
+    /// Fold the evaluations of `p(x, y) = p_0(x) + y * p_1(x)`, two per row in cfft order, into
+    /// those of `p_0 + beta * p_1`.
+    pub fn fold_y<EF: ExtensionField<F>>(&self, beta: EF, evals: impl Matrix<EF>) -> Vec<EF> {
+        assert_eq!(evals.width(), 2);
+        assert_ne!(evals.height(), 0, "fold_y called on an empty matrix");
+        let log_n = log2_strict_usize(evals.height()) + 1;
+        let mut inv_y_twiddles = self.inv_y_twiddles.borrow_mut();
+        let twiddles = inv_y_twiddles.entry(log_n).or_insert_with(|| {
+            batch_multiplicative_inverse_packed(&self.domain(log_n).y_twiddles())
+        });
+        fold(evals, beta, twiddles)
+    }
+
+    /// Like `fold_y`, but computing the twiddles as it goes, a chunk of rows at a time, rather than
+    /// building the twiddles of the whole domain and caching them. The extra memory is then one
+    /// chunk of twiddles, which suits a one-off fold of a domain too large to keep twiddles for.
+    pub fn fold_y_streaming<EF: ExtensionField<F>>(
+        &self,
+        beta: EF,
+        evals: impl Matrix<EF>,
+    ) -> Vec<EF> {
+        assert_eq!(evals.width(), 2);
+        assert_ne!(
+            evals.height(),
+            0,
+            "fold_y_streaming called on an empty matrix"
+        );
+        let log_height = log2_strict_usize(evals.height());
+        let log_chunk = log_height.min(LOG_FOLD_Y_STREAMING_CHUNK);
+        let log_chunks = log_height - log_chunk;
+        let log_n = log_height + 1;
+        // Row `(c << log_chunk) + j` folds with the first coset's point at the bit reversal of its
+        // index, `(rev(j) << log_chunks) + rev(c)`. Within chunk `c` these points step by a fixed
+        // multiple of the generator, so a chunk of twiddles costs one lookup and additions.
+        let step = self.domain(log_n).gen() * (1 << log_chunks);
+        let mut folded = Vec::with_capacity(evals.height());
+        for c in 0..1 << log_chunks {
+            let start = self.coset0_point(log_n, reverse_bits_len(c, log_chunks));
+            let mut ys = iterate(start, |&p| p + step)
+                .map(|p| p.y)
+                .take(1 << log_chunk)
+                .collect_vec();
+            reverse_slice_index_bits(&mut ys);
+            let twiddles = batch_multiplicative_inverse_packed(&ys);
+            fold_into(&evals, c << log_chunk, beta, &twiddles, &mut folded);
+        }
+        folded
+    }
+
+    /// Fold the evaluations of `p(x) = p_0(2x^2 - 1) + x * p_1(2x^2 - 1)`, two per row in cfft
+    /// order, into those of `p_0 + beta * p_1`.
+    ///
+    /// Rows of `2^k` evaluations are folded `k` times in a row, with `beta`, `beta^2`, and so on,
+    /// squaring `beta` each time.
+    pub fn fold_x<EF: ExtensionField<F>>(&self, beta: EF, evals: impl Matrix<EF>) -> Vec<EF> {
+        let log_arity = log2_strict_usize(evals.width());
+        assert_ne!(log_arity, 0, "fold_x needs at least 2 evaluations per row");
+        // FRI stops folding once it reaches the blowup, so there is always at least one row left.
+        assert_ne!(
+            evals.height(),
+            0,
+            "fold_x called on an empty matrix; the codeword is already below the blowup"
+        );
+        let mut folded = self.fold_x_layer(beta, evals);
+        let mut beta = beta;
+        for _ in 1..log_arity {
+            beta = beta.square();
+            folded = self.fold_x_layer(beta, RowMajorMatrix::new(folded, 2));
+        }
+        folded
+    }
+
+    /// Fold each consecutive pair of `evals`, read row by row, once.
+    fn fold_x_layer<EF: ExtensionField<F>>(&self, beta: EF, evals: impl Matrix<EF>) -> Vec<EF> {
+        let len = evals.width() * evals.height();
+        if len == 2 {
+            // The terminal fold needs a single twiddle, so skip building the domain.
+            let (lo, hi) = evals.first_row().collect_tuple().unwrap();
+            return vec![self.fold_x_pair(0, 0, beta, lo, hi)];
+        }
+        self.with_inv_x_twiddles(log2_strict_usize(len), |twiddles| {
+            fold(evals, beta, twiddles)
+        })
+    }
+
+    /// Fold `k` codewords at once, like `fold_x` with arity 2, with their evaluations side by
+    /// side: each row holds a pair `lo, hi` for each codeword in turn, and the result holds the
+    /// `k` folded values in the same order.
+    ///
+    /// All `k` codewords share the same twiddles, so each twiddle is read once per row rather
+    /// than once per codeword.
+    pub fn fold_x_batched<EF: ExtensionField<F>>(
+        &self,
+        beta: EF,
+        evals: impl Matrix<EF>,
+    ) -> RowMajorMatrix<EF> {
+        assert!(
+            evals.width() > 0 && evals.width() % 2 == 0,
+            "fold_x_batched needs a pair of evaluations for each codeword"
+        );
+        assert_ne!(
+            evals.height(),
+            0,
+            "fold_x_batched called on an empty matrix; the codewords are already below the blowup"
+        );
+        let num_codewords = evals.width() / 2;
+        let fold_rows = |twiddles: &[F]| {
+            evals
+                .rows()
+                .zip(twiddles)
+                .flat_map(|(row, &t)| row.tuples().map(move |(lo, hi)| fold_pair(lo, hi, beta, t)))
+                .collect_vec()
+        };
+        let values = if evals.height() == 1 {
+            // As for the terminal fold in `fold_x_layer`, there is only one twiddle.
+            let t = self.coset0_point(2, 0).x.inverse();
+            fold_rows(&[t])
+        } else {
+            self.with_inv_x_twiddles(log2_strict_usize(evals.height()) + 1, fold_rows)
+        };
+        RowMajorMatrix::new(values, num_codewords)
+    }
+
+    /// Run `f` on the inverse x twiddles folding a codeword of size `2^log_len`.
+    fn with_inv_x_twiddles<R>(&self, log_len: usize, f: impl FnOnce(&[F]) -> R) -> R {
+        // +1 because twiddles after the first layer come from the x coordinates of the larger
+        // domain.
+        let log_n = log_len + 1;
+        let mut inv_x_twiddles = self.inv_x_twiddles.borrow_mut();
+        let twiddles = inv_x_twiddles.entry(log_n).or_insert_with(|| {
+            batch_multiplicative_inverse_packed(&self.domain(log_n).x_twiddles(0))
+        });
+        f(twiddles)
+    }
+
+    pub(crate) fn fold_y_row<EF: ExtensionField<F>>(
+        &self,
+        index: usize,
+        log_folded_height: usize,
+        beta: EF,
+        evals: impl Iterator<Item = EF>,
+    ) -> EF {
+        let evals = evals.collect_vec();
+        assert_eq!(evals.len(), 2);
+        // The y twiddles are the first coset in bit-reversed order, as in `y_twiddles`.
+        let t = self
+            .coset0_point(
+                log_folded_height + 1,
+                reverse_bits_len(index, log_folded_height),
+            )
+            .y
+            .inverse();
+        fold_pair(evals[0], evals[1], beta, t)
+    }
+
+    /// Fold row `index` of `2^k` evaluations down to one, as `fold_x` does: the pairs of the row
+    /// are folded first, each with its own twiddle, then the pairs of their results, and so on.
+    pub(crate) fn fold_x_row<EF: ExtensionField<F>>(
+        &self,
+        index: usize,
+        log_folded_height: usize,
+        beta: EF,
+        evals: impl Iterator<Item = EF>,
+    ) -> EF {
+        let mut evals = evals.collect_vec();
+        let log_arity = log2_strict_usize(evals.len());
+        assert_ne!(log_arity, 0, "fold_x_row needs at least 2 evaluations");
+
+        let mut beta = beta;
+        // After each fold, `2^log_left` values remain of the row, at that many times the height.
+        for log_left in (0..log_arity).rev() {
+            evals = evals
+                .into_iter()
+                .tuples()
+                .enumerate()
+                .map(|(i, (lo, hi))| {
+                    let index = (index << log_left) + i;
+                    self.fold_x_pair(index, log_folded_height + log_left, beta, lo, hi)
+                })
+                .collect();
+            beta = beta.square();
+        }
+        evals[0]
+    }
+
+    /// Fold the pair at `index` of a codeword, onto a codeword of height `2^log_folded_height`.
+    fn fold_x_pair<EF: ExtensionField<F>>(
+        &self,
+        index: usize,
+        log_folded_height: usize,
+        beta: EF,
+        lo: EF,
+        hi: EF,
+    ) -> EF {
+        // The x twiddles are the first coset of the domain of twice the unfolded size, in
+        // bit-reversed order, as in `x_twiddles`.
+        let t = self
+            .coset0_point(
+                log_folded_height + 2,
+                reverse_bits_len(index, log_folded_height),
+            )
+            .x
+            .inverse();
+        fold_pair(lo, hi, beta, t)
+    }
+}
+
+/// The log number of rows `fold_y_streaming` computes twiddles for at once.
+const LOG_FOLD_Y_STREAMING_CHUNK: usize = 10;
+
+/// `(lo + hi) / 2 + beta * (lo - hi) * t / 2`, where `t` is the inverse twiddle of the pair.
+fn fold_pair<F: Field, EF: ExtensionField<F>>(lo: EF, hi: EF, beta: EF, t: F) -> EF {
+    let sum = lo + hi;
+    let diff = (lo - hi) * t;
     (sum + beta * diff).halve()
 }
 
-pub(crate) fn fold_x<F: ComplexExtendable, EF: ExtensionField<F>>(
-    beta: EF,
+/// Fold each consecutive pair of `evals`, read row by row, with its twiddle.
+///
+/// Pairs are folded `F::Packing::WIDTH` at a time on packed coordinates, and any left over one at
+/// a time. The two give the same results.
+fn fold<F: ComplexExtendable, EF: ExtensionField<F>>(
     evals: impl Matrix<EF>,
+    beta: EF,
+    twiddles: &[F],
 ) -> Vec<EF> {
-    let log_n = log2_strict_usize(evals.width() * evals.height());
-    // +1 because twiddles after the first layer come from the x coordinates of the larger domain.
-    let domain = CircleDomain::standard(log_n + 1);
-    fold(
-        evals,
-        beta,
-        &batch_multiplicative_inverse(&domain.x_twiddles(0)),
-    )
+    assert_eq!(twiddles.len() * 2, evals.width() * evals.height());
+    let mut folded = Vec::with_capacity(twiddles.len());
+    fold_into(&evals, 0, beta, twiddles, &mut folded);
+    folded
 }
 
-pub(crate) fn fold_x_row<F: ComplexExtendable, EF: ExtensionField<F>>(
-    index: usize,
-    log_folded_height: usize,
+/// Fold the pairs of `evals` from pair `first` on, one per twiddle, appending the results to
+/// `folded`, as `fold` does for all of them.
+fn fold_into<F: ComplexExtendable, EF: ExtensionField<F>>(
+    evals: &impl Matrix<EF>,
+    first: usize,
     beta: EF,
-    evals: impl Iterator<Item = EF>,
-) -> EF {
-    let evals = evals.collect_vec();
-    assert_eq!(evals.len(), 2);
-    let log_arity = log2_strict_usize(evals.len());
-
-    let t = CircleDomain::<F>::standard(log_folded_height + log_arity + 1)
-        .nth_x_twiddle(reverse_bits_len(index, log_folded_height))
-        .inverse();
-
-    let sum = evals[0] + evals[1];
-    let diff = (evals[0] - evals[1]) * t;
-    (sum + beta * diff).halve()
+    twiddles: &[F],
+    folded: &mut Vec<EF>,
+) {
+    let width = evals.width();
+    assert!((first + twiddles.len()) * 2 <= width * evals.height());
+    let lo = |i: usize| evals.get(2 * (first + i) / width, 2 * (first + i) % width);
+    let hi = |i: usize| evals.get(2 * (first + i) / width, 2 * (first + i) % width + 1);
+
+    let (packed_twiddles, suffix_twiddles) = F::Packing::pack_slice_with_suffix(twiddles);
+    let packed_beta = EF::ExtensionPacking::from_base_fn(|k| beta.as_base_slice()[k].into());
+    let packed_half = F::Packing::from(F::ONE.halve());
+
+    for (chunk, &t) in packed_twiddles.iter().enumerate() {
+        let start = chunk * F::Packing::WIDTH;
+        let packed_lo = pack_ext::<F, EF>(|j| lo(start + j));
+        let packed_hi = pack_ext::<F, EF>(|j| hi(start + j));
+        let sum = packed_lo + packed_hi;
+        let diff = (packed_lo - packed_hi) * t;
+        let res = (sum + packed_beta * diff) * packed_half;
+        folded.extend((0..F::Packing::WIDTH).map(|j| unpack_ext::<F, EF>(&res, j)));
+    }
+    let start = packed_twiddles.len() * F::Packing::WIDTH;
+    folded.extend(
+        suffix_twiddles
+            .iter()
+            .enumerate()
+            .map(|(j, &t)| fold_pair(lo(start + j), hi(start + j), beta, t)),
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use itertools::iproduct;
     use p3_field::extension::BinomialExtensionField;
+    use p3_field::{AbstractField, Field};
     use p3_matrix::dense::RowMajorMatrix;
     use p3_mersenne_31::Mersenne31;
     use rand::{random, thread_rng};
 
     use super::*;
+    use crate::point::Point;
     use crate::CircleEvaluations;
 
     type F = Mersenne31;
     type EF = BinomialExtensionField<F, 3>;
 
+    fn fold_y<EF: ExtensionField<F>>(beta: EF, evals: impl Matrix<EF>) -> Vec<EF> {
+        FoldingTwiddles::<F>::default().fold_y(beta, evals)
+    }
+
+    fn fold_x<EF: ExtensionField<F>>(beta: EF, evals: impl Matrix<EF>) -> Vec<EF> {
+        FoldingTwiddles::<F>::default().fold_x(beta, evals)
+    }
+
+    /// A few twin-cosets of size `2^log_n` with random shifts, alongside the standard one.
+    fn domains(log_n: usize) -> Vec<CircleDomain<F>> {
+        let mut domains = vec![CircleDomain::standard(log_n)];
+        domains.extend((0..3).map(|_| {
+            // An odd multiple of the generator never lands on a degenerate twin-coset.
+            let shift = Point::generator(F::CIRCLE_TWO_ADICITY) * (random::<usize>() | 1);
+            CircleDomain::new(log_n, shift)
+        }));
+        domains
+    }
+
     #[test]
-    fn fold_matrix_same_as_row() {
-        let log_folded_height = 5;
-        let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_folded_height, 2);
+    fn packed_fold_matches_scalar() {
+        let width = <F as Field>::Packing::WIDTH;
+        // Lengths with and without a scalar suffix, including none packed at all.
+        for (num_pairs, row_width) in iproduct!(1..=3 * width + 1, [2, 4]) {
+            if (2 * num_pairs) % row_width != 0 {
+                continue;
+            }
+            let m =
+                RowMajorMatrix::<EF>::rand(&mut thread_rng(), 2 * num_pairs / row_width, row_width);
+            let twiddles: Vec<F> = (0..num_pairs).map(|_| random()).collect();
+            let beta: EF = random();
+
+            let scalar = m
+                .values
+                .iter()
+                .tuples()
+                .zip(&twiddles)
+                .map(|((&lo, &hi), &t)| fold_pair(lo, hi, beta, t))
+                .collect_vec();
+            assert_eq!(
+                fold(m.as_view(), beta, &twiddles),
+                scalar,
+                "num_pairs = {num_pairs}, row_width = {row_width}"
+            );
+        }
+    }
+
+    #[test]
+    fn twiddles_computed_once_per_height() {
+        let twiddles = FoldingTwiddles::<F>::default();
+        let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << 6, 2);
         let beta: EF = random();
 
-        let mat_y_folded = fold_y::<F, EF>(beta, m.as_view());
-        let row_y_folded = (0..(1 << log_folded_height))
-            .map(|i| fold_y_row::<F, EF>(i, log_folded_height, beta, m.row(i)))
-            .collect_vec();
-        assert_eq!(mat_y_folded, row_y_folded);
+        let y_folded = twiddles.fold_y(beta, m.as_view());
+        let x_folded = twiddles.fold_x(beta, m.as_view());
+        let y_twiddles_ptr = twiddles.inv_y_twiddles.borrow()[&7].as_ptr();
+        let x_twiddles_ptr = twiddles.inv_x_twiddles.borrow()[&8].as_ptr();
+
+        // Folding again at the same height reuses the twiddles.
+        assert_eq!(twiddles.fold_y(beta, m.as_view()), y_folded);
+        assert_eq!(twiddles.fold_x(beta, m.as_view()), x_folded);
+        assert_eq!(twiddles.inv_y_twiddles.borrow().len(), 1);
+        assert_eq!(twiddles.inv_x_twiddles.borrow().len(), 1);
+        assert_eq!(
+            twiddles.inv_y_twiddles.borrow()[&7].as_ptr(),
+            y_twiddles_ptr
+        );
+        assert_eq!(
+            twiddles.inv_x_twiddles.borrow()[&8].as_ptr(),
+            x_twiddles_ptr
+        );
+
+        // The cached twiddles are the ones a fresh cache computes.
+        assert_eq!(y_folded, fold_y(beta, m.as_view()));
+        assert_eq!(x_folded, fold_x(beta, m.as_view()));
+    }
+
+    #[test]
+    fn streaming_fold_y_matches_cached() {
+        // Heights on both sides of a single streaming chunk, including several chunks.
+        for log_height in
+            (0..4).chain(LOG_FOLD_Y_STREAMING_CHUNK - 1..=LOG_FOLD_Y_STREAMING_CHUNK + 3)
+        {
+            for domain in domains(log_height + 1) {
+                let twiddles = FoldingTwiddles::new(domain);
+                let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_height, 2);
+                let beta: EF = random();
+
+                let streamed = twiddles.fold_y_streaming(beta, m.as_view());
+                // Streaming leaves nothing behind in the twiddle cache.
+                assert!(twiddles.inv_y_twiddles.borrow().is_empty());
+                assert_eq!(
+                    streamed,
+                    twiddles.fold_y(beta, m.as_view()),
+                    "log_height = {log_height}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn fold_matrix_same_as_row() {
+        // Include the boundary heights, down to the terminal fold producing a single value. The
+        // domains are large enough to fold from with arity up to 8.
+        for (log_folded_height, domain) in
+            (0..6).flat_map(|h| domains(h + 4).into_iter().map(move |d| (h, d)))
+        {
+            let twiddles = FoldingTwiddles::new(domain);
+            let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_folded_height, 2);
+            let beta: EF = random();
+
+            let mat_y_folded = twiddles.fold_y(beta, m.as_view());
+            let row_y_folded = (0..(1 << log_folded_height))
+                .map(|i| twiddles.fold_y_row(i, log_folded_height, beta, m.row(i)))
+                .collect_vec();
+            assert_eq!(mat_y_folded, row_y_folded);
+
+            for log_arity in 1..=3 {
+                let m = RowMajorMatrix::<EF>::rand(
+                    &mut thread_rng(),
+                    1 << log_folded_height,
+                    1 << log_arity,
+                );
+                let mat_x_folded = twiddles.fold_x(beta, m.as_view());
+                let row_x_folded = (0..(1 << log_folded_height))
+                    .map(|i| twiddles.fold_x_row(i, log_folded_height, beta, m.row(i)))
+                    .collect_vec();
+                assert_eq!(mat_x_folded, row_x_folded, "log_arity = {log_arity}");
+            }
+        }
+    }
+
+    #[test]
+    fn batched_fold_matches_separate_folds() {
+        for (log_folded_height, domain) in
+            (0..6).flat_map(|h| domains(h + 2).into_iter().map(move |d| (h, d)))
+        {
+            let twiddles = FoldingTwiddles::new(domain);
+            let codewords = (0..3)
+                .map(|_| RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_folded_height, 2))
+                .collect_vec();
+            let batched = RowMajorMatrix::new(
+                (0..1 << log_folded_height)
+                    .flat_map(|r| codewords.iter().flat_map(move |m| m.row(r)))
+                    .collect(),
+                6,
+            );
+            let beta: EF = random();
+
+            let folded = twiddles.fold_x_batched(beta, batched);
+            assert_eq!(folded.width(), 3);
+            for (c, m) in codewords.iter().enumerate() {
+                let separately = twiddles.fold_x(beta, m.as_view());
+                assert!(
+                    folded
+                        .rows()
+                        .map(|mut row| row.nth(c).unwrap())
+                        .eq(separately),
+                    "codeword {c} at log_folded_height = {log_folded_height}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn higher_arity_is_repeated_folding() {
+        for log_folded_height in 0..6 {
+            let twiddles = FoldingTwiddles::<F>::default();
+            let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << log_folded_height, 4);
+            let beta: EF = random();
+
+            let once = twiddles.fold_x(beta, RowMajorMatrix::new(m.values.clone(), 2));
+            let twice = twiddles.fold_x(beta.square(), RowMajorMatrix::new(once, 2));
+            assert_eq!(twiddles.fold_x(beta, m.as_view()), twice);
+        }
+    }
+
+    #[test]
+    fn row_folds_match_uncached_twiddles() {
+        for (log_folded_height, domain) in
+            (0..10).flat_map(|h| domains(h + 2).into_iter().map(move |d| (h, d)))
+        {
+            let twiddles = FoldingTwiddles::new(domain);
+            let beta: EF = random();
+            let fold_with =
+                |t: F, lo: EF, hi: EF| (lo + hi + beta * (lo - hi) * t.inverse()).halve();
+            for _ in 0..8 {
+                let index = random::<usize>() % (1 << log_folded_height);
+                let (lo, hi): (EF, EF) = random();
+
+                let t = domain.folded(log_folded_height + 1).nth_y_twiddle(index);
+                assert_eq!(
+                    twiddles.fold_y_row(index, log_folded_height, beta, [lo, hi].into_iter()),
+                    fold_with(t, lo, hi)
+                );
 
-        let mat_x_folded = fold_x::<F, EF>(beta, m.as_view());
-        let row_x_folded = (0..(1 << log_folded_height))
-            .map(|i| fold_x_row::<F, EF>(i, log_folded_height, beta, m.row(i)))
-            .collect_vec();
-        assert_eq!(mat_x_folded, row_x_folded);
+                let t = domain
+                    .folded(log_folded_height + 2)
+                    .nth_x_twiddle(reverse_bits_len(index, log_folded_height));
+                assert_eq!(
+                    twiddles.fold_x_row(index, log_folded_height, beta, [lo, hi].into_iter()),
+                    fold_with(t, lo, hi)
+                );
+            }
+            // One entry per height folded from, each a domain of twice the previous size.
+            assert_eq!(
+                twiddles
+                    .row_fold_points
+                    .borrow()
+                    .keys()
+                    .copied()
+                    .collect_vec(),
+                [log_folded_height + 1, log_folded_height + 2]
+            );
+        }
+    }
+
+    #[test]
+    fn standard_domain_folds_as_before() {
+        for log_n in 2..8 {
+            let twiddles = FoldingTwiddles::new(CircleDomain::<F>::standard(log_n));
+            let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << (log_n - 1), 2);
+            let beta: EF = random();
+            assert_eq!(
+                twiddles.fold_y(beta, m.as_view()),
+                fold_y(beta, m.as_view())
+            );
+            let m = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << (log_n - 2), 2);
+            assert_eq!(
+                twiddles.fold_x(beta, m.as_view()),
+                fold_x(beta, m.as_view())
+            );
+        }
+    }
+
+    #[test]
+    fn terminal_fold_of_constant() {
+        let c: EF = random();
+        let m = RowMajorMatrix::new(vec![c, c], 2);
+        assert_eq!(fold_x(random(), m.as_view()), vec![c]);
+        assert_eq!(fold_y(random(), m.as_view()), vec![c]);
+    }
+
+    #[test]
+    #[should_panic(expected = "already below the blowup")]
+    fn fold_empty_matrix_panics() {
+        let m = RowMajorMatrix::<EF>::new(vec![], 2);
+        fold_x(random(), m.as_view());
     }
 
     #[test]
@@ -186,4 +721,28 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn shifted_domain_folds_to_constant() {
+        for (log_n, log_blowup) in iproduct!(3..6, 1..4) {
+            for domain in domains(log_n + log_blowup) {
+                let twiddles = FoldingTwiddles::new(domain);
+                let mut values = CircleEvaluations::evaluate(
+                    domain,
+                    RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_n, 1),
+                )
+                .to_cfft_order()
+                .values;
+
+                values = twiddles.fold_y(random(), RowMajorMatrix::new(values, 2));
+                for _ in 0..(log_n - 1) {
+                    values = twiddles.fold_x(random(), RowMajorMatrix::new(values, 2));
+                }
+                // Folding down to the blowup leaves a constant, as long as each fold used the
+                // twiddles of the correctly shifted domain.
+                assert_eq!(values.len(), 1 << log_blowup);
+                assert!(values.iter().all_equal(), "shift = {:?}", domain.shift());
+            }
+        }
+    }
 }
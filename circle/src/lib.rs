@@ -10,6 +10,7 @@ mod deep_quotient;
 mod domain;
 mod folding;
 mod ordering;
+mod packing;
 mod pcs;
 mod point;
 mod proof;
@@ -18,6 +19,8 @@ mod verifier;
 
 pub use cfft::*;
 pub use domain::*;
+pub use folding::FoldingTwiddles;
 pub use ordering::*;
 pub use pcs::*;
+pub use point::Point;
 pub use proof::*;
@@ -1,8 +1,7 @@
-use alloc::vec::Vec;
-
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::row_index_mapped::{RowIndexMap, RowIndexMappedView};
 use p3_matrix::Matrix;
+use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_strict_usize, reverse_bits_len};
 
 #[inline]
@@ -18,13 +17,21 @@ pub(crate) fn cfft_permute_index(index: usize, log_n: usize) -> usize {
     )
 }
 
-pub(crate) fn cfft_permute_slice<T: Clone>(xs: &[T]) -> Vec<T> {
+/// The copying counterpart of `cfft_permute_slice_in_place`, kept as a reference for tests.
+#[cfg(test)]
+pub(crate) fn cfft_permute_slice<T: Clone>(xs: &[T]) -> alloc::vec::Vec<T> {
     let log_n = log2_strict_usize(xs.len());
     (0..xs.len())
         .map(|i| xs[cfft_permute_index(i, log_n)].clone())
         .collect()
 }
 
+/// Permute `xs` into (or out of) cfft order without allocating. The permutation is an involution,
+/// so its cycles have length at most 2, and it is applied as a sequence of swaps.
+pub(crate) fn cfft_permute_slice_in_place<T>(xs: &mut [T]) {
+    cfft_permute_slice_chunked_in_place(xs, 1);
+}
+
 pub(crate) fn cfft_permute_slice_chunked_in_place<T>(xs: &mut [T], chunk_size: usize) {
     assert_eq!(xs.len() % chunk_size, 0);
     let n_chunks = xs.len() / chunk_size;
@@ -49,13 +56,48 @@ pub(crate) fn cfft_permute_slice_chunked_in_place<T>(xs: &mut [T], chunk_size: u
     }
 }
 
+/// The number of entries from which `CfftPerm::to_row_major_matrix` gathers rows in parallel rather
+/// than swapping them in place on one thread. The `cfft_perm` benchmark compares the two.
+const PAR_PERMUTE_MIN_LEN: usize = 1 << 16;
+
+/// Gather the rows of `inner` into cfft order in a new matrix, with each output row computing its
+/// source index independently, so the rows can be filled in parallel.
+pub(crate) fn cfft_permute_rows_par<T: Clone + Send + Sync, M: Matrix<T>>(
+    inner: &M,
+) -> RowMajorMatrix<T> {
+    let log_height = log2_strict_usize(inner.height());
+    RowMajorMatrix::from_par_row_fn(inner.height(), inner.width(), |r, row| {
+        row.extend(inner.row(cfft_permute_index(r, log_height)));
+    })
+}
+
 pub type CfftView<M> = RowIndexMappedView<CfftPerm, M>;
 
-#[derive(Copy, Clone)]
+/// Reorders rows between natural and cfft order. The permutation is an involution, so the same
+/// view converts in either direction, and serves as its own inverse.
+#[derive(Copy, Clone, Debug)]
 pub struct CfftPerm {
     log_height: usize,
 }
 
+impl CfftPerm {
+    /// Assumes the inner matrix height is a power of two; panics otherwise.
+    pub fn new_view<T: Send + Sync, Inner: Matrix<T>>(inner: Inner) -> CfftView<Inner> {
+        RowIndexMappedView {
+            index_map: Self {
+                log_height: log2_strict_usize(inner.height()),
+            },
+            inner,
+        }
+    }
+
+    /// Permuting a `CfftView`'s rows again gives back the original matrix, so drop both views
+    /// rather than paying for two lookups per row.
+    pub fn collapse<Inner>(view: CfftView<CfftView<Inner>>) -> Inner {
+        view.inner.inner
+    }
+}
+
 impl RowIndexMap for CfftPerm {
     fn height(&self) -> usize {
         1 << self.log_height
@@ -67,6 +109,9 @@ impl RowIndexMap for CfftPerm {
         &self,
         inner: Inner,
     ) -> RowMajorMatrix<T> {
+        if current_num_threads() > 1 && inner.width() * inner.height() >= PAR_PERMUTE_MIN_LEN {
+            return cfft_permute_rows_par(&inner);
+        }
         let mut inner = inner.to_row_major_matrix();
         cfft_permute_slice_chunked_in_place(&mut inner.values, inner.width);
         inner
@@ -79,18 +124,15 @@ pub(crate) trait CfftPermutable<T: Send + Sync>: Matrix<T> + Sized {
 
 impl<T: Send + Sync, M: Matrix<T>> CfftPermutable<T> for M {
     fn cfft_perm_rows(self) -> CfftView<M> {
-        RowIndexMappedView {
-            index_map: CfftPerm {
-                log_height: log2_strict_usize(self.height()),
-            },
-            inner: self,
-        }
+        CfftPerm::new_view(self)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
+    use p3_mersenne_31::Mersenne31;
+    use rand::thread_rng;
 
     use super::*;
 
@@ -115,4 +157,70 @@ mod tests {
             );
         }
     }
+
+    /// Definition of the cfft ordering, one bit at a time: row `i` holds the point whose index in
+    /// the natural order has its bits given by `i` read backwards, complemented when `i` is odd.
+    fn cfft_permute_index_reference(index: usize, log_n: usize) -> usize {
+        let lsb = index & 1;
+        let mut result = 0;
+        for bit in 0..log_n {
+            let b = ((index >> (bit + 1)) & 1) ^ lsb;
+            result |= b << (log_n - 1 - bit);
+        }
+        result
+    }
+
+    #[test]
+    fn permute_index_matches_reference() {
+        for log_n in 0..=20 {
+            for i in 0..1 << log_n {
+                assert_eq!(
+                    cfft_permute_index(i, log_n),
+                    cfft_permute_index_reference(i, log_n),
+                    "log_n = {log_n}, i = {i}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn permute_in_place() {
+        for log_n in 0..=16 {
+            let original = (0..1 << log_n).map(|i| i * 3 + 1).collect_vec();
+            let mut xs = original.clone();
+            cfft_permute_slice_in_place(&mut xs);
+            assert_eq!(xs, cfft_permute_slice(&original), "log_n = {log_n}");
+            // The permutation is its own inverse.
+            cfft_permute_slice_in_place(&mut xs);
+            assert_eq!(xs, original, "log_n = {log_n}");
+        }
+    }
+
+    #[test]
+    fn parallel_permutation_matches_in_place() {
+        for (log_n, width) in [(0, 1), (1, 3), (10, 1), (12, 5), (17, 1)] {
+            let m = RowMajorMatrix::<Mersenne31>::rand(&mut thread_rng(), 1 << log_n, width);
+            let mut expected = m.clone();
+            cfft_permute_slice_chunked_in_place(&mut expected.values, width);
+            assert_eq!(cfft_permute_rows_par(&m), expected, "log_n = {log_n}");
+            assert_eq!(
+                CfftPerm::new_view(m).to_row_major_matrix(),
+                expected,
+                "log_n = {log_n}"
+            );
+        }
+    }
+
+    #[test]
+    fn double_view_is_identity() {
+        for log_n in 1..=12 {
+            let m = RowMajorMatrix::<Mersenne31>::rand(&mut thread_rng(), 1 << log_n, 3);
+            let twice = CfftPerm::new_view(CfftPerm::new_view(m.as_view()));
+            for r in 0..m.height() {
+                assert!(twice.row(r).eq(m.row(r)), "log_n = {log_n}, r = {r}");
+            }
+            assert_eq!(twice.to_row_major_matrix(), m, "log_n = {log_n}");
+            assert_eq!(CfftPerm::collapse(twice).to_row_major_matrix(), m);
+        }
+    }
 }
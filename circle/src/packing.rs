@@ -0,0 +1,56 @@
+//! Moving extension field elements in and out of `ExtensionPacking`, so that arithmetic on
+//! `F::Packing::WIDTH` of them at a time runs on packed base field coordinates.
+
+use p3_field::{AbstractExtensionField, ExtensionField, Field, PackedValue};
+
+/// Pack the first `F::Packing::WIDTH` elements given by `x`, transposing their coordinates.
+#[inline]
+pub(crate) fn pack_ext<F: Field, EF: ExtensionField<F>>(
+    x: impl Fn(usize) -> EF,
+) -> EF::ExtensionPacking {
+    EF::ExtensionPacking::from_base_fn(|k| F::Packing::from_fn(|j| x(j).as_base_slice()[k]))
+}
+
+/// The `j`th element packed in `packed`.
+#[inline]
+pub(crate) fn unpack_ext<F: Field, EF: ExtensionField<F>>(
+    packed: &EF::ExtensionPacking,
+    j: usize,
+) -> EF {
+    EF::from_base_fn(|k| packed.as_base_slice()[k].as_slice()[j])
+}
+
+/// Apply `packed` to the elements of `row_1` and `row_2`, `F::Packing::WIDTH` pairs at a time,
+/// and `scalar` to the pairs left over. Both must compute the same function.
+#[inline]
+pub(crate) fn apply_to_ext_rows<F: Field, EF: ExtensionField<F>>(
+    row_1: &mut [EF],
+    row_2: &mut [EF],
+    packed: impl Fn(
+        EF::ExtensionPacking,
+        EF::ExtensionPacking,
+    ) -> (EF::ExtensionPacking, EF::ExtensionPacking),
+    scalar: impl Fn(EF, EF) -> (EF, EF),
+) {
+    debug_assert_eq!(row_1.len(), row_2.len());
+    let width = F::Packing::WIDTH;
+    let packed_len = row_1.len() - row_1.len() % width;
+    let (shorts_1, suffix_1) = row_1.split_at_mut(packed_len);
+    let (shorts_2, suffix_2) = row_2.split_at_mut(packed_len);
+    for (xs_1, xs_2) in shorts_1
+        .chunks_exact_mut(width)
+        .zip(shorts_2.chunks_exact_mut(width))
+    {
+        let (y_1, y_2) = packed(
+            pack_ext::<F, EF>(|j| xs_1[j]),
+            pack_ext::<F, EF>(|j| xs_2[j]),
+        );
+        for (j, (x_1, x_2)) in xs_1.iter_mut().zip(xs_2.iter_mut()).enumerate() {
+            *x_1 = unpack_ext::<F, EF>(&y_1, j);
+            *x_2 = unpack_ext::<F, EF>(&y_2, j);
+        }
+    }
+    for (x_1, x_2) in suffix_1.iter_mut().zip(suffix_2) {
+        (*x_1, *x_2) = scalar(*x_1, *x_2);
+    }
+}
@@ -1,15 +1,14 @@
 use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
-use core::marker::PhantomData;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace};
 use p3_field::extension::ComplexExtendable;
 use p3_field::{ExtensionField, Field};
-use p3_fri::verifier::FriError;
-use p3_fri::FriConfig;
+use p3_fri::verifier::{FriError, ProofShapeError};
+use p3_fri::{ChallengerPow, FriConfig, SampleLowBits};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
@@ -19,17 +18,71 @@ use tracing::info_span;
 
 use crate::deep_quotient::{deep_quotient_reduce_row, extract_lambda};
 use crate::domain::CircleDomain;
-use crate::folding::{fold_y, fold_y_row, CircleFriConfig, CircleFriGenericConfig};
+use crate::folding::{CircleFriConfig, CircleFriGenericConfig, FoldingTwiddles};
 use crate::point::Point;
 use crate::prover::prove;
 use crate::verifier::verify;
-use crate::{cfft_permute_index, CfftPermutable, CircleEvaluations, CircleFriProof};
+use crate::{CfftPermutable, CircleEvaluations, CircleFriProof};
 
 #[derive(Debug)]
 pub struct CirclePcs<Val: Field, InputMmcs, FriMmcs> {
     pub mmcs: InputMmcs,
     pub fri_config: FriConfig<FriMmcs>,
-    pub _phantom: PhantomData<Val>,
+    /// Folding twiddles are reused across proofs, as they only depend on the height.
+    folding_twiddles: FoldingTwiddles<Val>,
+    /// Each FRI commit phase round folds up to `2^log_folding_arity` values into one.
+    log_folding_arity: usize,
+}
+
+impl<Val: Field, InputMmcs, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
+    /// Circle FRI always folds down to a constant, so `fri_config.log_final_poly_len` must be 0.
+    pub fn new(mmcs: InputMmcs, fri_config: FriConfig<FriMmcs>) -> Self {
+        assert_eq!(
+            fri_config.log_final_poly_len, 0,
+            "circle FRI does not support a final polynomial of degree > 0"
+        );
+        Self {
+            mmcs,
+            fri_config,
+            folding_twiddles: FoldingTwiddles::default(),
+            log_folding_arity: 1,
+        }
+    }
+
+    /// Fold `2^log_arity` values at a time in each FRI commit phase round, rather than 2, so that
+    /// FRI commits to fewer codewords and each query opens fewer of them. A round folds less when
+    /// folding fully would take it below the blowup or past the height of an input.
+    ///
+    /// Proofs only verify with a PCS using the same arity.
+    pub fn with_log_folding_arity(mut self, log_arity: usize) -> Self {
+        assert_ne!(log_arity, 0, "FRI must fold at least 2 values at a time");
+        self.log_folding_arity = log_arity;
+        self
+    }
+}
+
+impl<Val: Field, InputMmcs: Mmcs<Val>, FriMmcs> CirclePcs<Val, InputMmcs, FriMmcs> {
+    /// Check one query's opening of a committed batch against the batch's commitment, and
+    /// nothing else. Since this is only the MMCS check, it helps to tell whether a proof is
+    /// rejected by the MMCS or by FRI.
+    ///
+    /// `index` is the opened row of the batch's tallest matrix, and `dims` the dimensions of each
+    /// committed matrix.
+    pub fn verify_input_opening(
+        &self,
+        commitment: &InputMmcs::Commitment,
+        index: usize,
+        opening: &BatchOpening<Val, InputMmcs>,
+        dims: &[Dimensions],
+    ) -> Result<(), InputMmcs::Error> {
+        self.mmcs.verify_batch(
+            commitment,
+            dims,
+            index,
+            &opening.opened_values,
+            &opening.opening_proof,
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -188,7 +241,7 @@ where
                                 (Challenge::ONE, vec![Challenge::ZERO; 1 << log_height])
                             });
 
-                        points_for_mat
+                        let openings = points_for_mat
                             .iter()
                             .map(|&zeta| {
                                 let zeta = Point::from_projective_line(zeta);
@@ -199,23 +252,33 @@ where
                                 let ps_at_zeta: Vec<Challenge> =
                                     info_span!("compute opened values with Lagrange interpolation")
                                         .in_scope(|| evals.evaluate_at_point(zeta));
-
-                                // Reduce this matrix, as a deep quotient, into one column with powers of α.
-                                let mat_ros = evals.deep_quotient_reduce(alpha, zeta, &ps_at_zeta);
-
-                                // Fold it into our running reduction, offset by alpha_offset.
-                                reduced_opening_for_log_height
-                                    .par_iter_mut()
-                                    .zip(mat_ros)
-                                    .for_each(|(ro, mat_ro)| {
-                                        *ro += *alpha_offset * mat_ro;
-                                    });
-
-                                // Update alpha_offset from α^i -> α^(i + 2 * width)
-                                *alpha_offset *= alpha.exp_u64(2 * evals.values.width() as u64);
-
-                                ps_at_zeta
+                                (zeta, ps_at_zeta)
                             })
+                            .collect_vec();
+                        if openings.is_empty() {
+                            return vec![];
+                        }
+
+                        // Each point's reduction is offset by the next α^(2 * width), as if the
+                        // matrix were opened at one point after another.
+                        let alpha_pow_2_width = alpha.exp_u64(2 * evals.values.width() as u64);
+                        let scales = alpha_pow_2_width
+                            .shifted_powers(*alpha_offset)
+                            .take(openings.len())
+                            .collect_vec();
+                        *alpha_offset *= alpha_pow_2_width.exp_u64(openings.len() as u64);
+
+                        // Reduce this matrix, as a deep quotient at all its points, into one column
+                        // with powers of α, and fold it into our running reduction.
+                        let mat_ros = evals.deep_quotient_reduce_multi(alpha, &openings, &scales);
+                        reduced_opening_for_log_height
+                            .par_iter_mut()
+                            .zip(mat_ros)
+                            .for_each(|(ro, mat_ro)| *ro += mat_ro);
+
+                        openings
+                            .into_iter()
+                            .map(|(_, ps_at_zeta)| ps_at_zeta)
                             .collect()
                     })
                     .collect()
@@ -257,51 +320,62 @@ where
             .mmcs
             .get_matrices(&first_layer_data)
             .into_iter()
-            .map(|m| fold_y(bivariate_beta, m.as_view()))
+            .map(|m| self.folding_twiddles.fold_y(bivariate_beta, m.as_view()))
             // Reverse, because FRI expects descending by height
             .rev()
             .collect();
 
-        let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> =
-            CircleFriGenericConfig(PhantomData);
-
-        let fri_proof = prove(&g, &self.fri_config, fri_input, challenger, |index| {
-            // CircleFriFolder asks for an extra query index bit, so we use that here to index
-            // the first layer fold.
-
-            // Open the input (big opening, lots of columns) at the full index...
-            let input_openings = rounds
-                .iter()
-                .map(|(data, _)| {
-                    let log_max_batch_height = log2_strict_usize(self.mmcs.get_max_height(data));
-                    let reduced_index = index >> (log_max_height - log_max_batch_height);
-                    let (opened_values, opening_proof) = self.mmcs.open_batch(reduced_index, data);
-                    BatchOpening {
-                        opened_values,
-                        opening_proof,
-                    }
-                })
-                .collect();
+        let g: CircleFriConfig<'_, Val, Challenge, InputMmcs, FriMmcs> =
+            CircleFriGenericConfig::new(&self.folding_twiddles);
 
-            // We committed to first_layer in pairs, so open the reduced index and include the sibling
-            // as part of the input proof.
-            let (first_layer_values, first_layer_proof) = self
-                .fri_config
-                .mmcs
-                .open_batch(index >> 1, &first_layer_data);
-            let first_layer_siblings = izip!(&first_layer_values, &log_heights)
-                .map(|(v, log_height)| {
-                    let reduced_index = index >> (log_max_height - log_height);
-                    let sibling_index = (reduced_index & 1) ^ 1;
-                    v[sibling_index]
-                })
-                .collect();
-            CircleInputProof {
-                input_openings,
-                first_layer_siblings,
-                first_layer_proof,
-            }
-        });
+        let fri_proof = prove(
+            &g,
+            &self.fri_config,
+            self.log_folding_arity,
+            &SampleLowBits,
+            &ChallengerPow,
+            fri_input,
+            challenger,
+            |index| {
+                // CircleFriFolder asks for an extra query index bit, so we use that here to index
+                // the first layer fold.
+
+                // Open the input (big opening, lots of columns) at the full index...
+                let input_openings = rounds
+                    .iter()
+                    .map(|(data, _)| {
+                        let log_max_batch_height =
+                            log2_strict_usize(self.mmcs.get_max_height(data));
+                        let reduced_index = index >> (log_max_height - log_max_batch_height);
+                        let (opened_values, opening_proof) =
+                            self.mmcs.open_batch(reduced_index, data);
+                        BatchOpening {
+                            opened_values,
+                            opening_proof,
+                        }
+                    })
+                    .collect();
+
+                // We committed to first_layer in pairs, so open the reduced index and include the sibling
+                // as part of the input proof.
+                let (first_layer_values, first_layer_proof) = self
+                    .fri_config
+                    .mmcs
+                    .open_batch(index >> 1, &first_layer_data);
+                let first_layer_siblings = izip!(&first_layer_values, &log_heights)
+                    .map(|(v, log_height)| {
+                        let reduced_index = index >> (log_max_height - log_height);
+                        let sibling_index = (reduced_index & 1) ^ 1;
+                        v[sibling_index]
+                    })
+                    .collect();
+                CircleInputProof {
+                    input_openings,
+                    first_layer_siblings,
+                    first_layer_proof,
+                }
+            },
+        );
 
         (
             values,
@@ -339,16 +413,26 @@ where
         challenger.observe(proof.first_layer_commitment.clone());
         let bivariate_beta: Challenge = challenger.sample_ext_element();
 
-        // +1 to account for first layer
-        let log_global_max_height =
-            proof.fri_proof.commit_phase_commits.len() + self.fri_config.log_blowup + 1;
+        let Some(log_global_max_height) = rounds
+            .iter()
+            .flat_map(|(_, mats)| mats)
+            .map(|(domain, _)| domain.log_n + self.fri_config.log_blowup)
+            .max()
+        else {
+            return Err(FriError::InvalidProofShape(ProofShapeError::NoClaims));
+        };
 
-        let g: CircleFriConfig<Val, Challenge, InputMmcs, FriMmcs> =
-            CircleFriGenericConfig(PhantomData);
+        let g: CircleFriConfig<'_, Val, Challenge, InputMmcs, FriMmcs> =
+            CircleFriGenericConfig::new(&self.folding_twiddles);
 
         verify(
             &g,
             &self.fri_config,
+            self.log_folding_arity,
+            &SampleLowBits,
+            &ChallengerPow,
+            // -1 to account for the first layer, which is folded before FRI.
+            log_global_max_height - 1,
             &proof.fri_proof,
             challenger,
             |index, input_proof| {
@@ -375,25 +459,21 @@ where
                     let log_batch_max_height =
                         log2_strict_usize(batch_heights.iter().max().copied().unwrap());
 
-                    self.mmcs
-                        .verify_batch(
-                            batch_commit,
-                            &batch_dims,
-                            index >> (log_global_max_height - log_batch_max_height),
-                            &batch_opening.opened_values,
-                            &batch_opening.opening_proof,
-                        )
-                        .map_err(InputError::InputMmcsError)?;
+                    self.verify_input_opening(
+                        batch_commit,
+                        index >> (log_global_max_height - log_batch_max_height),
+                        batch_opening,
+                        &batch_dims,
+                    )
+                    .map_err(InputError::InputMmcsError)?;
 
                     for (ps_at_x, (mat_domain, mat_points_and_values)) in
                         izip!(&batch_opening.opened_values, mats)
                     {
                         let log_height = mat_domain.log_n + self.fri_config.log_blowup;
                         let bits_reduced = log_global_max_height - log_height;
-                        let orig_idx = cfft_permute_index(index >> bits_reduced, log_height);
-
                         let committed_domain = CircleDomain::standard(log_height);
-                        let x = committed_domain.nth_point(orig_idx);
+                        let x = committed_domain.point_at_cfft(index >> bits_reduced);
 
                         let (alpha_offset, ro) = reduced_openings
                             .entry(log_height)
@@ -420,10 +500,8 @@ where
 
                             let orig_size = log_height - self.fri_config.log_blowup;
                             let bits_reduced = log_global_max_height - log_height;
-                            let orig_idx = cfft_permute_index(index >> bits_reduced, log_height);
-
                             let lde_domain = CircleDomain::standard(log_height);
-                            let p: Point<Val> = lde_domain.nth_point(orig_idx);
+                            let p: Point<Val> = lde_domain.point_at_cfft(index >> bits_reduced);
 
                             let lambda_corrected = ro - lambda * p.v_n(orig_size);
 
@@ -433,7 +511,7 @@ where
                             let fri_input = (
                                 // - 1 here is because we have already folded a layer.
                                 log_height - 1,
-                                fold_y_row(
+                                self.folding_twiddles.fold_y_row(
                                     index >> (bits_reduced + 1),
                                     // - 1 here is log_arity.
                                     log_height - 1,
@@ -476,6 +554,7 @@ mod tests {
     use p3_challenger::{HashChallenger, SerializingChallenger32};
     use p3_commit::ExtensionMmcs;
     use p3_field::extension::BinomialExtensionField;
+    use p3_field::AbstractField;
     use p3_keccak::Keccak256Hash;
     use p3_merkle_tree::MerkleTreeMmcs;
     use p3_mersenne_31::Mersenne31;
@@ -485,44 +564,43 @@ mod tests {
 
     use super::*;
 
-    #[test]
-    fn circle_pcs() {
-        // Very simple pcs test. More rigorous tests in p3_fri/tests/pcs.
-
-        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+    type Val = Mersenne31;
+    type Challenge = BinomialExtensionField<Mersenne31, 3>;
 
-        type Val = Mersenne31;
-        type Challenge = BinomialExtensionField<Mersenne31, 3>;
+    type ByteHash = Keccak256Hash;
+    type FieldHash = SerializingHasher32<ByteHash>;
+    type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
+    type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+    type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
+    type MyPcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
 
-        type ByteHash = Keccak256Hash;
-        type FieldHash = SerializingHasher32<ByteHash>;
+    fn get_pcs() -> MyPcs {
         let byte_hash = ByteHash {};
         let field_hash = FieldHash::new(byte_hash);
-
-        type MyCompress = CompressionFunctionFromHasher<ByteHash, 2, 32>;
         let compress = MyCompress::new(byte_hash);
-
-        type ValMmcs = MerkleTreeMmcs<Val, u8, FieldHash, MyCompress, 32>;
         let val_mmcs = ValMmcs::new(field_hash, compress);
-
-        type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
-
-        type Challenger = SerializingChallenger32<Val, HashChallenger<u8, ByteHash, 32>>;
-
         let fri_config = FriConfig {
             log_blowup: 1,
+            log_final_poly_len: 0,
             num_queries: 2,
             proof_of_work_bits: 1,
+            omit_zero_bit_pow: false,
             mmcs: challenge_mmcs,
         };
+        MyPcs::new(val_mmcs, fri_config)
+    }
 
-        type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
-        let pcs = Pcs {
-            mmcs: val_mmcs,
-            fri_config,
-            _phantom: PhantomData,
-        };
+    #[test]
+    fn circle_pcs() {
+        // Very simple pcs test. More rigorous tests in p3_fri/tests/pcs.
+
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let byte_hash = ByteHash {};
+
+        type Pcs = MyPcs;
+        let pcs = get_pcs();
 
         let log_n = 10;
 
@@ -549,4 +627,144 @@ mod tests {
         )
         .expect("verify err");
     }
+
+    #[test]
+    fn open_at_multiple_points() {
+        type Pcs = MyPcs;
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let byte_hash = ByteHash {};
+        let pcs = get_pcs();
+
+        let domains_and_evals = [8, 8, 6]
+            .into_iter()
+            .map(|log_n| {
+                (
+                    CircleDomain::standard(log_n),
+                    RowMajorMatrix::rand(&mut rng, 1 << log_n, 3),
+                )
+            })
+            .collect_vec();
+        // Each matrix at three points, as if opened at zeta, its rotation, and one more.
+        let points_for_mats = (0..domains_and_evals.len())
+            .map(|_| (0..3).map(|_| rng.gen::<Challenge>()).collect_vec())
+            .collect_vec();
+
+        let (comm, data) =
+            <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, domains_and_evals.clone());
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        let (values, proof) = pcs.open(vec![(&data, points_for_mats.clone())], &mut chal);
+
+        let claims = izip!(&domains_and_evals, &points_for_mats, &values[0])
+            .map(|((domain, evals), points, mat_values)| {
+                let claims = izip!(points, mat_values)
+                    .map(|(&zeta, values)| {
+                        let zeta_point = Point::from_projective_line(zeta);
+                        let expected =
+                            CircleEvaluations::from_natural_order(*domain, evals.clone())
+                                .evaluate_at_point(zeta_point);
+                        assert_eq!(values, &expected);
+                        (zeta, values.clone())
+                    })
+                    .collect_vec();
+                (*domain, claims)
+            })
+            .collect();
+        let mut chal = Challenger::from_hasher(vec![], byte_hash);
+        pcs.verify(vec![(comm, claims)], &proof, &mut chal)
+            .expect("verify err");
+    }
+
+    #[test]
+    fn higher_folding_arity() {
+        type Pcs = MyPcs;
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let byte_hash = ByteHash {};
+
+        // Heights an odd number of folds apart, so some rounds must fold less.
+        let domains_and_evals = [10, 7, 4]
+            .into_iter()
+            .map(|log_n| {
+                (
+                    CircleDomain::standard(log_n),
+                    RowMajorMatrix::rand(&mut rng, 1 << log_n, 2),
+                )
+            })
+            .collect_vec();
+        let zeta: Challenge = rng.gen();
+
+        let prove_and_verify = |prover: &Pcs, verifier: &Pcs| {
+            let (comm, data) = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(
+                prover,
+                domains_and_evals.clone(),
+            );
+            let mut chal = Challenger::from_hasher(vec![], byte_hash);
+            let (values, proof) = prover.open(vec![(&data, vec![vec![zeta]; 3])], &mut chal);
+            let claims = izip!(&domains_and_evals, &values[0])
+                .map(|((domain, _), mat_values)| (*domain, vec![(zeta, mat_values[0].clone())]))
+                .collect();
+
+            let mut chal = Challenger::from_hasher(vec![], byte_hash);
+            let result = verifier.verify(vec![(comm, claims)], &proof, &mut chal);
+            (proof.fri_proof.num_commit_phase_rounds(), result)
+        };
+
+        let (binary_rounds, result) = prove_and_verify(&get_pcs(), &get_pcs());
+        result.expect("arity 2 should verify");
+        assert_eq!(binary_rounds, 9);
+
+        let quaternary = get_pcs().with_log_folding_arity(2);
+        let (quaternary_rounds, result) = prove_and_verify(&quaternary, &quaternary);
+        result.expect("arity 4 should verify");
+        // From 2^10 down to the blowup, stopping at 2^7 and 2^4 to roll in the other inputs.
+        assert_eq!(quaternary_rounds, 6);
+
+        let (_, result) = prove_and_verify(&quaternary, &get_pcs());
+        assert!(
+            result.is_err(),
+            "a proof should only verify with its own arity"
+        );
+    }
+
+    #[test]
+    fn verify_input_opening_flags_corruption() {
+        type Pcs = MyPcs;
+        let mut rng = ChaCha8Rng::from_seed([0; 32]);
+        let pcs = get_pcs();
+
+        let domains_and_evals = [8, 6]
+            .into_iter()
+            .map(|log_n| {
+                let d = <Pcs as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    1 << log_n,
+                );
+                (d, RowMajorMatrix::rand(&mut rng, 1 << log_n, 3))
+            })
+            .collect_vec();
+        let (comm, data) =
+            <Pcs as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, domains_and_evals);
+        let dims = pcs
+            .mmcs
+            .get_matrices(&data)
+            .into_iter()
+            .map(|m| m.dimensions())
+            .collect_vec();
+
+        let index = 37;
+        let (opened_values, opening_proof) = pcs.mmcs.open_batch(index, &data);
+        let mut opening = BatchOpening {
+            opened_values,
+            opening_proof,
+        };
+        pcs.verify_input_opening(&comm, index, &opening, &dims)
+            .expect("honest opening should verify");
+        assert!(pcs
+            .verify_input_opening(&comm, index + 1, &opening, &dims)
+            .is_err());
+
+        opening.opened_values[1][2] += Val::ONE;
+        assert!(pcs
+            .verify_input_opening(&comm, index, &opening, &dims)
+            .is_err());
+    }
 }
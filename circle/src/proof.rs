@@ -15,7 +15,26 @@ pub struct CircleFriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
     // This could become Vec<FC::Challenge> if this library was generalized to support non-constant
     // final polynomials.
     pub final_poly: F,
-    pub pow_witness: Witness,
+    /// The proof-of-work witness, absent when the config asks for zero bits of grinding.
+    pub pow_witness: Option<Witness>,
+}
+
+impl<F: Field, M: Mmcs<F>, Witness, InputProof> CircleFriProof<F, M, Witness, InputProof> {
+    /// The number of commit phase rounds. Each round applies as many univariate folds as its
+    /// arity allows, so this is the number of folds only when folding with arity 2.
+    pub fn num_commit_phase_rounds(&self) -> usize {
+        self.commit_phase_commits.len()
+    }
+
+    pub const fn final_poly(&self) -> &F {
+        &self.final_poly
+    }
+
+    /// The number of coefficients of the final polynomial. The input is folded all the way down to
+    /// a constant, so for now this is always 1.
+    pub const fn final_poly_len(&self) -> usize {
+        1
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -33,10 +52,9 @@ pub struct CircleQueryProof<F: Field, M: Mmcs<F>, InputProof> {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CircleCommitPhaseProofStep<F: Field, M: Mmcs<F>> {
-    /// The opening of the commit phase codeword at the sibling location.
-    // This may change to Vec<FC::Challenge> if the library is generalized to support other FRI
-    // folding arities besides 2, meaning that there can be multiple siblings.
-    pub sibling_value: F,
+    /// The openings of the commit phase codeword at the other locations of the queried leaf, in
+    /// order. There is one fewer of them than the round's folding arity.
+    pub sibling_values: Vec<F>,
 
     pub opening_proof: M::Proof,
 }
@@ -6,17 +6,22 @@ use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field};
-use p3_fri::{FriConfig, FriGenericConfig};
+use p3_fri::{FriConfig, FriGenericConfig, PowStrategy, QueryIndexSampler};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_util::log2_strict_usize;
 use tracing::{info_span, instrument};
 
+use crate::folding::log_round_arity;
 use crate::{CircleCommitPhaseProofStep, CircleFriProof, CircleQueryProof};
 
+#[allow(clippy::too_many_arguments)]
 #[instrument(name = "FRI prover", skip_all)]
 pub fn prove<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    log_max_arity: usize,
+    query_sampler: &impl QueryIndexSampler<Challenger>,
+    pow: &impl PowStrategy<Challenger>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize) -> G::InputProof,
@@ -36,22 +41,28 @@ where
 
     let log_max_height = log2_strict_usize(inputs[0].len());
 
-    let commit_phase_result = commit_phase(g, config, inputs, challenger);
+    let commit_phase_result = commit_phase(g, config, log_max_arity, inputs, challenger);
 
-    let pow_witness = challenger.grind(config.proof_of_work_bits);
+    // With zero bits and `omit_zero_bit_pow`, the transcript skips the witness entirely.
+    let pow_witness = config
+        .grinds()
+        .then(|| pow.grind(challenger, config.proof_of_work_bits));
 
     let query_proofs = info_span!("query phase").in_scope(|| {
-        iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
-            .take(config.num_queries)
-            .map(|index| CircleQueryProof {
-                input_proof: open_input(index),
-                commit_phase_openings: answer_query(
-                    config,
-                    &commit_phase_result.data,
-                    index >> g.extra_query_index_bits(),
-                ),
-            })
-            .collect()
+        iter::repeat_with(|| {
+            query_sampler.sample_index(challenger, log_max_height + g.extra_query_index_bits())
+        })
+        .take(config.num_queries)
+        .map(|index| CircleQueryProof {
+            input_proof: open_input(index),
+            commit_phase_openings: answer_query(
+                config,
+                &commit_phase_result.data,
+                &commit_phase_result.log_arities,
+                index >> g.extra_query_index_bits(),
+            ),
+        })
+        .collect()
     });
 
     CircleFriProof {
@@ -65,6 +76,8 @@ where
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
+    /// The log arity each round folded with.
+    log_arities: Vec<usize>,
     final_poly: F,
 }
 
@@ -72,6 +85,7 @@ struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
 fn commit_phase<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    log_max_arity: usize,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
 ) -> CommitPhaseResult<Challenge, M>
@@ -86,9 +100,20 @@ where
     let mut folded = inputs_iter.next().unwrap();
     let mut commits = vec![];
     let mut data = vec![];
+    let mut log_arities = vec![];
 
     while folded.len() > config.blowup() {
-        let leaves = RowMajorMatrix::new(folded, 2);
+        let log_next_height = inputs_iter
+            .peek()
+            .map_or(config.log_blowup, |v| log2_strict_usize(v.len()))
+            .max(config.log_blowup);
+        let log_arity = log_round_arity(
+            log_max_arity,
+            log2_strict_usize(folded.len()),
+            log_next_height,
+        );
+
+        let leaves = RowMajorMatrix::new(folded, 1 << log_arity);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
 
@@ -99,6 +124,7 @@ where
 
         commits.push(commit);
         data.push(prover_data);
+        log_arities.push(log_arity);
 
         if let Some(v) = inputs_iter.next_if(|v| v.len() == folded.len()) {
             izip!(&mut folded, v).for_each(|(c, x)| *c += x);
@@ -116,6 +142,7 @@ where
     CommitPhaseResult {
         commits,
         data,
+        log_arities,
         final_poly,
     }
 }
@@ -123,28 +150,30 @@ where
 fn answer_query<F, M>(
     config: &FriConfig<M>,
     commit_phase_commits: &[M::ProverData<RowMajorMatrix<F>>],
-    index: usize,
+    log_arities: &[usize],
+    mut index: usize,
 ) -> Vec<CircleCommitPhaseProofStep<F, M>>
 where
     F: Field,
     M: Mmcs<F>,
 {
-    commit_phase_commits
-        .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let index_i = index >> i;
-            let index_i_sibling = index_i ^ 1;
-            let index_pair = index_i >> 1;
+    izip!(commit_phase_commits, log_arities)
+        .map(|(commit, &log_arity)| {
+            let index_leaf = index >> log_arity;
 
-            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_pair, commit);
+            let (mut opened_rows, opening_proof) = config.mmcs.open_batch(index_leaf, commit);
             assert_eq!(opened_rows.len(), 1);
-            let opened_row = opened_rows.pop().unwrap();
-            assert_eq!(opened_row.len(), 2, "Committed data should be in pairs");
-            let sibling_value = opened_row[index_i_sibling % 2];
+            let mut sibling_values = opened_rows.pop().unwrap();
+            assert_eq!(
+                sibling_values.len(),
+                1 << log_arity,
+                "committed leaves should have one value per folded position"
+            );
+            sibling_values.remove(index & ((1 << log_arity) - 1));
+            index = index_leaf;
 
             CircleCommitPhaseProofStep {
-                sibling_value,
+                sibling_values,
                 opening_proof,
             }
         })
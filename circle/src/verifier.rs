@@ -1,19 +1,28 @@
-use alloc::vec;
 use alloc::vec::Vec;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
 use p3_field::{ExtensionField, Field};
-use p3_fri::verifier::FriError;
-use p3_fri::{FriConfig, FriGenericConfig};
+use p3_fri::verifier::{FriError, ProofShapeError};
+use p3_fri::{FriConfig, FriGenericConfig, PowStrategy, QueryIndexSampler};
 use p3_matrix::Dimensions;
 
+use crate::folding::log_round_arity;
 use crate::{CircleCommitPhaseProofStep, CircleFriProof};
 
+/// Verify a FRI proof of inputs whose tallest has height `2^log_max_height`.
+///
+/// Since rounds may fold with different arities, the number of rounds alone doesn't determine
+/// that height; the caller knows it from what was committed.
+#[allow(clippy::too_many_arguments)]
 pub fn verify<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    log_max_arity: usize,
+    query_sampler: &impl QueryIndexSampler<Challenger>,
+    pow: &impl PowStrategy<Challenger>,
+    log_max_height: usize,
     proof: &CircleFriProof<Challenge, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
@@ -36,19 +45,43 @@ where
     challenger.observe_ext_element(proof.final_poly);
 
     if proof.query_proofs.len() != config.num_queries {
-        return Err(FriError::InvalidProofShape);
+        return Err(FriError::InvalidProofShape(ProofShapeError::NumQueries {
+            expected: config.num_queries,
+            got: proof.query_proofs.len(),
+        }));
     }
 
-    // Check PoW.
-    if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
-        return Err(FriError::InvalidPowWitness);
+    // Check PoW. A witness is present exactly when the config asks for grinding.
+    match (config.grinds(), proof.pow_witness) {
+        (false, None) => {}
+        (false, Some(_)) => {
+            return Err(FriError::InvalidProofShape(
+                ProofShapeError::UnexpectedPowWitness,
+            ))
+        }
+        (true, None) => return Err(FriError::InvalidPowWitness),
+        (true, Some(witness)) => {
+            if !pow.check_witness(challenger, config.proof_of_work_bits, witness) {
+                return Err(FriError::InvalidPowWitness);
+            }
+        }
     }
 
-    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
+    for (query, qp) in proof.query_proofs.iter().enumerate() {
+        if qp.commit_phase_openings.len() != proof.num_commit_phase_rounds() {
+            return Err(FriError::InvalidProofShape(
+                ProofShapeError::CommitPhaseOpenings {
+                    query,
+                    expected: proof.num_commit_phase_rounds(),
+                    got: qp.commit_phase_openings.len(),
+                },
+            ));
+        }
 
-    for qp in &proof.query_proofs {
-        let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
-        let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
+        let index =
+            query_sampler.sample_index(challenger, log_max_height + g.extra_query_index_bits());
+        let ro = open_input(index, &qp.input_proof)
+            .map_err(|source| FriError::InputError { query, source })?;
 
         debug_assert!(
             ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
@@ -58,6 +91,8 @@ where
         let folded_eval = verify_query(
             g,
             config,
+            log_max_arity,
+            query,
             index >> g.extra_query_index_bits(),
             izip!(
                 &betas,
@@ -69,7 +104,7 @@ where
         )?;
 
         if folded_eval != proof.final_poly {
-            return Err(FriError::FinalPolyMismatch);
+            return Err(FriError::FinalPolyMismatch { query });
         }
     }
 
@@ -82,11 +117,14 @@ type CommitStep<'a, F, M> = (
     &'a CircleCommitPhaseProofStep<F, M>,
 );
 
+#[allow(clippy::too_many_arguments)]
 fn verify_query<'a, G, F, M>(
     g: &G,
     config: &FriConfig<M>,
+    log_max_arity: usize,
+    query: usize,
     mut index: usize,
-    steps: impl Iterator<Item = CommitStep<'a, F, M>>,
+    mut steps: impl Iterator<Item = CommitStep<'a, F, M>>,
     reduced_openings: Vec<(usize, F)>,
     log_max_height: usize,
 ) -> Result<F, FriError<M::Error, G::InputError>>
@@ -98,19 +136,43 @@ where
     let mut folded_eval = F::ZERO;
     let mut ro_iter = reduced_openings.into_iter().peekable();
 
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
-        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
+    let mut log_height = log_max_height;
+
+    for round in 0.. {
+        if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_height) {
             folded_eval += ro;
         }
+        if log_height <= config.log_blowup {
+            break;
+        }
 
-        let index_sibling = index ^ 1;
-        let index_pair = index >> 1;
+        let log_next_height = ro_iter
+            .peek()
+            .map_or(config.log_blowup, |(lh, _)| *lh)
+            .max(config.log_blowup);
+        let log_arity = log_round_arity(log_max_arity, log_height, log_next_height);
+        let log_folded_height = log_height - log_arity;
+
+        let (&beta, comm, opening) = steps.next().ok_or(FriError::InvalidProofShape(
+            ProofShapeError::FoldingRounds { query },
+        ))?;
+        if opening.sibling_values.len() != (1 << log_arity) - 1 {
+            return Err(FriError::InvalidProofShape(
+                ProofShapeError::SiblingValues {
+                    query,
+                    round,
+                    expected: (1 << log_arity) - 1,
+                    got: opening.sibling_values.len(),
+                },
+            ));
+        }
 
-        let mut evals = vec![folded_eval; 2];
-        evals[index_sibling % 2] = opening.sibling_value;
+        let index_leaf = index >> log_arity;
+        let mut evals = opening.sibling_values.clone();
+        evals.insert(index & ((1 << log_arity) - 1), folded_eval);
 
         let dims = &[Dimensions {
-            width: 2,
+            width: 1 << log_arity,
             height: 1 << log_folded_height,
         }];
         config
@@ -118,17 +180,30 @@ where
             .verify_batch(
                 comm,
                 dims,
-                index_pair,
+                index_leaf,
                 &[evals.clone()],
                 &opening.opening_proof,
             )
-            .map_err(FriError::CommitPhaseMmcsError)?;
+            .map_err(|source| FriError::CommitPhaseMmcsError {
+                query,
+                round,
+                log_height,
+                source,
+            })?;
 
-        index = index_pair;
+        index = index_leaf;
+        log_height = log_folded_height;
 
         folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
     }
 
+    // Every round must have been used up by the folds above.
+    if steps.next().is_some() {
+        return Err(FriError::InvalidProofShape(
+            ProofShapeError::FoldingRounds { query },
+        ));
+    }
+
     debug_assert!(index < config.blowup(), "index was {}", index);
     debug_assert!(
         ro_iter.next().is_none(),
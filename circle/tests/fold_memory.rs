@@ -0,0 +1,38 @@
+//! Compares the peak memory of a one-off `fold_y`, which builds the twiddles of the whole domain,
+//! with `fold_y_streaming`, which only holds a chunk of them at a time.
+
+use p3_circle::FoldingTwiddles;
+use p3_field::extension::BinomialExtensionField;
+use p3_field_testing::{exclusive_tracking, track_allocations, TrackingAllocator};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_mersenne_31::Mersenne31;
+use rand::{random, thread_rng};
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+type F = Mersenne31;
+type EF = BinomialExtensionField<F, 3>;
+
+#[test]
+fn streaming_fold_y_memory() {
+    let _tracking = exclusive_tracking();
+    let log_n = 20;
+    let evals = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 1 << (log_n - 1), 2);
+    let beta: EF = random();
+    let output_bytes = (1 << (log_n - 1)) * size_of::<EF>();
+    let twiddle_bytes = (1 << (log_n - 1)) * size_of::<F>();
+
+    let (cached, cached_stats) =
+        track_allocations(|| FoldingTwiddles::<F>::default().fold_y(beta, evals.as_view()));
+    let (streamed, streamed_stats) = track_allocations(|| {
+        FoldingTwiddles::<F>::default().fold_y_streaming(beta, evals.as_view())
+    });
+    assert_eq!(streamed, cached);
+
+    // The cached fold holds the whole domain's twiddles alongside its output, while the streamed
+    // chunks are a rounding error next to the output.
+    let (cached_peak, streamed_peak) = (cached_stats.peak_bytes, streamed_stats.peak_bytes);
+    assert!(cached_peak >= output_bytes + twiddle_bytes, "{cached_peak}");
+    assert!(streamed_peak < output_bytes + (1 << 16), "{streamed_peak}");
+}
@@ -31,6 +31,7 @@ where
     type ProverData<M> = InnerMmcs::ProverData<FlatMatrixView<F, EF, M>>;
     type Commitment = InnerMmcs::Commitment;
     type Proof = InnerMmcs::Proof;
+    type MultiProof = InnerMmcs::MultiProof;
     type Error = InnerMmcs::Error;
 
     fn commit<M: Matrix<EF>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>) {
@@ -51,6 +52,23 @@ where
         (opened_ext_values, proof)
     }
 
+    fn open_multi_batch<M: Matrix<EF>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Vec<EF>>>, Self::MultiProof) {
+        let (opened_base_values, proof) = self.inner.open_multi_batch(indices, prover_data);
+        let opened_ext_values = opened_base_values
+            .into_iter()
+            .map(|rows| {
+                rows.into_iter()
+                    .map(|row| row.chunks(EF::D).map(EF::from_base_slice).collect())
+                    .collect()
+            })
+            .collect();
+        (opened_ext_values, proof)
+    }
+
     fn get_matrices<'a, M: Matrix<EF>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M> {
         self.inner
             .get_matrices(prover_data)
@@ -86,4 +104,41 @@ where
         self.inner
             .verify_batch(commit, &base_dimensions, index, &opened_base_values, proof)
     }
+
+    fn verify_multi_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<EF>>],
+        proof: &Self::MultiProof,
+    ) -> Result<(), Self::Error> {
+        let opened_base_values: Vec<Vec<Vec<F>>> = opened_values
+            .iter()
+            .map(|rows| {
+                rows.iter()
+                    .map(|row| {
+                        row.iter()
+                            .flat_map(|el| el.as_base_slice())
+                            .copied()
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        let base_dimensions = dimensions
+            .iter()
+            .map(|dim| Dimensions {
+                width: dim.width * EF::D,
+                height: dim.height,
+            })
+            .collect::<Vec<_>>();
+        self.inner.verify_multi_batch(
+            commit,
+            &base_dimensions,
+            indices,
+            &opened_base_values,
+            proof,
+        )
+    }
 }
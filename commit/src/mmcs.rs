@@ -20,6 +20,9 @@ pub trait Mmcs<T: Send + Sync>: Clone {
     type ProverData<M>;
     type Commitment: Clone + Serialize + DeserializeOwned;
     type Proof: Clone + Serialize + DeserializeOwned;
+    /// A proof for opening several row indices at once. Implementations may share data between
+    /// the individual openings, e.g. Merkle tree nodes which are common to several paths.
+    type MultiProof: Clone + Serialize + DeserializeOwned;
     type Error: Debug;
 
     fn commit<M: Matrix<T>>(&self, inputs: Vec<M>) -> (Self::Commitment, Self::ProverData<M>);
@@ -45,6 +48,16 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         prover_data: &Self::ProverData<M>,
     ) -> (Vec<Vec<T>>, Self::Proof);
 
+    /// Opens a batch of rows from committed matrices at each of the given indices.
+    /// returns `(openings, proof)`
+    /// where `openings[i]` is what `open_batch` would return for `indices[i]`, and `proof` is a
+    /// single proof covering all of the indices.
+    fn open_multi_batch<M: Matrix<T>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Vec<T>>>, Self::MultiProof);
+
     /// Get the matrices that were committed to.
     fn get_matrices<'a, M: Matrix<T>>(&self, prover_data: &'a Self::ProverData<M>) -> Vec<&'a M>;
 
@@ -55,12 +68,12 @@ pub trait Mmcs<T: Send + Sync>: Clone {
             .collect()
     }
 
-    /// Get the largest height of any committed matrix.
+    /// Get the largest height of any committed matrix, or 0 if no matrices were committed.
     fn get_max_height<M: Matrix<T>>(&self, prover_data: &Self::ProverData<M>) -> usize {
         self.get_matrix_heights(prover_data)
             .into_iter()
             .max()
-            .unwrap_or_else(|| panic!("No committed matrices?"))
+            .unwrap_or(0)
     }
 
     /// Verify a batch opening.
@@ -76,4 +89,16 @@ pub trait Mmcs<T: Send + Sync>: Clone {
         opened_values: &[Vec<T>],
         proof: &Self::Proof,
     ) -> Result<(), Self::Error>;
+
+    /// Verify a multi-batch opening, as produced by `open_multi_batch`.
+    /// `opened_values[i]` holds the opened rows for `indices[i]`, following the same semantics as
+    /// `verify_batch`.
+    fn verify_multi_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<T>>],
+        proof: &Self::MultiProof,
+    ) -> Result<(), Self::Error>;
 }
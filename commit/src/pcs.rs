@@ -3,6 +3,7 @@
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
+use p3_challenger::CanObserve;
 use p3_field::ExtensionField;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
@@ -41,6 +42,22 @@ where
         evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
     ) -> (Self::Commitment, Self::ProverData);
 
+    /// Like `commit`, but also observes the commitment, so that every challenge sampled from
+    /// here on depends on it, as the verifier's will once it observes the same commitment.
+    #[allow(clippy::type_complexity)]
+    fn commit_and_observe(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val<Self::Domain>>)>,
+        challenger: &mut Challenger,
+    ) -> (Self::Commitment, Self::ProverData)
+    where
+        Challenger: CanObserve<Self::Commitment>,
+    {
+        let (commitment, prover_data) = self.commit(evaluations);
+        challenger.observe(commitment.clone());
+        (commitment, prover_data)
+    }
+
     fn get_evaluations_on_domain<'a>(
         &self,
         prover_data: &'a Self::ProverData,
@@ -48,6 +65,17 @@ where
         domain: Self::Domain,
     ) -> impl Matrix<Val<Self::Domain>> + 'a;
 
+    /// Open each round's matrices at the given points.
+    ///
+    /// The prover data is only borrowed, so a committed round can be opened any number of times,
+    /// e.g. at fresh points in each round of an interactive protocol, without recomputing its
+    /// LDE. Each call produces a standalone proof, checked by its own call to `verify` on a
+    /// challenger in the state the prover's was in when opening.
+    ///
+    /// A matrix given no points is skipped: it gets no opened values and contributes nothing to
+    /// the proof beyond the rows of it that the batch's Merkle openings already cover. So a subset
+    /// of a committed batch is opened by passing empty point lists for the rest, and verified by
+    /// claiming no points for them.
     fn open(
         &self,
         // For each round,
@@ -90,3 +118,22 @@ pub type OpenedValues<F> = Vec<OpenedValuesForRound<F>>;
 pub type OpenedValuesForRound<F> = Vec<OpenedValuesForMatrix<F>>;
 pub type OpenedValuesForMatrix<F> = Vec<OpenedValuesForPoint<F>>;
 pub type OpenedValuesForPoint<F> = Vec<F>;
+
+/// Every opened value, in the canonical order for observing them into a challenger: by round,
+/// then matrix, then point, then column.
+pub fn iter_flat<F>(opened_values: &OpenedValues<F>) -> impl Iterator<Item = &F> {
+    opened_values.iter().flatten().flatten().flatten()
+}
+
+/// The claimed values passed to `Pcs::verify`, in the same order as `iter_flat` yields the values
+/// returned by `Pcs::open`, so that a verifier observes exactly what the prover did.
+#[allow(clippy::type_complexity)]
+pub fn iter_flat_claims<Commitment, Domain, F>(
+    rounds: &[(Commitment, Vec<(Domain, Vec<(F, Vec<F>)>)>)],
+) -> impl Iterator<Item = &F> {
+    rounds
+        .iter()
+        .flat_map(|(_, mats)| mats)
+        .flat_map(|(_, points)| points)
+        .flat_map(|(_, values)| values)
+}
@@ -36,12 +36,15 @@ fn bench_fft(c: &mut Criterion) {
     m31_fft::<Mersenne31ComplexRadix2Dit, BATCH_SIZE>(c, log_sizes);
 
     ifft::<Goldilocks, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
+    ifft::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
 
     coset_lde::<BabyBear, RecursiveDft<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2Dit<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
     coset_lde::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, log_sizes);
     coset_lde::<Goldilocks, Radix2Bowers, BATCH_SIZE>(c, log_sizes);
+    coset_lde_with_added_bits::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, &[14, 16, 18], 4);
+    coset_lde_shifts::<BabyBear, Radix2DitParallel<_>, BATCH_SIZE>(c, &[14, 16, 18], 4);
 }
 
 fn fft<F, Dft, const BATCH_SIZE: usize>(c: &mut Criterion, log_sizes: &[usize])
@@ -133,12 +136,25 @@ where
     F: TwoAdicField,
     Dft: TwoAdicSubgroupDft<F>,
     Standard: Distribution<F>,
+{
+    coset_lde_with_added_bits::<F, Dft, BATCH_SIZE>(c, log_sizes, 1);
+}
+
+fn coset_lde_with_added_bits<F, Dft, const BATCH_SIZE: usize>(
+    c: &mut Criterion,
+    log_sizes: &[usize],
+    added_bits: usize,
+) where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+    Standard: Distribution<F>,
 {
     let mut group = c.benchmark_group(format!(
-        "coset_lde/{}/{}/ncols={}",
+        "coset_lde/{}/{}/ncols={}/added_bits={}",
         pretty_name::<F>(),
         pretty_name::<Dft>(),
-        BATCH_SIZE
+        BATCH_SIZE,
+        added_bits
     ));
     group.sample_size(10);
 
@@ -151,7 +167,53 @@ where
         let dft = Dft::default();
         group.bench_with_input(BenchmarkId::from_parameter(n), &dft, |b, dft| {
             b.iter(|| {
-                dft.coset_lde_batch(messages.clone(), 1, F::GENERATOR);
+                dft.coset_lde_batch(messages.clone(), added_bits, F::GENERATOR);
+            });
+        });
+    }
+}
+
+/// Compares `coset_lde_batch_shifts` against one `coset_lde_batch` per shift.
+fn coset_lde_shifts<F, Dft, const BATCH_SIZE: usize>(
+    c: &mut Criterion,
+    log_sizes: &[usize],
+    num_shifts: usize,
+) where
+    F: TwoAdicField,
+    Dft: TwoAdicSubgroupDft<F>,
+    Standard: Distribution<F>,
+{
+    let mut group = c.benchmark_group(format!(
+        "coset_lde_shifts/{}/{}/ncols={}/shifts={}",
+        pretty_name::<F>(),
+        pretty_name::<Dft>(),
+        BATCH_SIZE,
+        num_shifts
+    ));
+    group.sample_size(10);
+
+    let shifts = F::GENERATOR
+        .powers()
+        .skip(1)
+        .take(num_shifts)
+        .collect::<Vec<_>>();
+    let mut rng = thread_rng();
+    for n_log in log_sizes {
+        let n = 1 << n_log;
+
+        let messages = RowMajorMatrix::rand(&mut rng, n, BATCH_SIZE);
+
+        let dft = Dft::default();
+        group.bench_with_input(BenchmarkId::new("separate", n), &dft, |b, dft| {
+            b.iter(|| {
+                for &shift in &shifts {
+                    dft.coset_lde_batch(messages.clone(), 1, shift);
+                }
+            });
+        });
+        group.bench_with_input(BenchmarkId::new("shared_idft", n), &dft, |b, dft| {
+            b.iter(|| {
+                dft.coset_lde_batch_shifts(messages.clone(), 1, &shifts);
             });
         });
     }
@@ -11,6 +11,8 @@ pub trait Butterfly<F: Field>: Copy + Send + Sync {
         (*x_1, *x_2) = self.apply(*x_1, *x_2);
     }
 
+    /// Apply the butterfly to each pair of elements in the two rows, which may have any width:
+    /// as many elements as fill whole packed lanes are done together, then the rest one by one.
     #[inline]
     fn apply_to_rows(&self, row_1: &mut [F], row_2: &mut [F]) {
         let (shorts_1, suffix_1) = F::Packing::pack_slice_with_suffix_mut(row_1);
@@ -85,3 +87,63 @@ impl<F: Field> Butterfly<F> for TwiddleFreeButterfly {
         (x_1 + x_2, x_1 - x_2)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use p3_baby_bear::BabyBear;
+    use rand::distributions::Standard;
+    use rand::{random, thread_rng, Rng};
+
+    use super::*;
+
+    type F = BabyBear;
+
+    /// Widths on either side of the packing widths of the targets we build for, so that some
+    /// rows are all packed lanes, some all scalar tail, and some both.
+    const WIDTHS: [usize; 5] = [1, 7, 10, 16, 17];
+
+    fn rand_row(len: usize) -> Vec<F> {
+        thread_rng().sample_iter(Standard).take(len).collect()
+    }
+
+    fn matches_scalar_path<B: Butterfly<F>>(butterfly: B) {
+        for width in WIDTHS {
+            let (row_1, row_2) = (rand_row(width), rand_row(width));
+            let (expected_1, expected_2): (Vec<_>, Vec<_>) = row_1
+                .iter()
+                .zip(&row_2)
+                .map(|(&x_1, &x_2)| butterfly.apply::<F>(x_1, x_2))
+                .unzip();
+
+            let (mut in_place_1, mut in_place_2) = (row_1.clone(), row_2.clone());
+            butterfly.apply_to_rows(&mut in_place_1, &mut in_place_2);
+            assert_eq!(in_place_1, expected_1, "width = {width}");
+            assert_eq!(in_place_2, expected_2, "width = {width}");
+
+            let mut dst_1 = Vec::<F>::with_capacity(width);
+            let mut dst_2 = Vec::<F>::with_capacity(width);
+            butterfly.apply_to_rows_oop(
+                &row_1,
+                &mut dst_1.spare_capacity_mut()[..width],
+                &row_2,
+                &mut dst_2.spare_capacity_mut()[..width],
+            );
+            // Safety: `apply_to_rows_oop` wrote every element.
+            unsafe {
+                dst_1.set_len(width);
+                dst_2.set_len(width);
+            }
+            assert_eq!(dst_1, expected_1, "width = {width}");
+            assert_eq!(dst_2, expected_2, "width = {width}");
+        }
+    }
+
+    #[test]
+    fn rows_of_any_width_match_scalar_path() {
+        matches_scalar_path(DifButterfly(random::<F>()));
+        matches_scalar_path(DitButterfly(random::<F>()));
+        matches_scalar_path(TwiddleFreeButterfly);
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::vec::Vec;
+
+use p3_field::{Field, TwoAdicField};
+use p3_matrix::bitrev::BitReversableMatrix;
+use p3_matrix::dense::RowMajorMatrix;
+
+use crate::TwoAdicSubgroupDft;
+
+/// Low-degree extension of the columns of a matrix onto a coset of a larger domain.
+///
+/// This is all a PCS needs to commit to evaluations, so it is split out from
+/// `TwoAdicSubgroupDft`, letting the PCS accept backends that compute LDEs some other way. Every
+/// `TwoAdicSubgroupDft` implements it.
+pub trait CosetLde<F: Field>: Clone + Default {
+    /// The evaluations of an LDE, which may be returned with their rows bit-reversed.
+    type Evaluations: BitReversableMatrix<F> + 'static;
+
+    /// Extend the columns of `mat`, evaluations over a domain of its height, to `2^added_bits`
+    /// times that height, on the coset with the given `shift`.
+    fn coset_lde_batch(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> Self::Evaluations;
+
+    /// Like `coset_lde_batch`, but onto one coset per shift.
+    fn coset_lde_batch_shifts(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shifts: &[F],
+    ) -> Vec<Self::Evaluations> {
+        shifts
+            .iter()
+            .map(|&shift| self.coset_lde_batch(mat.clone(), added_bits, shift))
+            .collect()
+    }
+}
+
+impl<F: TwoAdicField, Dft: TwoAdicSubgroupDft<F>> CosetLde<F> for Dft {
+    type Evaluations = <Dft as TwoAdicSubgroupDft<F>>::Evaluations;
+
+    fn coset_lde_batch(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> Self::Evaluations {
+        TwoAdicSubgroupDft::coset_lde_batch(self, mat, added_bits, shift)
+    }
+
+    fn coset_lde_batch_shifts(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shifts: &[F],
+    ) -> Vec<Self::Evaluations> {
+        TwoAdicSubgroupDft::coset_lde_batch_shifts(self, mat, added_bits, shifts)
+    }
+}
@@ -9,7 +9,7 @@ use p3_util::{log2_strict_usize, reverse_bits, reverse_slice_index_bits};
 use tracing::instrument;
 
 use crate::butterflies::{Butterfly, DifButterfly, DitButterfly, TwiddleFreeButterfly};
-use crate::util::divide_by_height;
+use crate::util::{divide_by_height, lde_len};
 use crate::TwoAdicSubgroupDft;
 
 /// The Bowers G FFT algorithm.
@@ -37,6 +37,8 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Bowers {
     fn lde_batch(&self, mut mat: RowMajorMatrix<F>, added_bits: usize) -> RowMajorMatrix<F> {
         bowers_g_t(&mut mat.as_view_mut());
         divide_by_height(&mut mat);
+        // Check that the LDE fits before padding the matrix out to it.
+        lde_len(&mat, added_bits);
         mat = mat.bit_reversed_zero_pad(added_bits);
         bowers_g(&mut mat.as_view_mut());
         mat
@@ -67,6 +69,8 @@ impl<F: TwoAdicField> TwoAdicSubgroupDft<F> for Radix2Bowers {
             mat.scale_row(reverse_bits(row, h), weight);
         }
 
+        // Check that the LDE fits before padding the matrix out to it.
+        lde_len(&mat, added_bits);
         mat = mat.bit_reversed_zero_pad(added_bits);
 
         bowers_g(&mut mat.as_view_mut());
@@ -112,7 +116,7 @@ fn butterfly_layer<F: Field, B: Butterfly<F>>(
     half_block_size: usize,
     twiddles: &[B],
 ) {
-    mat.par_row_chunks_exact_mut(2 * half_block_size)
+    mat.par_row_chunks_mut(2 * half_block_size)
         .enumerate()
         .for_each(|(block, mut chunks)| {
             let (mut hi_chunks, mut lo_chunks) = chunks.split_rows_mut(half_block_size);
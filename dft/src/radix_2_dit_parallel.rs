@@ -4,7 +4,7 @@ use alloc::vec::Vec;
 use core::cell::RefCell;
 use core::mem::{transmute, MaybeUninit};
 
-use itertools::{izip, Itertools};
+use itertools::izip;
 use p3_field::{Field, Powers, TwoAdicField};
 use p3_matrix::bitrev::{BitReversableMatrix, BitReversalPerm, BitReversedMatrixView};
 use p3_matrix::dense::{RowMajorMatrix, RowMajorMatrixView, RowMajorMatrixViewMut};
@@ -15,6 +15,7 @@ use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
 use tracing::{debug_span, instrument};
 
 use crate::butterflies::{Butterfly, DitButterfly};
+use crate::util::lde_len;
 use crate::TwoAdicSubgroupDft;
 
 /// A parallel FFT algorithm which divides a butterfly network's layers into two halves.
@@ -24,6 +25,10 @@ use crate::TwoAdicSubgroupDft;
 /// the same network but in bit-reversed order. This way we're always working with small blocks,
 /// so within each half, we can have a certain amount of parallelism with no cross-thread
 /// communication.
+///
+/// The first half takes `mid = ceil(log_h / 2)` layers, working on chunks of `2^mid` rows, and the
+/// second half takes the remaining `log_h - mid`, on chunks of `2^(log_h - mid)` rows. When `log_h`
+/// is odd the split is uneven, with the first half doing one more layer than the second.
 #[derive(Default, Clone, Debug)]
 pub struct Radix2DitParallel<F> {
     /// Twiddles based on roots of unity, used in the forward DFT.
@@ -37,24 +42,41 @@ pub struct Radix2DitParallel<F> {
     inverse_twiddles: RefCell<BTreeMap<usize, VectorPair<F>>>,
 }
 
-/// A pair of vectors, one with twiddle factors in their natural order, the other bit-reversed.
+/// The twiddle factors for one DFT size, laid out for each half of the network.
 #[derive(Default, Clone, Debug)]
 struct VectorPair<F> {
-    twiddles: Vec<F>,
+    /// For each layer of the first half, the twiddles it uses, in their natural order. These are
+    /// strided through the full set of twiddles once here, rather than on every pass over a block,
+    /// and add up to only `2^mid` elements.
+    first_half_twiddles: Vec<Vec<F>>,
+    /// All the twiddles, in bit-reversed order, for the second half.
     bitrev_twiddles: Vec<F>,
 }
 
+impl<F: Copy> VectorPair<F> {
+    fn new(log_h: usize, twiddles: Vec<F>) -> Self {
+        let mid = log_h.div_ceil(2);
+        let first_half_twiddles = (0..mid)
+            .map(|layer| {
+                let layer_rev = log_h - 1 - layer;
+                twiddles.iter().copied().step_by(1 << layer_rev).collect()
+            })
+            .collect();
+        let mut bitrev_twiddles = twiddles;
+        reverse_slice_index_bits(&mut bitrev_twiddles);
+        Self {
+            first_half_twiddles,
+            bitrev_twiddles,
+        }
+    }
+}
+
 #[instrument(level = "debug", skip_all)]
 fn compute_twiddles<F: TwoAdicField + Ord>(log_h: usize) -> VectorPair<F> {
     let half_h = (1 << log_h) >> 1;
     let root = F::two_adic_generator(log_h);
     let twiddles: Vec<F> = root.powers().take(half_h).collect();
-    let mut bit_reversed_twiddles = twiddles.clone();
-    reverse_slice_index_bits(&mut bit_reversed_twiddles);
-    VectorPair {
-        twiddles,
-        bitrev_twiddles: bit_reversed_twiddles,
-    }
+    VectorPair::new(log_h, twiddles)
 }
 
 #[instrument(level = "debug", skip_all)]
@@ -88,15 +110,9 @@ fn compute_inverse_twiddles<F: TwoAdicField + Ord>(log_h: usize) -> VectorPair<F
     let half_h = (1 << log_h) >> 1;
     let root_inv = F::two_adic_generator(log_h).inverse();
     let twiddles: Vec<F> = root_inv.powers().take(half_h).collect();
-    let mut bit_reversed_twiddles = twiddles.clone();
-
-    // In the middle of the coset LDE, we're in bit-reversed order.
-    reverse_slice_index_bits(&mut bit_reversed_twiddles);
-
-    VectorPair {
-        twiddles,
-        bitrev_twiddles: bit_reversed_twiddles,
-    }
+    // In the middle of the coset LDE, we're in bit-reversed order, so the second half of the
+    // inverse DFT uses bit-reversed twiddles too.
+    VectorPair::new(log_h, twiddles)
 }
 
 impl<F: TwoAdicField + Ord> TwoAdicSubgroupDft<F> for Radix2DitParallel<F> {
@@ -116,7 +132,7 @@ impl<F: TwoAdicField + Ord> TwoAdicSubgroupDft<F> for Radix2DitParallel<F> {
 
         // The first half looks like a normal DIT.
         reverse_matrix_index_bits(&mut mat);
-        first_half(&mut mat, mid, &twiddles.twiddles);
+        first_half(&mut mat, mid, &twiddles.first_half_twiddles);
 
         // For the second half, we flip the DIT, working in bit-reversed order.
         reverse_matrix_index_bits(&mut mat);
@@ -132,60 +148,170 @@ impl<F: TwoAdicField + Ord> TwoAdicSubgroupDft<F> for Radix2DitParallel<F> {
         added_bits: usize,
         shift: F,
     ) -> Self::Evaluations {
-        let w = mat.width;
-        let h = mat.height();
-        let log_h = log2_strict_usize(h);
-        let mid = log_h.div_ceil(2);
+        bitrev_idft(self, &mut mat);
+        coset_lde_of_bitrev_coeffs(self, mat, added_bits, shift)
+    }
 
-        let mut inverse_twiddles_ref_mut = self.inverse_twiddles.borrow_mut();
-        let inverse_twiddles = inverse_twiddles_ref_mut
-            .entry(log_h)
-            .or_insert_with(|| compute_inverse_twiddles(log_h));
+    #[instrument(skip_all, fields(dims = %mat.dimensions(), added_bits = added_bits, shifts = shifts.len()))]
+    fn coset_lde_batch_shifts(
+        &self,
+        mut mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shifts: &[F],
+    ) -> Vec<Self::Evaluations> {
+        bitrev_idft(self, &mut mat);
+        let Some((&last_shift, shifts)) = shifts.split_last() else {
+            return Vec::new();
+        };
 
-        // The first half looks like a normal DIT.
-        reverse_matrix_index_bits(&mut mat);
-        first_half(&mut mat, mid, &inverse_twiddles.twiddles);
+        let lde_elems = lde_len(&mat, added_bits);
+        let mut ldes: Vec<_> = shifts
+            .iter()
+            .map(|&shift| {
+                // Copy the coefficients into a buffer which already has room for the LDE.
+                let mut coeffs = Vec::with_capacity(lde_elems);
+                coeffs.extend_from_slice(&mat.values);
+                coset_lde_of_bitrev_coeffs(
+                    self,
+                    RowMajorMatrix::new(coeffs, mat.width),
+                    added_bits,
+                    shift,
+                )
+            })
+            .collect();
+        ldes.push(coset_lde_of_bitrev_coeffs(
+            self, mat, added_bits, last_shift,
+        ));
+        ldes
+    }
+}
 
-        // For the second half, we flip the DIT, working in bit-reversed order.
-        reverse_matrix_index_bits(&mut mat);
-        // We'll also scale by 1/h, as per the usual inverse DFT algorithm.
-        let scale = Some(F::from_canonical_usize(h).inverse());
-        second_half(&mut mat, mid, &inverse_twiddles.bitrev_twiddles, scale);
-        // We skip the final bit-reversal, since the next FFT expects bit-reversed input.
-
-        let lde_elems = w * (h << added_bits);
-        let elems_to_add = lde_elems - w * h;
-        debug_span!("reserve_exact").in_scope(|| mat.values.reserve_exact(elems_to_add));
-
-        let g_big = F::two_adic_generator(log_h + added_bits);
-
-        let mat_ptr = mat.values.as_mut_ptr();
-        let rest_ptr = unsafe { (mat_ptr as *mut MaybeUninit<F>).add(w * h) };
-        let first_slice: &mut [F] = unsafe { slice::from_raw_parts_mut(mat_ptr, w * h) };
-        let rest_slice: &mut [MaybeUninit<F>] =
-            unsafe { slice::from_raw_parts_mut(rest_ptr, lde_elems - w * h) };
-        let mut first_coset_mat = RowMajorMatrixViewMut::new(first_slice, w);
-        let mut rest_cosets_mat = rest_slice
-            .chunks_exact_mut(w * h)
-            .map(|slice| RowMajorMatrixViewMut::new(slice, w))
-            .collect_vec();
-
-        for coset_idx in 1..(1 << added_bits) {
-            let total_shift = g_big.exp_u64(coset_idx as u64) * shift;
-            let coset_idx = reverse_bits_len(coset_idx, added_bits);
-            let dest = &mut rest_cosets_mat[coset_idx - 1]; // - 1 because we removed the first matrix.
-            coset_dft_oop(self, &first_coset_mat.as_view(), dest, total_shift);
-        }
+/// An inverse DFT of each column, leaving the coefficients in bit-reversed order, as the forward
+/// DFTs of `coset_lde_of_bitrev_coeffs` expect.
+#[instrument(level = "debug", skip_all)]
+fn bitrev_idft<F: TwoAdicField + Ord>(dft: &Radix2DitParallel<F>, mat: &mut RowMajorMatrix<F>) {
+    let h = mat.height();
+    let log_h = log2_strict_usize(h);
+    let mid = log_h.div_ceil(2);
 
-        // Now run a forward DFT on the very first coset, this time in-place.
-        coset_dft(self, &mut first_coset_mat.as_view_mut(), shift);
+    let mut inverse_twiddles_ref_mut = dft.inverse_twiddles.borrow_mut();
+    let inverse_twiddles = inverse_twiddles_ref_mut
+        .entry(log_h)
+        .or_insert_with(|| compute_inverse_twiddles(log_h));
 
-        // SAFETY: We wrote all values above.
-        unsafe {
-            mat.values.set_len(lde_elems);
-        }
-        BitReversalPerm::new_view(mat)
+    // The first half looks like a normal DIT.
+    reverse_matrix_index_bits(mat);
+    first_half(mat, mid, &inverse_twiddles.first_half_twiddles);
+
+    // For the second half, we flip the DIT, working in bit-reversed order.
+    reverse_matrix_index_bits(mat);
+    // We'll also scale by 1/h, as per the usual inverse DFT algorithm.
+    let scale = Some(F::from_canonical_usize(h).inverse());
+    second_half(mat, mid, &inverse_twiddles.bitrev_twiddles, scale);
+    // We skip the final bit-reversal, since the next FFT expects bit-reversed input.
+}
+
+/// Extend bit-reversed coefficients, as left by `bitrev_idft`, onto the coset `shift` of the
+/// subgroup `added_bits` larger.
+fn coset_lde_of_bitrev_coeffs<F: TwoAdicField + Ord>(
+    dft: &Radix2DitParallel<F>,
+    mut mat: RowMajorMatrix<F>,
+    added_bits: usize,
+    shift: F,
+) -> BitReversedMatrixView<RowMajorMatrix<F>> {
+    if added_bits == 0 {
+        // There's nothing to extend, so this is just a coset DFT of the coefficients.
+        coset_dft(dft, &mut mat.as_view_mut(), shift);
+        return BitReversalPerm::new_view(mat);
+    }
+
+    // Under Miri, take the path that never handles uninitialized memory, so that the rest of the
+    // network is checked without relying on the `MaybeUninit` transmutes.
+    if cfg!(miri) {
+        return coset_lde_of_bitrev_coeffs_safe(dft, mat, added_bits, shift);
     }
+    coset_lde_of_bitrev_coeffs_uninit(dft, mat, added_bits, shift)
+}
+
+/// `coset_lde_of_bitrev_coeffs` for `added_bits > 0`, writing the extra cosets out-of-place into
+/// the buffer's uninitialized spare capacity.
+fn coset_lde_of_bitrev_coeffs_uninit<F: TwoAdicField + Ord>(
+    dft: &Radix2DitParallel<F>,
+    mut mat: RowMajorMatrix<F>,
+    added_bits: usize,
+    shift: F,
+) -> BitReversedMatrixView<RowMajorMatrix<F>> {
+    let w = mat.width;
+    let h = mat.height();
+    let log_h = log2_strict_usize(h);
+    let lde_elems = lde_len(&mat, added_bits);
+
+    let elems_to_add = lde_elems - w * h;
+    debug_span!("reserve_exact").in_scope(|| mat.values.reserve_exact(elems_to_add));
+
+    let g_big = F::two_adic_generator(log_h + added_bits);
+
+    let mat_ptr = mat.values.as_mut_ptr();
+    let rest_ptr = unsafe { (mat_ptr as *mut MaybeUninit<F>).add(w * h) };
+    let first_slice: &mut [F] = unsafe { slice::from_raw_parts_mut(mat_ptr, w * h) };
+    let rest_slice: &mut [MaybeUninit<F>] =
+        unsafe { slice::from_raw_parts_mut(rest_ptr, lde_elems - w * h) };
+    let mut first_coset_mat = RowMajorMatrixViewMut::new(first_slice, w);
+    let mut rest_cosets_mat = RowMajorMatrixViewMut::new(rest_slice, w);
+
+    // The cosets are laid out in bit-reversed order, and the first one was removed above.
+    // Iterating over the chunks lazily means a call with warm twiddle caches only allocates the
+    // LDE itself.
+    let rest_cosets = rest_cosets_mat.split_into_equal_height_chunks((1 << added_bits) - 1);
+    for (i, mut dest) in rest_cosets.enumerate() {
+        let coset_idx = reverse_bits_len(i + 1, added_bits);
+        let total_shift = shift * g_big.exp_u64(coset_idx as u64);
+        coset_dft_oop(dft, &first_coset_mat.as_view(), &mut dest, total_shift);
+    }
+
+    // Now run a forward DFT on the very first coset, this time in-place.
+    coset_dft(dft, &mut first_coset_mat.as_view_mut(), shift);
+
+    // SAFETY: We wrote all values above.
+    unsafe {
+        mat.values.set_len(lde_elems);
+    }
+    BitReversalPerm::new_view(mat)
+}
+
+/// `coset_lde_of_bitrev_coeffs` for `added_bits > 0`, without uninitialized memory. The buffer is
+/// zero-filled to the LDE's length, then each extra coset is a copy of the coefficients
+/// transformed in place. The extra pass over the buffer makes this slower, so it's only used
+/// under Miri.
+fn coset_lde_of_bitrev_coeffs_safe<F: TwoAdicField + Ord>(
+    dft: &Radix2DitParallel<F>,
+    mut mat: RowMajorMatrix<F>,
+    added_bits: usize,
+    shift: F,
+) -> BitReversedMatrixView<RowMajorMatrix<F>> {
+    let w = mat.width;
+    let h = mat.height();
+    let log_h = log2_strict_usize(h);
+    let lde_elems = lde_len(&mat, added_bits);
+
+    mat.values.resize(lde_elems, F::ZERO);
+
+    let g_big = F::two_adic_generator(log_h + added_bits);
+
+    let (first_slice, rest_slice) = mat.values.split_at_mut(w * h);
+    let mut rest_cosets_mat = RowMajorMatrixViewMut::new(rest_slice, w);
+
+    // The cosets are laid out in bit-reversed order, as in `coset_lde_of_bitrev_coeffs_uninit`.
+    let rest_cosets = rest_cosets_mat.split_into_equal_height_chunks((1 << added_bits) - 1);
+    for (i, mut dest) in rest_cosets.enumerate() {
+        let coset_idx = reverse_bits_len(i + 1, added_bits);
+        let total_shift = shift * g_big.exp_u64(coset_idx as u64);
+        dest.values.copy_from_slice(first_slice);
+        coset_dft(dft, &mut dest, total_shift);
+    }
+
+    coset_dft(dft, &mut RowMajorMatrixViewMut::new(first_slice, w), shift);
+    BitReversalPerm::new_view(mat)
 }
 
 #[instrument(level = "debug", skip_all)]
@@ -226,10 +352,9 @@ fn coset_dft_oop<F: TwoAdicField + Ord>(
     if log_h == 0 {
         // This is an edge case where first_half_general_oop doesn't work, as it expects there to be
         // at least one layer in the network, so we just copy instead.
-        let src_maybe = unsafe {
-            transmute::<&RowMajorMatrixView<F>, &RowMajorMatrixView<MaybeUninit<F>>>(src)
-        };
-        dst_maybe.copy_from(src_maybe);
+        for (dst, &src) in dst_maybe.values.iter_mut().zip(src.values) {
+            dst.write(src);
+        }
         return;
     }
 
@@ -257,26 +382,25 @@ fn coset_dft_oop<F: TwoAdicField + Ord>(
 }
 
 /// This can be used as the first half of a DIT butterfly network.
+///
+/// `twiddles[layer]` holds the twiddles of each layer, as in `VectorPair::first_half_twiddles`.
 #[instrument(level = "debug", skip_all)]
-fn first_half<F: Field>(mat: &mut RowMajorMatrix<F>, mid: usize, twiddles: &[F]) {
-    let log_h = log2_strict_usize(mat.height());
+fn first_half<F: Field>(mat: &mut RowMajorMatrix<F>, mid: usize, twiddles: &[Vec<F>]) {
+    debug_assert_eq!(twiddles.len(), mid);
 
     // max block size: 2^mid
-    mat.par_row_chunks_exact_mut(1 << mid)
-        .for_each(|mut submat| {
-            let mut backwards = false;
-            for layer in 0..mid {
-                let layer_rev = log_h - 1 - layer;
-                let layer_pow = 1 << layer_rev;
-                dit_layer(
-                    &mut submat,
-                    layer,
-                    twiddles.iter().copied().step_by(layer_pow),
-                    backwards,
-                );
-                backwards = !backwards;
-            }
-        });
+    mat.par_row_chunks_mut(1 << mid).for_each(|mut submat| {
+        let mut backwards = false;
+        for (layer, layer_twiddles) in twiddles.iter().enumerate() {
+            dit_layer(
+                &mut submat,
+                layer,
+                layer_twiddles.iter().copied(),
+                backwards,
+            );
+            backwards = !backwards;
+        }
+    });
 }
 
 /// Like `first_half`, except supporting different twiddle factors per layer, enabling coset shifts
@@ -288,20 +412,19 @@ fn first_half_general<F: Field>(
     twiddles: &[Vec<F>],
 ) {
     let log_h = log2_strict_usize(mat.height());
-    mat.par_row_chunks_exact_mut(1 << mid)
-        .for_each(|mut submat| {
-            let mut backwards = false;
-            for layer in 0..mid {
-                let layer_rev = log_h - 1 - layer;
-                dit_layer(
-                    &mut submat,
-                    layer,
-                    twiddles[layer_rev].iter().copied(),
-                    backwards,
-                );
-                backwards = !backwards;
-            }
-        });
+    mat.par_row_chunks_mut(1 << mid).for_each(|mut submat| {
+        let mut backwards = false;
+        for layer in 0..mid {
+            let layer_rev = log_h - 1 - layer;
+            dit_layer(
+                &mut submat,
+                layer,
+                twiddles[layer_rev].iter().copied(),
+                backwards,
+            );
+            backwards = !backwards;
+        }
+    });
 }
 
 /// Like `first_half_general`, except out-of-place.
@@ -316,8 +439,8 @@ fn first_half_general_oop<F: Field>(
     twiddles: &[Vec<F>],
 ) {
     let log_h = log2_strict_usize(src.height());
-    src.par_row_chunks_exact(1 << mid)
-        .zip(dst_maybe.par_row_chunks_exact_mut(1 << mid))
+    src.par_row_chunks(1 << mid)
+        .zip(dst_maybe.par_row_chunks_mut(1 << mid))
         .for_each(|(src_submat, mut dst_submat_maybe)| {
             debug_assert_eq!(src_submat.dimensions(), dst_submat_maybe.dimensions());
 
@@ -369,7 +492,7 @@ fn second_half<F: Field>(
     let log_h = log2_strict_usize(mat.height());
 
     // max block size: 2^(log_h - mid)
-    mat.par_row_chunks_exact_mut(1 << (log_h - mid))
+    mat.par_row_chunks_mut(1 << (log_h - mid))
         .enumerate()
         .for_each(|(thread, mut submat)| {
             let mut backwards = false;
@@ -399,7 +522,7 @@ fn second_half_general<F: Field>(
     twiddles_rev: &[Vec<F>],
 ) {
     let log_h = log2_strict_usize(mat.height());
-    mat.par_row_chunks_exact_mut(1 << (log_h - mid))
+    mat.par_row_chunks_mut(1 << (log_h - mid))
         .enumerate()
         .for_each(|(thread, mut submat)| {
             let mut backwards = false;
@@ -517,3 +640,36 @@ fn dit_layer_rev<F: Field>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use rand::thread_rng;
+
+    use super::*;
+
+    type F = BabyBear;
+
+    #[test]
+    fn safe_coset_lde_matches_uninit() {
+        let dft = Radix2DitParallel::<F>::default();
+        let shift = F::GENERATOR;
+        // Kept small, so that this is quick enough to run under Miri.
+        for log_h in 0..4 {
+            for added_bits in 1..3 {
+                let mut coeffs = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_h, 3);
+                // The coefficients have to be bit-reversed, as `bitrev_idft` would leave them.
+                reverse_matrix_index_bits(&mut coeffs);
+
+                let uninit =
+                    coset_lde_of_bitrev_coeffs_uninit(&dft, coeffs.clone(), added_bits, shift);
+                let safe = coset_lde_of_bitrev_coeffs_safe(&dft, coeffs, added_bits, shift);
+                assert_eq!(
+                    safe.to_row_major_matrix(),
+                    uninit.to_row_major_matrix(),
+                    "log_h = {log_h}, added_bits = {added_bits}"
+                );
+            }
+        }
+    }
+}
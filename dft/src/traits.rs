@@ -6,7 +6,8 @@ use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::swap_rows;
 use p3_matrix::Matrix;
 
-use crate::util::{coset_shift_cols, divide_by_height};
+use crate::util::{checked_lde_len, coset_shift_cols, divide_by_height, lde_len, LdeError};
+use crate::Radix2DitParallel;
 
 pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
     // Effectively this is either RowMajorMatrix or BitReversedMatrixView<RowMajorMatrix>.
@@ -91,9 +92,8 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
     /// Compute the low-degree extension of each column in `mat` onto a larger subgroup.
     fn lde_batch(&self, mat: RowMajorMatrix<F>, added_bits: usize) -> Self::Evaluations {
         let mut coeffs = self.idft_batch(mat);
-        coeffs
-            .values
-            .resize(coeffs.values.len() << added_bits, F::ZERO);
+        let lde_len = lde_len(&coeffs, added_bits);
+        coeffs.values.resize(lde_len, F::ZERO);
         self.dft_batch(coeffs)
     }
 
@@ -112,15 +112,52 @@ pub trait TwoAdicSubgroupDft<F: TwoAdicField>: Clone + Default {
         shift: F,
     ) -> Self::Evaluations {
         let mut coeffs = self.idft_batch(mat);
-        // PANICS: possible panic if the new resized length overflows
-        coeffs.values.resize(
-            coeffs
-                .values
-                .len()
-                .checked_shl(added_bits.try_into().unwrap())
-                .unwrap(),
-            F::ZERO,
-        );
+        let lde_len = lde_len(&coeffs, added_bits);
+        coeffs.values.resize(lde_len, F::ZERO);
         self.coset_dft_batch(coeffs, shift)
     }
+
+    /// Like `coset_lde_batch`, but returns an error instead of panicking if the LDE is too large,
+    /// either for the field's two-adicity or for a `usize`.
+    fn try_coset_lde_batch(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shift: F,
+    ) -> Result<Self::Evaluations, LdeError> {
+        checked_lde_len(&mat, added_bits)?;
+        Ok(self.coset_lde_batch(mat, added_bits, shift))
+    }
+
+    /// Compute the low-degree extension of each column in `mat` onto several cosets of a larger
+    /// subgroup, one for each shift. The inverse DFT doesn't depend on the shift, so it is shared
+    /// between the cosets, and only the forward DFT is repeated.
+    fn coset_lde_batch_shifts(
+        &self,
+        mat: RowMajorMatrix<F>,
+        added_bits: usize,
+        shifts: &[F],
+    ) -> Vec<Self::Evaluations> {
+        let mut coeffs = self.idft_batch(mat);
+        let lde_len = lde_len(&coeffs, added_bits);
+        coeffs.values.resize(lde_len, F::ZERO);
+        shifts
+            .iter()
+            .map(|&shift| self.coset_dft_batch(coeffs.clone(), shift))
+            .collect()
+    }
+}
+
+/// Selects a sensible default DFT implementation for a field, so that generic code (e.g. a PCS)
+/// can be instantiated from the field type alone.
+///
+/// Every two-adic field uses `Radix2DitParallel`. Fields such as Mersenne31, whose multiplicative
+/// group has only a tiny power-of-two subgroup, don't implement `TwoAdicField` and hence can't use
+/// any `TwoAdicSubgroupDft`; for those, use the circle group machinery in `p3-circle` instead.
+pub trait HasDefaultDft: TwoAdicField {
+    type DefaultDft: TwoAdicSubgroupDft<Self>;
+}
+
+impl<F: TwoAdicField + Ord> HasDefaultDft for F {
+    type DefaultDft = Radix2DitParallel<F>;
 }
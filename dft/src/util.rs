@@ -1,8 +1,10 @@
 use core::borrow::BorrowMut;
+use core::fmt::{Display, Formatter};
 
-use p3_field::Field;
+use p3_field::{Field, TwoAdicField};
 use p3_matrix::dense::{DenseMatrix, DenseStorage, RowMajorMatrix};
 use p3_matrix::Matrix;
+use p3_util::log2_ceil_usize;
 use tracing::instrument;
 
 /// Divide each coefficient of the given matrix by its height.
@@ -13,6 +15,65 @@ pub fn divide_by_height<F: Field, S: DenseStorage<F> + BorrowMut<[F]>>(
     mat.scale(F::from_canonical_usize(mat.height()).inverse())
 }
 
+/// Why a low-degree extension can't be computed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LdeError {
+    /// The LDE would be over a subgroup of order `2^log_lde_height`, but the field only has
+    /// subgroups of order up to `2^two_adicity`.
+    TwoAdicityExceeded {
+        log_lde_height: usize,
+        two_adicity: usize,
+    },
+    /// The LDE of a matrix with `len` entries would have more than `usize::MAX` entries.
+    SizeOverflow { len: usize, added_bits: usize },
+}
+
+impl Display for LdeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::TwoAdicityExceeded {
+                log_lde_height,
+                two_adicity,
+            } => write!(
+                f,
+                "an LDE of height 2^{log_lde_height} exceeds the field's two-adicity of {two_adicity}"
+            ),
+            Self::SizeOverflow { len, added_bits } => write!(
+                f,
+                "an LDE of {len} entries with {added_bits} added bits overflows usize"
+            ),
+        }
+    }
+}
+
+/// The number of entries in the LDE of `mat` with `added_bits`, provided the field has a subgroup
+/// of that height and the size fits in a `usize`.
+pub fn checked_lde_len<F: TwoAdicField>(
+    mat: &RowMajorMatrix<F>,
+    added_bits: usize,
+) -> Result<usize, LdeError> {
+    let log_lde_height = log2_ceil_usize(mat.height()).saturating_add(added_bits);
+    if log_lde_height > F::TWO_ADICITY {
+        return Err(LdeError::TwoAdicityExceeded {
+            log_lde_height,
+            two_adicity: F::TWO_ADICITY,
+        });
+    }
+    // The height now fits in a subgroup, but the width may still push the size over.
+    mat.values
+        .len()
+        .checked_mul(1 << added_bits)
+        .ok_or(LdeError::SizeOverflow {
+            len: mat.values.len(),
+            added_bits,
+        })
+}
+
+/// Like `checked_lde_len`, for the LDEs which have no way to report an error.
+pub(crate) fn lde_len<F: TwoAdicField>(mat: &RowMajorMatrix<F>, added_bits: usize) -> usize {
+    checked_lde_len(mat, added_bits).unwrap_or_else(|err| panic!("{err}"))
+}
+
 /// Multiply each element of row `i` of `mat` by `shift**i`.
 pub(crate) fn coset_shift_cols<F: Field>(mat: &mut RowMajorMatrix<F>, shift: F) {
     mat.rows_mut()
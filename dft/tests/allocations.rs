@@ -0,0 +1,45 @@
+//! Checks that repeated DFTs of the same shape don't allocate beyond their output, once the
+//! twiddle caches are warm.
+
+use p3_baby_bear::BabyBear;
+use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::Field;
+use p3_field_testing::{exclusive_tracking, track_allocations, TrackingAllocator};
+use p3_matrix::dense::RowMajorMatrix;
+use rand::thread_rng;
+
+#[global_allocator]
+static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+type F = BabyBear;
+
+#[test]
+fn dft_batch_reuses_input() {
+    let _tracking = exclusive_tracking();
+    let dft = Radix2DitParallel::<F>::default();
+    let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << 10, 8);
+    dft.dft_batch(mat.clone());
+
+    let input = mat.clone();
+    let (_, stats) = track_allocations(|| dft.dft_batch(input));
+    assert_eq!(stats.allocations, 0);
+}
+
+#[test]
+fn coset_lde_batch_only_allocates_output() {
+    let _tracking = exclusive_tracking();
+    let dft = Radix2DitParallel::<F>::default();
+    let shift = F::GENERATOR;
+    let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << 10, 8);
+
+    for added_bits in 0..4 {
+        // Warm up the twiddle caches for this shape.
+        dft.coset_lde_batch(mat.clone(), added_bits, shift);
+
+        let input = mat.clone();
+        let (_, stats) = track_allocations(|| dft.coset_lde_batch(input, added_bits, shift));
+        // Growing the input into the LDE is the only allocation left.
+        let expected = usize::from(added_bits > 0);
+        assert_eq!(stats.allocations, expected, "added_bits = {added_bits}");
+    }
+}
@@ -0,0 +1,117 @@
+//! Fixed BabyBear input/output pairs, so that a change in ordering or twiddles shows up as a
+//! wrong answer rather than only as a failed round trip.
+//!
+//! The expected outputs were computed outside of this crate as schoolbook sums
+//! `y_k = sum_j c_j (s g^k)^j`: for the DFT, `c` is the column and `s = 1`; for the LDE, `c` is the
+//! column's interpolating polynomial, zero-padded to twice its height. The inputs are two columns
+//! holding `i^3 + 7` for the row-major index `i`.
+
+use p3_baby_bear::BabyBear;
+use p3_dft::{NaiveDft, Radix2Bowers, Radix2Dit, Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::{AbstractField, Field};
+use p3_matrix::assert_matrices_eq;
+use p3_matrix::bitrev::BitReversableMatrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::util::reverse_matrix_index_bits;
+
+type F = BabyBear;
+
+const WIDTH: usize = 2;
+
+/// `(log_h, dft_batch, coset_lde_batch with one added bit at F::GENERATOR)`, both row-major.
+const VECTORS: [(usize, &[u32], &[u32]); 3] = [
+    (
+        1,
+        &[22, 42, 2013265913, 2013265895],
+        &[
+            2013265808, 2013265539, 1097293946, 42989948, 135, 424, 915971997, 1970276015,
+        ],
+    ),
+    (
+        2,
+        &[
+            316, 524, 866461091, 1432504280, 2013265761, 2013265677, 1146804702, 580761393,
+        ],
+        &[
+            2006264566, 1615262935, 1400821133, 1709695872, 155350028, 1937401990, 200123339,
+            593048605, 6924633, 397886006, 450803348, 1969669499, 1857992931, 75981435, 1974784338,
+            1767384311,
+        ],
+    ),
+    (
+        3,
+        &[
+            6328, 8184, 1970855216, 1877913802, 388072940, 1855369595, 1501674276, 1155046581,
+            2013264257, 2013263897, 1826192635, 827195709, 1625190421, 157893142, 741073588,
+            166372838,
+        ],
+        &[
+            77482317, 1280986618, 832064604, 1876012969, 232706432, 626892189, 248707113,
+            592705859, 32672996, 922971529, 1540328515, 1735849139, 214387224, 1861622708,
+            1436473925, 352727144, 193575836, 1250859863, 82737806, 55532661, 342952134, 324254254,
+            1461643572, 1157162122, 941168464, 1650380593, 1102538856, 1695633879, 1991592767,
+            135104114, 1348575621, 587448095,
+        ],
+    ),
+];
+
+fn input(log_h: usize) -> RowMajorMatrix<F> {
+    let values = (0..WIDTH << log_h)
+        .map(|i| F::from_canonical_usize(i * i * i + 7))
+        .collect();
+    RowMajorMatrix::new(values, WIDTH)
+}
+
+fn expected(values: &[u32]) -> RowMajorMatrix<F> {
+    RowMajorMatrix::new(
+        values.iter().map(|&x| F::from_canonical_u32(x)).collect(),
+        WIDTH,
+    )
+}
+
+fn matches_known_answers<Dft: TwoAdicSubgroupDft<F>>() {
+    let dft = Dft::default();
+    for (log_h, dft_values, lde_values) in VECTORS {
+        assert_matrices_eq!(
+            expected(dft_values),
+            dft.dft_batch(input(log_h)),
+            "dft, log_h = {log_h}"
+        );
+        assert_matrices_eq!(
+            expected(lde_values),
+            dft.coset_lde_batch(input(log_h), 1, F::GENERATOR),
+            "coset lde, log_h = {log_h}"
+        );
+    }
+}
+
+#[test]
+fn all_dfts_match_known_answers() {
+    matches_known_answers::<NaiveDft>();
+    matches_known_answers::<Radix2Dit<F>>();
+    matches_known_answers::<Radix2Bowers>();
+    matches_known_answers::<Radix2DitParallel<F>>();
+}
+
+#[test]
+fn radix_2_dit_parallel_stores_bit_reversed_rows() {
+    let dft = Radix2DitParallel::<F>::default();
+    for (log_h, dft_values, lde_values) in VECTORS {
+        let mut dft_rows = expected(dft_values);
+        reverse_matrix_index_bits(&mut dft_rows);
+        assert_matrices_eq!(
+            dft_rows,
+            dft.dft_batch(input(log_h)).bit_reverse_rows(),
+            "dft, log_h = {log_h}"
+        );
+
+        let mut lde_rows = expected(lde_values);
+        reverse_matrix_index_bits(&mut lde_rows);
+        assert_matrices_eq!(
+            lde_rows,
+            dft.coset_lde_batch(input(log_h), 1, F::GENERATOR)
+                .bit_reverse_rows(),
+            "coset lde, log_h = {log_h}"
+        );
+    }
+}
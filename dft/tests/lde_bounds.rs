@@ -0,0 +1,60 @@
+//! LDEs beyond the field's two-adicity are rejected with a descriptive error, by every DFT.
+
+use p3_baby_bear::BabyBear;
+use p3_dft::{
+    checked_lde_len, LdeError, NaiveDft, Radix2Bowers, Radix2Dit, Radix2DitParallel,
+    TwoAdicSubgroupDft,
+};
+use p3_field::{Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use rand::thread_rng;
+
+type F = BabyBear;
+
+fn rejects_lde_beyond_two_adicity<Dft: TwoAdicSubgroupDft<F>>() {
+    let dft = Dft::default();
+    let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << 4, 3);
+
+    // The largest LDE the field allows is fine.
+    let added_bits = F::TWO_ADICITY - 4;
+    assert_eq!(checked_lde_len(&mat, added_bits), Ok(3 << F::TWO_ADICITY));
+
+    let err = dft
+        .try_coset_lde_batch(mat, added_bits + 1, F::GENERATOR)
+        .err()
+        .expect("an LDE beyond the two-adicity should be rejected");
+    assert_eq!(
+        err,
+        LdeError::TwoAdicityExceeded {
+            log_lde_height: F::TWO_ADICITY + 1,
+            two_adicity: F::TWO_ADICITY,
+        }
+    );
+}
+
+#[test]
+fn all_dfts_reject_lde_beyond_two_adicity() {
+    rejects_lde_beyond_two_adicity::<NaiveDft>();
+    rejects_lde_beyond_two_adicity::<Radix2Dit<F>>();
+    rejects_lde_beyond_two_adicity::<Radix2Bowers>();
+    rejects_lde_beyond_two_adicity::<Radix2DitParallel<F>>();
+}
+
+#[test]
+fn absurd_added_bits_are_rejected() {
+    let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << 4, 3);
+    assert_eq!(
+        checked_lde_len(&mat, usize::MAX),
+        Err(LdeError::TwoAdicityExceeded {
+            log_lde_height: usize::MAX,
+            two_adicity: F::TWO_ADICITY,
+        })
+    );
+}
+
+#[test]
+#[should_panic(expected = "an LDE of height 2^28 exceeds the field's two-adicity of 27")]
+fn coset_lde_batch_panics_with_description() {
+    let mat = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << 4, 3);
+    Radix2DitParallel::<F>::default().coset_lde_batch(mat, 24, F::GENERATOR);
+}
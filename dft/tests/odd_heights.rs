@@ -0,0 +1,102 @@
+//! `Radix2DitParallel` splits its layers unevenly when `log_h` is odd, so check it against the
+//! naive DFT at odd heights specifically.
+
+use p3_baby_bear::BabyBear;
+use p3_dft::{NaiveDft, Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::Field;
+use p3_matrix::assert_matrices_eq;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use rand::thread_rng;
+
+type F = BabyBear;
+
+const ODD_LOG_HEIGHTS: [usize; 6] = [1, 3, 5, 7, 9, 11];
+
+/// Widths both narrower and wider than any packing, and not a multiple of it.
+const WIDTHS: [usize; 2] = [1, 19];
+
+fn rand_mat(log_h: usize, width: usize) -> RowMajorMatrix<F> {
+    RowMajorMatrix::rand(&mut thread_rng(), 1 << log_h, width)
+}
+
+#[test]
+fn dft_and_idft_match_naive() {
+    let dft = Radix2DitParallel::<F>::default();
+    for log_h in ODD_LOG_HEIGHTS {
+        for width in WIDTHS {
+            let mat = rand_mat(log_h, width);
+            assert_matrices_eq!(
+                NaiveDft.dft_batch(mat.clone()),
+                dft.dft_batch(mat.clone()),
+                "dft, log_h = {log_h}, width = {width}"
+            );
+            assert_matrices_eq!(
+                NaiveDft.idft_batch(mat.clone()),
+                dft.idft_batch(mat),
+                "idft, log_h = {log_h}, width = {width}"
+            );
+        }
+    }
+}
+
+#[test]
+fn coset_dft_matches_naive() {
+    let dft = Radix2DitParallel::<F>::default();
+    let shift = F::GENERATOR;
+    for log_h in ODD_LOG_HEIGHTS {
+        for width in WIDTHS {
+            let mat = rand_mat(log_h, width);
+            assert_matrices_eq!(
+                NaiveDft.coset_dft_batch(mat.clone(), shift),
+                dft.coset_dft_batch(mat, shift),
+                "log_h = {log_h}, width = {width}"
+            );
+        }
+    }
+}
+
+#[test]
+fn coset_lde_matches_naive() {
+    let dft = Radix2DitParallel::<F>::default();
+    let shift = F::GENERATOR;
+    // Odd inputs extended to both odd and even LDE heights.
+    for (log_h, added_bits) in ODD_LOG_HEIGHTS[..5].iter().flat_map(|&h| [(h, 1), (h, 2)]) {
+        for width in WIDTHS {
+            let mat = rand_mat(log_h, width);
+            assert_matrices_eq!(
+                NaiveDft.coset_lde_batch(mat.clone(), added_bits, shift),
+                dft.coset_lde_batch(mat, added_bits, shift),
+                "log_h = {log_h}, added_bits = {added_bits}, width = {width}"
+            );
+        }
+    }
+}
+
+#[test]
+fn coset_lde_round_trip() {
+    let dft = Radix2DitParallel::<F>::default();
+    let shift = F::GENERATOR;
+    for log_h in ODD_LOG_HEIGHTS {
+        for added_bits in 0..3 {
+            let mat = rand_mat(log_h, 5);
+            let lde = dft
+                .coset_lde_batch(mat.clone(), added_bits, shift)
+                .to_row_major_matrix();
+            assert_eq!(lde.height(), 1 << (log_h + added_bits));
+
+            // Interpolating the LDE gives back the original coefficients, followed by zeros.
+            let coeffs = dft.coset_idft_batch(lde, shift);
+            let (low, high) = coeffs.values.split_at(mat.values.len());
+            assert_eq!(
+                low,
+                dft.idft_batch(mat).values,
+                "log_h = {log_h}, added_bits = {added_bits}"
+            );
+            assert!(
+                high.iter().all(Field::is_zero),
+                "log_h = {log_h}, added_bits = {added_bits}"
+            );
+        }
+    }
+}
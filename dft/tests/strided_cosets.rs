@@ -0,0 +1,39 @@
+//! A coset LDE interleaves the cosets of the smaller subgroup, so striding through its rows
+//! recovers each one.
+
+use p3_baby_bear::BabyBear;
+use p3_dft::{Radix2DitParallel, TwoAdicSubgroupDft};
+use p3_field::{AbstractField, Field, TwoAdicField};
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use rand::thread_rng;
+
+type F = BabyBear;
+
+#[test]
+fn strided_rows_of_coset_lde_are_cosets() {
+    let dft = Radix2DitParallel::<F>::default();
+    let (log_h, added_bits) = (5, 2);
+    let shift = F::GENERATOR;
+    let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_h, 3);
+    let coeffs = dft.idft_batch(evals.clone());
+
+    let lde = dft
+        .coset_lde_batch(evals, added_bits, shift)
+        .to_row_major_matrix();
+    let g_big = F::two_adic_generator(log_h + added_bits);
+    for (j, coset_shift) in g_big
+        .shifted_powers(shift)
+        .take(1 << added_bits)
+        .enumerate()
+    {
+        // Row `i 2^added_bits + j` of the LDE is at `shift g_big^j g_small^i`.
+        let coset = lde.as_view().vertically_strided(1 << added_bits, j);
+        assert_eq!(
+            coset.to_row_major_matrix(),
+            dft.coset_dft_batch(coeffs.clone(), coset_shift)
+                .to_row_major_matrix(),
+            "coset {j}"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! A global allocator that tracks heap usage, for tests that bound how much a computation
+//! allocates.
+//!
+//! Usage is tracked across every thread, so that work handed to rayon's pool is included. A test
+//! binary opts in with
+//! ```ignore
+//! #[global_allocator]
+//! static GLOBAL: TrackingAllocator = TrackingAllocator;
+//! ```
+//! and each test holds [`exclusive_tracking`] for its whole body, so that other tests in the same
+//! binary don't allocate while it measures.
+
+extern crate std;
+
+use core::alloc::{GlobalAlloc, Layout};
+use core::sync::atomic::{AtomicUsize, Ordering};
+use std::alloc::System;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static CURRENT_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_BYTES: AtomicUsize = AtomicUsize::new(0);
+static EXCLUSIVE: Mutex<()> = Mutex::new(());
+
+/// Forwards to the system allocator, tracking the number of allocations, the bytes live and their
+/// peak.
+pub struct TrackingAllocator;
+
+fn record_alloc(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    let current = CURRENT_BYTES.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK_BYTES.fetch_max(current, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for TrackingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        record_alloc(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        CURRENT_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        record_alloc(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// What a computation allocated, as seen by [`TrackingAllocator`].
+#[derive(Copy, Clone, Debug)]
+pub struct AllocationStats {
+    /// The number of allocations and reallocations made.
+    pub allocations: usize,
+    /// The most memory held at once, beyond what was held when the computation started.
+    pub peak_bytes: usize,
+}
+
+/// Serializes the tests of a binary that track allocations. Hold the guard for a test's whole
+/// body, warm-up included.
+pub fn exclusive_tracking() -> MutexGuard<'static, ()> {
+    EXCLUSIVE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Run `f`, returning its result and what it allocated on any thread.
+///
+/// Only meaningful in a binary whose global allocator is [`TrackingAllocator`], while holding
+/// [`exclusive_tracking`].
+pub fn track_allocations<R>(f: impl FnOnce() -> R) -> (R, AllocationStats) {
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes_before = CURRENT_BYTES.load(Ordering::Relaxed);
+    PEAK_BYTES.store(bytes_before, Ordering::Relaxed);
+    let result = f();
+    let stats = AllocationStats {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations_before,
+        peak_bytes: PEAK_BYTES.load(Ordering::Relaxed) - bytes_before,
+    };
+    (result, stats)
+}
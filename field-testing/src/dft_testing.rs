@@ -1,5 +1,6 @@
 use p3_dft::{NaiveDft, TwoAdicSubgroupDft};
 use p3_field::TwoAdicField;
+use p3_matrix::assert_matrices_eq;
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::Matrix;
 use rand::distributions::{Distribution, Standard};
@@ -18,7 +19,7 @@ where
         let mat = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
         let dft_naive = NaiveDft.dft_batch(mat.clone());
         let dft_result = dft.dft_batch(mat);
-        assert_eq!(dft_naive, dft_result.to_row_major_matrix());
+        assert_matrices_eq!(dft_naive, dft_result, "log_h = {log_h}");
     }
 }
 
@@ -36,7 +37,7 @@ where
         let shift = F::GENERATOR;
         let coset_dft_naive = NaiveDft.coset_dft_batch(mat.clone(), shift);
         let coset_dft_result = dft.coset_dft_batch(mat, shift);
-        assert_eq!(coset_dft_naive, coset_dft_result.to_row_major_matrix());
+        assert_matrices_eq!(coset_dft_naive, coset_dft_result, "log_h = {log_h}");
     }
 }
 
@@ -53,7 +54,7 @@ where
         let mat = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
         let idft_naive = NaiveDft.idft_batch(mat.clone());
         let idft_result = dft.idft_batch(mat.clone());
-        assert_eq!(idft_naive, idft_result.to_row_major_matrix());
+        assert_matrices_eq!(idft_naive, idft_result, "log_h = {log_h}");
     }
 }
 
@@ -71,7 +72,7 @@ where
         let shift = F::GENERATOR;
         let idft_naive = NaiveDft.coset_idft_batch(mat.clone(), shift);
         let idft_result = dft.coset_idft_batch(mat, shift);
-        assert_eq!(idft_naive, idft_result.to_row_major_matrix());
+        assert_matrices_eq!(idft_naive, idft_result, "log_h = {log_h}");
     }
 }
 
@@ -88,7 +89,7 @@ where
         let mat = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
         let lde_naive = NaiveDft.lde_batch(mat.clone(), 1);
         let lde_result = dft.lde_batch(mat, 1);
-        assert_eq!(lde_naive, lde_result.to_row_major_matrix());
+        assert_matrices_eq!(lde_naive, lde_result, "log_h = {log_h}");
     }
 }
 
@@ -106,10 +107,88 @@ where
         let shift = F::GENERATOR;
         let coset_lde_naive = NaiveDft.coset_lde_batch(mat.clone(), 1, shift);
         let coset_lde_result = dft.coset_lde_batch(mat, 1, shift);
-        assert_eq!(coset_lde_naive, coset_lde_result.to_row_major_matrix());
+        assert_matrices_eq!(coset_lde_naive, coset_lde_result, "log_h = {log_h}");
     }
 }
 
+pub fn test_coset_lde_many_added_bits_matches_naive<F, Dft>()
+where
+    F: TwoAdicField,
+    Standard: Distribution<F>,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    let dft = Dft::default();
+    let mut rng = thread_rng();
+    for log_h in 0..4 {
+        for added_bits in 2..5 {
+            let h = 1 << log_h;
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
+            let shift = F::GENERATOR;
+            let coset_lde_naive = NaiveDft.coset_lde_batch(mat.clone(), added_bits, shift);
+            let coset_lde_result = dft.coset_lde_batch(mat, added_bits, shift);
+            assert_matrices_eq!(
+                coset_lde_naive,
+                coset_lde_result,
+                "log_h = {log_h}, added_bits = {added_bits}"
+            );
+        }
+    }
+}
+
+pub fn test_coset_lde_no_added_bits<F, Dft>()
+where
+    F: TwoAdicField,
+    Standard: Distribution<F>,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    let dft = Dft::default();
+    let mut rng = thread_rng();
+    for log_h in 0..5 {
+        let h = 1 << log_h;
+        let mat = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
+        let shift = F::GENERATOR;
+        let coeffs = NaiveDft.idft_batch(mat.clone());
+        let coset_dft_naive = NaiveDft.coset_dft_batch(coeffs, shift);
+        let coset_lde_result = dft.coset_lde_batch(mat, 0, shift);
+        assert_matrices_eq!(coset_dft_naive, coset_lde_result, "log_h = {log_h}");
+    }
+}
+
+pub fn test_coset_lde_shifts_matches_single_shift<F, Dft>()
+where
+    F: TwoAdicField,
+    Standard: Distribution<F>,
+    Dft: TwoAdicSubgroupDft<F>,
+{
+    let dft = Dft::default();
+    let mut rng = thread_rng();
+    let shifts = [
+        F::GENERATOR,
+        F::ONE,
+        F::GENERATOR.square(),
+        F::GENERATOR.inverse(),
+    ];
+    for log_h in 0..5 {
+        for added_bits in 0..3 {
+            let h = 1 << log_h;
+            let mat = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
+            let ldes = dft.coset_lde_batch_shifts(mat.clone(), added_bits, &shifts);
+            assert_eq!(ldes.len(), shifts.len());
+            for (lde, &shift) in ldes.into_iter().zip(&shifts) {
+                let expected = NaiveDft.coset_lde_batch(mat.clone(), added_bits, shift);
+                assert_matrices_eq!(
+                    expected,
+                    lde,
+                    "log_h = {log_h}, added_bits = {added_bits}, shift = {shift}"
+                );
+            }
+        }
+    }
+    assert!(dft
+        .coset_lde_batch_shifts(RowMajorMatrix::<F>::rand(&mut rng, 4, 3), 1, &[])
+        .is_empty());
+}
+
 pub fn test_dft_idft_consistency<F, Dft>()
 where
     F: TwoAdicField,
@@ -123,7 +202,7 @@ where
         let original = RowMajorMatrix::<F>::rand(&mut rng, h, 3);
         let dft_output = dft.dft_batch(original.clone());
         let idft_output = dft.idft_batch(dft_output.to_row_major_matrix());
-        assert_eq!(original, idft_output.to_row_major_matrix());
+        assert_matrices_eq!(original, idft_output, "log_h = {log_h}");
     }
 }
 
@@ -161,6 +240,21 @@ macro_rules! test_field_dft {
                 $crate::test_coset_lde_matches_naive::<$field, $dft>();
             }
 
+            #[test]
+            fn coset_lde_many_added_bits_matches_naive() {
+                $crate::test_coset_lde_many_added_bits_matches_naive::<$field, $dft>();
+            }
+
+            #[test]
+            fn coset_lde_no_added_bits() {
+                $crate::test_coset_lde_no_added_bits::<$field, $dft>();
+            }
+
+            #[test]
+            fn coset_lde_shifts_matches_single_shift() {
+                $crate::test_coset_lde_shifts_matches_single_shift::<$field, $dft>();
+            }
+
             #[test]
             fn dft_idft_consistency() {
                 $crate::test_dft_idft_consistency::<$field, $dft>();
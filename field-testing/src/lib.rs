@@ -4,15 +4,18 @@
 
 extern crate alloc;
 
+pub mod alloc_tracking;
 pub mod bench_func;
 pub mod dft_testing;
 pub mod packedfield_testing;
 
+pub use alloc_tracking::*;
 pub use bench_func::*;
 pub use dft_testing::*;
 use num_bigint::BigUint;
 use num_traits::identities::One;
 use p3_field::{
+    batch_multiplicative_inverse, batch_multiplicative_inverse_packed,
     cyclic_subgroup_coset_known_order, cyclic_subgroup_known_order, two_adic_coset_zerofier,
     two_adic_subgroup_zerofier, ExtensionField, Field, TwoAdicField,
 };
@@ -86,6 +89,25 @@ where
     }
 }
 
+pub fn test_batch_multiplicative_inverse<F: Field>()
+where
+    Standard: Distribution<F>,
+{
+    let mut rng = rand::thread_rng();
+    // Cover empty input, partial packed vectors and multiple parallel chunks.
+    for n in [0, 1, 3, 16, 17, 1024, 2048 + 5] {
+        let x: alloc::vec::Vec<F> = (0..n)
+            .map(|_| rng.gen::<F>())
+            .map(|x| if x.is_zero() { F::ONE } else { x })
+            .collect();
+        let inverses = batch_multiplicative_inverse(&x);
+        for (&x, &x_inv) in x.iter().zip(&inverses) {
+            assert_eq!(x * x_inv, F::ONE);
+        }
+        assert_eq!(batch_multiplicative_inverse_packed(&x), inverses, "n = {n}");
+    }
+}
+
 pub fn test_multiplicative_group_factors<F: Field>() {
     let product: BigUint = F::multiplicative_group_factors()
         .into_iter()
@@ -150,6 +172,10 @@ macro_rules! test_field {
                 $crate::test_inverse::<$field>();
             }
             #[test]
+            fn test_batch_multiplicative_inverse() {
+                $crate::test_batch_multiplicative_inverse::<$field>();
+            }
+            #[test]
             fn test_multiplicative_group_factors() {
                 $crate::test_multiplicative_group_factors::<$field>();
             }
@@ -17,7 +17,8 @@ use super::{HasFrobenius, HasTwoAdicBionmialExtension};
 use crate::extension::BinomiallyExtendable;
 use crate::field::Field;
 use crate::{
-    field_to_array, AbstractExtensionField, AbstractField, ExtensionField, Packable, TwoAdicField,
+    field_to_array, AbstractExtensionField, AbstractField, ContiguousExtensionField,
+    ExtensionField, Packable, TwoAdicField,
 };
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Serialize, Deserialize, PartialOrd, Ord)]
@@ -54,6 +55,13 @@ impl<F: BinomiallyExtendable<D>, const D: usize> ExtensionField<F>
     type ExtensionPacking = BinomialExtensionField<F::Packing, D>;
 }
 
+// SAFETY: `BinomialExtensionField` is a `repr(transparent)` wrapper around `[F; D]`, which
+// `as_base_slice` returns.
+unsafe impl<F: BinomiallyExtendable<D>, const D: usize> ContiguousExtensionField<F>
+    for BinomialExtensionField<F, D>
+{
+}
+
 impl<F: BinomiallyExtendable<D>, const D: usize> HasFrobenius<F> for BinomialExtensionField<F, D> {
     /// FrobeniusField automorphisms: x -> x^n, where n is the order of BaseField.
     fn frobenius(&self) -> Self {
@@ -416,6 +416,19 @@ impl<F: Field> ExtensionField<F> for F {
     type ExtensionPacking = F::Packing;
 }
 
+/// An extension field whose elements are stored as their `D` coefficients over `Base`, contiguously
+/// and in the order of `as_base_slice`. Slices of `Self` can then be reinterpreted as slices of
+/// `Base` `D` times as long, and back.
+///
+/// # Safety
+/// - `Self` must have the size of `[Base; D]` and the alignment of `Base`.
+/// - `as_base_slice` must return the coefficients stored in `self`, in memory order.
+/// - Every array of `D` base field elements must be a valid `Self`.
+pub unsafe trait ContiguousExtensionField<Base: Field>: ExtensionField<Base> {}
+
+// SAFETY: a field is its own coefficient.
+unsafe impl<F: Field> ContiguousExtensionField<F> for F {}
+
 impl<AF: AbstractField> AbstractExtensionField<AF> for AF {
     const D: usize = 1;
 
@@ -1,22 +1,109 @@
 use alloc::vec::Vec;
 use core::fmt::Debug;
 
+use p3_commit::Mmcs;
 use p3_field::Field;
 use p3_matrix::Matrix;
 
 #[derive(Debug)]
 pub struct FriConfig<M> {
     pub log_blowup: usize,
+    /// FRI stops folding at a polynomial of `2^log_final_poly_len` coefficients, and sends those
+    /// instead. Inputs must be at least `blowup * final_poly_len` tall, bar ones of just `blowup`.
+    pub log_final_poly_len: usize,
+    /// The number of query indices sampled after the commit phase, which may repeat.
     pub num_queries: usize,
+    /// The bits of proof of work ground for before the query phase.
     pub proof_of_work_bits: usize,
+    /// With zero `proof_of_work_bits`, leave the proof of work out of the transcript altogether.
+    /// The prover and verifier must agree on this.
+    pub omit_zero_bit_pow: bool,
     pub mmcs: M,
 }
 
+/// The reasons a target security level can't be met by [`FriConfig::for_security`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecurityTargetError {
+    /// With a blowup of 1, a query carries no soundness, so no number of queries will do.
+    ZeroLogBlowup,
+    /// Challenges are sampled from the challenge field, so a cheating prover guessing them
+    /// succeeds with probability at least `1 / |F|`; the target can't exceed the field's bits.
+    ExceedsFieldBits {
+        target_bits: usize,
+        field_bits: usize,
+    },
+}
+
 impl<M> FriConfig<M> {
+    /// Builds a config with the fewest queries reaching `target_bits` of conjectured soundness
+    /// (see [`conjectured_soundness_bits`](Self::conjectured_soundness_bits)), i.e.
+    ///
+    /// `num_queries = max(1, ceil((target_bits - proof_of_work_bits) / log_blowup))`,
+    ///
+    /// where the subtraction saturates at zero. At least one query is always made, since
+    /// grinding alone doesn't test proximity at all. The config folds all the way down to a
+    /// constant; `log_final_poly_len` can be raised afterwards without changing the soundness.
+    ///
+    /// Only the conjectured bound is offered. A proven bound, as in
+    /// [BCIKS20](https://eprint.iacr.org/2020/654), adds a commit phase error which grows with the
+    /// length of the codewords relative to the field size, and the codewords' length isn't known
+    /// until the inputs are. Its query term alone also takes several times as many queries: in the
+    /// unique decoding regime a query gives only `log2(2 / (1 + 2^-log_blowup))` bits, about 0.42
+    /// rather than 1 at a blowup of 2. For proven soundness, work out `num_queries` for the
+    /// largest input and set it directly.
+    ///
+    /// Query indices are sampled independently, so they repeat whenever `num_queries` exceeds the
+    /// height of the tallest codeword, as it can for tiny traces. A repeated query is checked like
+    /// any other but adds no soundness, which the conjectured bound doesn't account for. The proof
+    /// of work is ground with whichever [`PowStrategy`](crate::PowStrategy) the prover and
+    /// verifier were given; with `omit_zero_bit_pow` unset, a witness is ground and observed even
+    /// for zero bits, as proofs made before that option existed expect.
+    pub fn for_security<F: Field>(
+        target_bits: usize,
+        log_blowup: usize,
+        proof_of_work_bits: usize,
+        mmcs: M,
+    ) -> Result<Self, SecurityTargetError>
+    where
+        M: Mmcs<F>,
+    {
+        if log_blowup == 0 {
+            return Err(SecurityTargetError::ZeroLogBlowup);
+        }
+        let field_bits = F::bits();
+        if target_bits > field_bits {
+            return Err(SecurityTargetError::ExceedsFieldBits {
+                target_bits,
+                field_bits,
+            });
+        }
+        let num_queries = target_bits
+            .saturating_sub(proof_of_work_bits)
+            .div_ceil(log_blowup)
+            .max(1);
+        Ok(Self {
+            log_blowup,
+            log_final_poly_len: 0,
+            num_queries,
+            proof_of_work_bits,
+            omit_zero_bit_pow: false,
+            mmcs,
+        })
+    }
+
     pub const fn blowup(&self) -> usize {
         1 << self.log_blowup
     }
 
+    pub const fn final_poly_len(&self) -> usize {
+        1 << self.log_final_poly_len
+    }
+
+    /// Whether the transcript includes a proof of work, and the proof a witness for it.
+    pub const fn grinds(&self) -> bool {
+        self.proof_of_work_bits > 0 || !self.omit_zero_bit_pow
+    }
+
     /// Returns the soundness bits of this FRI instance based on the
     /// [ethSTARK](https://eprint.iacr.org/2021/582) conjecture.
     ///
@@ -38,8 +125,8 @@ pub trait FriGenericConfig<F: Field> {
     fn extra_query_index_bits(&self) -> usize;
 
     /// Fold a row, returning a single column.
-    /// Right now the input row will always be 2 columns wide,
-    /// but we may support higher folding arity in the future.
+    /// The two-adic FRI prover always passes rows 2 columns wide; callers folding with a higher
+    /// arity pass a power of two columns.
     fn fold_row(
         &self,
         index: usize,
@@ -51,3 +138,86 @@ pub trait FriGenericConfig<F: Field> {
     /// Same as applying fold_row to every row, possibly faster.
     fn fold_matrix<M: Matrix<F>>(&self, beta: F, m: M) -> Vec<F>;
 }
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_commit::ExtensionMmcs;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::Field;
+    use p3_merkle_tree::MerkleTreeMmcs;
+    use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+    use rand::thread_rng;
+
+    use super::*;
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+    type Perm = Poseidon2BabyBear<16>;
+    type ValMmcs = MerkleTreeMmcs<
+        <Val as Field>::Packing,
+        <Val as Field>::Packing,
+        PaddingFreeSponge<Perm, 16, 8, 8>,
+        TruncatedPermutation<Perm, 2, 8, 16>,
+        8,
+    >;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
+
+    fn mmcs() -> ChallengeMmcs {
+        let perm = Perm::new_from_rng_128(&mut thread_rng());
+        ChallengeMmcs::new(ValMmcs::new(
+            PaddingFreeSponge::new(perm.clone()),
+            TruncatedPermutation::new(perm),
+        ))
+    }
+
+    fn num_queries(
+        target_bits: usize,
+        log_blowup: usize,
+        proof_of_work_bits: usize,
+    ) -> Result<usize, SecurityTargetError> {
+        FriConfig::for_security::<Challenge>(target_bits, log_blowup, proof_of_work_bits, mmcs())
+            .map(|config| config.num_queries)
+    }
+
+    #[test]
+    fn pinned_query_counts() {
+        assert_eq!(num_queries(100, 1, 0), Ok(100));
+        assert_eq!(num_queries(100, 1, 16), Ok(84));
+        assert_eq!(num_queries(100, 2, 0), Ok(50));
+        assert_eq!(num_queries(100, 2, 16), Ok(42));
+        assert_eq!(num_queries(100, 3, 16), Ok(28));
+        assert_eq!(num_queries(100, 4, 20), Ok(20));
+        assert_eq!(num_queries(16, 1, 16), Ok(1));
+    }
+
+    #[test]
+    fn reaches_target() {
+        for (target_bits, log_blowup, proof_of_work_bits) in [(100, 1, 16), (100, 3, 0), (80, 3, 7)]
+        {
+            let config = FriConfig::for_security::<Challenge>(
+                target_bits,
+                log_blowup,
+                proof_of_work_bits,
+                mmcs(),
+            )
+            .unwrap();
+            assert!(config.conjectured_soundness_bits() >= target_bits);
+        }
+    }
+
+    #[test]
+    fn unreachable_targets() {
+        assert_eq!(
+            num_queries(100, 0, 16),
+            Err(SecurityTargetError::ZeroLogBlowup)
+        );
+        assert_eq!(
+            num_queries(128, 1, 16),
+            Err(SecurityTargetError::ExceedsFieldBits {
+                target_bits: 128,
+                field_bits: 124,
+            })
+        );
+    }
+}
@@ -6,12 +6,16 @@ extern crate alloc;
 
 mod config;
 mod fold_even_odd;
+mod pow;
 mod proof;
 pub mod prover;
+mod query_index;
 mod two_adic_pcs;
 pub mod verifier;
 
 pub use config::*;
 pub use fold_even_odd::*;
+pub use pow::*;
 pub use proof::*;
+pub use query_index::*;
 pub use two_adic_pcs::*;
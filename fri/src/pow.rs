@@ -0,0 +1,45 @@
+use p3_challenger::GrindingChallenger;
+
+/// Produces and checks the proof-of-work witness that FRI grinds for between its commit and query
+/// phases.
+///
+/// The prover and verifier must grind with the same implementation, or every witness the prover
+/// finds will be rejected. Implementations should bind the witness to the transcript, typically by
+/// deriving their puzzle from the challenger's state and observing the witness once it is found,
+/// so that the query indices sampled afterwards depend on it.
+pub trait PowStrategy<Challenger: GrindingChallenger> {
+    /// Find a witness demonstrating `bits` bits of work, updating the challenger as
+    /// [`check_witness`](Self::check_witness) will.
+    fn grind(&self, challenger: &mut Challenger, bits: usize) -> Challenger::Witness;
+
+    /// Check that `witness` demonstrates `bits` bits of work.
+    #[must_use]
+    fn check_witness(
+        &self,
+        challenger: &mut Challenger,
+        bits: usize,
+        witness: Challenger::Witness,
+    ) -> bool;
+}
+
+/// Grinds with the challenger itself, via [`GrindingChallenger`].
+///
+/// This is the default. The work is done with the challenger's own permutation, which for an
+/// algebraic sponge is cheap to verify in a circuit but comparatively slow to grind on a CPU.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChallengerPow;
+
+impl<Challenger: GrindingChallenger> PowStrategy<Challenger> for ChallengerPow {
+    fn grind(&self, challenger: &mut Challenger, bits: usize) -> Challenger::Witness {
+        challenger.grind(bits)
+    }
+
+    fn check_witness(
+        &self,
+        challenger: &mut Challenger,
+        bits: usize,
+        witness: Challenger::Witness,
+    ) -> bool {
+        challenger.check_witness(bits, witness)
+    }
+}
@@ -1,7 +1,10 @@
 use alloc::vec::Vec;
+use core::fmt::{Display, Formatter};
 
 use p3_commit::Mmcs;
-use p3_field::Field;
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::{Field, TwoAdicField};
+use p3_util::reverse_slice_index_bits;
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -10,12 +13,157 @@ use serde::{Deserialize, Serialize};
     deserialize = "Witness: Deserialize<'de>, InputProof: Deserialize<'de>"
 ))]
 pub struct FriProof<F: Field, M: Mmcs<F>, Witness, InputProof> {
-    pub commit_phase_commits: Vec<M::Commitment>,
-    pub query_proofs: Vec<QueryProof<F, M, InputProof>>,
-    // This could become Vec<FC::Challenge> if this library was generalized to support non-constant
-    // final polynomials.
-    pub final_poly: F,
-    pub pow_witness: Witness,
+    commit_phase_commits: Vec<M::Commitment>,
+    query_proofs: Vec<QueryProof<F, M, InputProof>>,
+    /// The coefficients of the polynomial the input is folded down to, lowest degree first.
+    final_poly: Vec<F>,
+    /// The proof-of-work witness, absent when the config asks for zero bits of grinding.
+    pow_witness: Option<Witness>,
+}
+
+impl<F: Field, M: Mmcs<F>, Witness, InputProof> FriProof<F, M, Witness, InputProof> {
+    pub const fn new(
+        commit_phase_commits: Vec<M::Commitment>,
+        query_proofs: Vec<QueryProof<F, M, InputProof>>,
+        final_poly: Vec<F>,
+        pow_witness: Option<Witness>,
+    ) -> Self {
+        Self {
+            commit_phase_commits,
+            query_proofs,
+            final_poly,
+            pow_witness,
+        }
+    }
+
+    /// The number of commit phase rounds, i.e. the number of times the input was folded.
+    pub fn num_commit_phase_rounds(&self) -> usize {
+        self.commit_phase_commits.len()
+    }
+
+    pub fn commit_phase_commits(&self) -> &[M::Commitment] {
+        &self.commit_phase_commits
+    }
+
+    pub fn num_queries(&self) -> usize {
+        self.query_proofs.len()
+    }
+
+    pub fn query_proofs(&self) -> &[QueryProof<F, M, InputProof>] {
+        &self.query_proofs
+    }
+
+    pub fn final_poly(&self) -> &[F] {
+        &self.final_poly
+    }
+
+    /// The number of coefficients of the final polynomial, `FriConfig::final_poly_len` for a
+    /// well-formed proof.
+    pub fn final_poly_len(&self) -> usize {
+        self.final_poly.len()
+    }
+
+    pub const fn pow_witness(&self) -> Option<&Witness> {
+        self.pow_witness.as_ref()
+    }
+
+    /// For each query, the number of commit phase openings it contains.
+    pub fn query_opening_lens(&self) -> impl Iterator<Item = usize> + '_ {
+        self.query_proofs
+            .iter()
+            .map(|qp| qp.commit_phase_openings.len())
+    }
+}
+
+impl<F: TwoAdicField, M: Mmcs<F>, Witness, InputProof> FriProof<F, M, Witness, InputProof> {
+    /// Evaluate the final polynomial over the subgroup of order `final_poly_len << log_blowup`, in
+    /// bit-reversed order. For an honest proof this is the codeword the prover folded down to, so
+    /// external tools can check it against their own folding or interpolate it to confirm the
+    /// degree bound.
+    pub fn final_codeword(&self, log_blowup: usize) -> Vec<F> {
+        let mut coeffs = self.final_poly.clone();
+        coeffs.resize(self.final_poly.len() << log_blowup, F::ZERO);
+        let mut evals = Radix2Dit::default().dft(coeffs);
+        reverse_slice_index_bits(&mut evals);
+        evals
+    }
+}
+
+impl<F: Field, M: Mmcs<F>, Witness: Serialize, InputProof: Serialize>
+    FriProof<F, M, Witness, InputProof>
+{
+    /// The serialized size of each part of the proof, in bytes.
+    pub fn size_breakdown(&self) -> FriProofSize {
+        FriProofSize {
+            commit_phase_commits: serialized_size(&self.commit_phase_commits),
+            commit_phase_openings: self
+                .query_proofs
+                .iter()
+                .map(|qp| serialized_size(&qp.commit_phase_openings))
+                .sum(),
+            input_proofs: self
+                .query_proofs
+                .iter()
+                .map(|qp| serialized_size(&qp.input_proof))
+                .sum(),
+            final_poly: serialized_size(&self.final_poly),
+            pow_witness: serialized_size(&self.pow_witness),
+        }
+    }
+}
+
+/// The size of `value` in the postcard encoding, in bytes.
+pub(crate) fn serialized_size<T: Serialize + ?Sized>(value: &T) -> usize {
+    postcard::to_allocvec(value)
+        .expect("proofs serialize infallibly")
+        .len()
+}
+
+/// The serialized size of each part of a [`FriProof`], in bytes, as returned by
+/// [`FriProof::size_breakdown`].
+///
+/// Sizes are those of the postcard encoding. Each part is measured on its own, so the parts add up
+/// to the size of the whole proof up to a few length prefixes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FriProofSize {
+    pub commit_phase_commits: usize,
+    /// The commit phase openings of every query, siblings and MMCS proofs together.
+    pub commit_phase_openings: usize,
+    /// The input proofs of every query.
+    pub input_proofs: usize,
+    pub final_poly: usize,
+    pub pow_witness: usize,
+}
+
+impl FriProofSize {
+    pub const fn total(&self) -> usize {
+        self.commit_phase_commits
+            + self.commit_phase_openings
+            + self.input_proofs
+            + self.final_poly
+            + self.pow_witness
+    }
+}
+
+/// A one-line summary of the proof's shape and of the size of each of its parts.
+impl<F: Field, M: Mmcs<F>, Witness: Serialize, InputProof: Serialize> Display
+    for FriProof<F, M, Witness, InputProof>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let size = self.size_breakdown();
+        write!(
+            f,
+            "FRI proof: {} commit phase rounds, {} queries; {} B of commitments, {} B of commit \
+             phase openings, {} B of input proofs, {} B of final polynomial, {} B of PoW witness",
+            self.num_commit_phase_rounds(),
+            self.num_queries(),
+            size.commit_phase_commits,
+            size.commit_phase_openings,
+            size.input_proofs,
+            size.final_poly,
+            size.pow_witness
+        )
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -30,6 +178,16 @@ pub struct QueryProof<F: Field, M: Mmcs<F>, InputProof> {
     pub commit_phase_openings: Vec<CommitPhaseProofStep<F, M>>,
 }
 
+impl<F: Field, M: Mmcs<F>, InputProof> QueryProof<F, M, InputProof> {
+    pub const fn input_proof(&self) -> &InputProof {
+        &self.input_proof
+    }
+
+    pub fn commit_phase_openings(&self) -> &[CommitPhaseProofStep<F, M>] {
+        &self.commit_phase_openings
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
 pub struct CommitPhaseProofStep<F: Field, M: Mmcs<F>> {
@@ -5,25 +5,34 @@ use core::iter;
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
-use p3_field::{ExtensionField, Field};
+use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
+use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::dense::RowMajorMatrix;
-use p3_util::log2_strict_usize;
+use p3_maybe_rayon::prelude::*;
+use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits};
 use tracing::{info_span, instrument};
 
-use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof, QueryProof};
+use crate::{
+    CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof, PowStrategy, QueryIndexSampler,
+    QueryProof,
+};
 
 #[instrument(name = "FRI prover", skip_all)]
 pub fn prove<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    query_sampler: &impl QueryIndexSampler<Challenger>,
+    pow: &impl PowStrategy<Challenger>,
     inputs: Vec<Vec<Challenge>>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize) -> G::InputProof,
 ) -> FriProof<Challenge, M, Challenger::Witness, G::InputProof>
 where
     Val: Field,
-    Challenge: ExtensionField<Val>,
-    M: Mmcs<Challenge>,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge> + Sync,
+    M::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    M::Proof: Send,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
@@ -37,34 +46,44 @@ where
 
     let commit_phase_result = commit_phase(g, config, inputs, challenger);
 
-    let pow_witness = challenger.grind(config.proof_of_work_bits);
+    // With zero bits and `omit_zero_bit_pow`, the transcript skips the witness entirely.
+    let pow_witness = config
+        .grinds()
+        .then(|| pow.grind(challenger, config.proof_of_work_bits));
 
+    // The indices are sampled, and the inputs opened, in transcript order. The commit phase
+    // openings only read the prover data, so they are answered in parallel.
     let query_proofs = info_span!("query phase").in_scope(|| {
-        iter::repeat_with(|| challenger.sample_bits(log_max_height + g.extra_query_index_bits()))
-            .take(config.num_queries)
-            .map(|index| QueryProof {
-                input_proof: open_input(index),
-                commit_phase_openings: answer_query(
-                    config,
-                    &commit_phase_result.data,
-                    index >> g.extra_query_index_bits(),
-                ),
+        let (indices, input_proofs): (Vec<_>, Vec<_>) = iter::repeat_with(|| {
+            query_sampler.sample_index(challenger, log_max_height + g.extra_query_index_bits())
+        })
+        .take(config.num_queries)
+        .map(|index| (index >> g.extra_query_index_bits(), open_input(index)))
+        .unzip();
+        let commit_phase_openings: Vec<_> = indices
+            .into_par_iter()
+            .map(|index| answer_query(config, &commit_phase_result.data, index))
+            .collect();
+        izip!(input_proofs, commit_phase_openings)
+            .map(|(input_proof, commit_phase_openings)| QueryProof {
+                input_proof,
+                commit_phase_openings,
             })
             .collect()
     });
 
-    FriProof {
-        commit_phase_commits: commit_phase_result.commits,
+    FriProof::new(
+        commit_phase_result.commits,
         query_proofs,
-        final_poly: commit_phase_result.final_poly,
+        commit_phase_result.final_poly,
         pow_witness,
-    }
+    )
 }
 
 struct CommitPhaseResult<F: Field, M: Mmcs<F>> {
     commits: Vec<M::Commitment>,
     data: Vec<M::ProverData<RowMajorMatrix<F>>>,
-    final_poly: F,
+    final_poly: Vec<F>,
 }
 
 #[instrument(name = "commit phase", skip_all)]
@@ -76,17 +95,24 @@ fn commit_phase<G, Val, Challenge, M, Challenger>(
 ) -> CommitPhaseResult<Challenge, M>
 where
     Val: Field,
-    Challenge: ExtensionField<Val>,
+    Challenge: ExtensionField<Val> + TwoAdicField,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
+    let final_height = config.blowup() * config.final_poly_len();
+    assert!(
+        inputs[0].len() >= final_height,
+        "the tallest input has height {}, less than the final codeword's {final_height}",
+        inputs[0].len()
+    );
+
     let mut inputs_iter = inputs.into_iter().peekable();
     let mut folded = inputs_iter.next().unwrap();
     let mut commits = vec![];
     let mut data = vec![];
 
-    while folded.len() > config.blowup() {
+    while folded.len() > final_height {
         let leaves = RowMajorMatrix::new(folded, 2);
         let (commit, prover_data) = config.mmcs.commit_matrix(leaves);
         challenger.observe(commit.clone());
@@ -104,13 +130,36 @@ where
         }
     }
 
-    // We should be left with `blowup` evaluations of a constant polynomial.
-    assert_eq!(folded.len(), config.blowup());
-    let final_poly = folded[0];
-    for x in folded {
-        assert_eq!(x, final_poly);
+    // Inputs shorter than the final codeword can't be rolled in. Those only `blowup` tall encode
+    // constants, whose quotients vanish, so they can be skipped like the verifier skips them.
+    for v in inputs_iter {
+        assert_eq!(
+            v.len(),
+            config.blowup(),
+            "an input of height {} is shorter than the final codeword's {final_height}",
+            v.len()
+        );
+        debug_assert!(v.iter().all(|x| x.is_zero()));
+    }
+
+    // We should be left with the evaluations of a polynomial with `final_poly_len` coefficients,
+    // over the subgroup of order `final_height`, in bit-reversed order. Its first
+    // `final_poly_len` evaluations are over the subgroup of that order, which determine it.
+    assert_eq!(folded.len(), final_height);
+    let mut evals = folded[..config.final_poly_len()].to_vec();
+    reverse_slice_index_bits(&mut evals);
+    let final_poly = Radix2Dit::default().idft(evals);
+
+    // The remaining evaluations must agree, or the input wasn't low degree.
+    let log_final_height = log2_strict_usize(final_height);
+    let g = Challenge::two_adic_generator(log_final_height);
+    for (i, &y) in folded.iter().enumerate() {
+        let x = g.exp_u64(reverse_bits_len(i, log_final_height) as u64);
+        assert_eq!(eval_poly(&final_poly, x), y);
+    }
+    for &c in &final_poly {
+        challenger.observe_ext_element(c);
     }
-    challenger.observe_ext_element(final_poly);
 
     CommitPhaseResult {
         commits,
@@ -149,3 +198,8 @@ where
         })
         .collect()
 }
+
+/// Evaluate the polynomial with coefficients `coeffs`, lowest degree first, at `x`.
+pub(crate) fn eval_poly<F: Field>(coeffs: &[F], x: F) -> F {
+    coeffs.iter().rev().fold(F::ZERO, |acc, &c| acc * x + c)
+}
@@ -0,0 +1,110 @@
+use core::marker::PhantomData;
+
+use p3_challenger::{CanSample, CanSampleBits};
+use p3_field::PrimeField64;
+
+/// Derives FRI query indices from the challenger.
+///
+/// The prover and verifier must sample with the same implementation, or they will disagree on
+/// which positions are queried.
+pub trait QueryIndexSampler<Challenger> {
+    /// Sample an index in `0..2^bits`.
+    fn sample_index(&self, challenger: &mut Challenger, bits: usize) -> usize;
+}
+
+/// Takes the low `bits` bits of a single field element sampled from the challenger.
+///
+/// This is the default. Unless the field's order is a power of two, indices below
+/// `p mod 2^bits` are very slightly more likely than the others; use [`RejectionSampling`] where
+/// that matters.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SampleLowBits;
+
+impl<Challenger: CanSampleBits<usize>> QueryIndexSampler<Challenger> for SampleLowBits {
+    fn sample_index(&self, challenger: &mut Challenger, bits: usize) -> usize {
+        challenger.sample_bits(bits)
+    }
+}
+
+/// Samples field elements until one falls below the largest multiple of `2^bits` no greater than
+/// the field's order, then takes its low `bits` bits.
+///
+/// Every index is then equally likely. Since rejection only happens with probability
+/// `(p mod 2^bits) / p`, it costs an extra sample only very rarely. The accepted samples are
+/// reduced exactly as [`SampleLowBits`] would, so the two agree whenever nothing is rejected.
+#[derive(Debug)]
+pub struct RejectionSampling<F>(PhantomData<F>);
+
+impl<F> RejectionSampling<F> {
+    pub const fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<F> Default for RejectionSampling<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<F> Clone for RejectionSampling<F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<F> Copy for RejectionSampling<F> {}
+
+impl<F: PrimeField64, Challenger: CanSample<F>> QueryIndexSampler<Challenger>
+    for RejectionSampling<F>
+{
+    fn sample_index(&self, challenger: &mut Challenger, bits: usize) -> usize {
+        assert!(
+            bits < 64 && (1 << bits) < F::ORDER_U64,
+            "cannot sample {bits} bits from a single field element"
+        );
+        let bound = (F::ORDER_U64 >> bits) << bits;
+        loop {
+            let x = challenger.sample().as_canonical_u64();
+            if x < bound {
+                return (x & ((1 << bits) - 1)) as usize;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
+    use p3_challenger::{CanObserve, DuplexChallenger};
+    use p3_field::AbstractField;
+    use rand::thread_rng;
+
+    use super::*;
+
+    type F = BabyBear;
+    type Perm = Poseidon2BabyBear<16>;
+    type Challenger = DuplexChallenger<F, Perm, 16, 8>;
+
+    #[test]
+    fn rejection_sampling_agrees_with_default() {
+        let perm = Perm::new_from_rng_128(&mut thread_rng());
+        for bits in [0, 1, 5, 12, 20, 27] {
+            let mut default_challenger = Challenger::new(perm.clone());
+            default_challenger.observe(F::from_canonical_usize(bits));
+            let mut unbiased_challenger = default_challenger.clone();
+
+            for _ in 0..100 {
+                let index = SampleLowBits.sample_index(&mut default_challenger, bits);
+                assert!(index < 1 << bits);
+                // BabyBear's order is 15 * 2^27 + 1, so at most one value in ~2 billion is
+                // rejected here, and the two samplers agree.
+                assert_eq!(
+                    RejectionSampling::<F>::new().sample_index(&mut unbiased_challenger, bits),
+                    index,
+                    "bits = {bits}"
+                );
+            }
+        }
+    }
+}
@@ -1,62 +1,497 @@
 use alloc::collections::BTreeMap;
 use alloc::vec;
 use alloc::vec::Vec;
-use core::fmt::Debug;
+use core::cell::{Cell, RefCell};
+use core::fmt::{Debug, Display, Formatter};
 use core::marker::PhantomData;
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::{Mmcs, OpenedValues, Pcs, PolynomialSpace, TwoAdicMultiplicativeCoset};
-use p3_dft::TwoAdicSubgroupDft;
+use p3_dft::{CosetLde, HasDefaultDft};
 use p3_field::{
     batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, dot_product, ExtensionField,
     Field, TwoAdicField,
 };
-use p3_interpolation::interpolate_coset;
+use p3_interpolation::{
+    coset_interpolation_scalars_with_weights, interpolate_coset_with_scalars,
+    subgroup_barycentric_weights,
+};
 use p3_matrix::bitrev::{BitReversableMatrix, BitReversalPerm};
-use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::dense::{Padding, RowMajorMatrix};
+use p3_matrix::row_index_mapped::{RowIndexMap, RowIndexMappedView};
+use p3_matrix::strided::VerticallyStridedRowIndexMap;
 use p3_matrix::{Dimensions, Matrix};
 use p3_maybe_rayon::prelude::*;
 use p3_util::linear_map::LinearMap;
-use p3_util::{log2_strict_usize, reverse_bits_len, reverse_slice_index_bits, VecExt};
+use p3_util::{
+    log2_ceil_usize, log2_strict_usize, reverse_bits_len, reverse_slice_index_bits, VecExt,
+};
 use serde::{Deserialize, Serialize};
 use tracing::{info_span, instrument};
 
-use crate::verifier::{self, FriError};
-use crate::{prover, FriConfig, FriGenericConfig, FriProof};
+use crate::proof::serialized_size;
+use crate::verifier::{self, FriError, ProofShapeError};
+use crate::{
+    prover, ChallengerPow, FriConfig, FriGenericConfig, FriProof, FriProofSize, PowStrategy,
+    QueryIndexSampler, SampleLowBits,
+};
 
+/// A polynomial commitment scheme using FRI over a two-adic field.
+///
+/// This requires `Val: TwoAdicField`, so it can't be used with Mersenne31, whose multiplicative
+/// group has a power-of-two subgroup of order only 2. For Mersenne31, use `CirclePcs` from
+/// `p3-circle`, which works over the circle group of order `p + 1 = 2^31` instead.
+///
+/// Commitments are hiding if `InputMmcs` and `FriMmcs` are, e.g. with `MerkleTreeHidingMmcs`,
+/// which salts every leaf row with fresh randomness and opens the salts along with the rows.
+/// With a plain `MerkleTreeMmcs` nothing is salted, and there is no overhead.
 #[derive(Debug)]
-pub struct TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
+pub struct TwoAdicFriPcs<
+    Val,
+    Dft,
+    InputMmcs,
+    FriMmcs,
+    QuerySampler = SampleLowBits,
+    Pow = ChallengerPow,
+> {
     dft: Dft,
-    mmcs: InputMmcs,
-    fri: FriConfig<FriMmcs>,
-    _phantom: PhantomData<Val>,
+    verifier: TwoAdicFriVerifier<Val, InputMmcs, FriMmcs, QuerySampler, Pow>,
+    /// How to fill out matrices whose height is not a power of two, if at all.
+    padding: Option<TracePadding>,
+}
+
+/// How [`TwoAdicFriPcs`] fills out a matrix up to the power-of-two size of its domain, when
+/// enabled with [`TwoAdicFriPcs::with_trace_padding`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TracePadding {
+    /// Append rows of zeros.
+    Zeros,
+    /// Append copies of the last row.
+    RepeatLastRow,
+}
+
+impl TracePadding {
+    fn pad<F: Field>(self, mat: &mut RowMajorMatrix<F>, height: usize) {
+        assert!(
+            mat.height() <= height,
+            "a matrix of height {} does not fit in a domain of size {height}",
+            mat.height()
+        );
+        let fill = match self {
+            Self::Zeros => Padding::Zero,
+            Self::RepeatLastRow => Padding::RepeatLastRow,
+        };
+        mat.pad_to_height(height, fill);
+    }
+}
+
+/// What [`TwoAdicFriPcs`] keeps to open a committed batch: the MMCS data of the committed LDEs,
+/// and the height each matrix had before it was padded.
+///
+/// Opening never modifies this, so the same batch can be opened again in later calls.
+pub struct TwoAdicFriProverData<Val: Send + Sync, InputMmcs: Mmcs<Val>> {
+    mmcs_data: InputMmcs::ProverData<RowMajorMatrix<Val>>,
+    original_heights: Vec<usize>,
+}
+
+impl<Val: Send + Sync, InputMmcs: Mmcs<Val>> TwoAdicFriProverData<Val, InputMmcs> {
+    /// The MMCS data of the committed LDEs, in bit-reversed order unless the PCS was configured
+    /// with `TwoAdicFriPcs::with_natural_order_ldes`.
+    pub const fn mmcs_data(&self) -> &InputMmcs::ProverData<RowMajorMatrix<Val>> {
+        &self.mmcs_data
+    }
+
+    /// The height of each committed matrix as it was passed to `commit`, before any padding.
+    pub fn original_heights(&self) -> &[usize] {
+        &self.original_heights
+    }
 }
 
 impl<Val, Dft, InputMmcs, FriMmcs> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs> {
+    /// `mmcs` commits to the LDEs of the input matrices, and `fri.mmcs` to the FRI commit-phase
+    /// codewords. The two are independent, so FRI may hash its extension field layers with
+    /// different parameters than the base field inputs, rather than extending the input MMCS.
     pub const fn new(dft: Dft, mmcs: InputMmcs, fri: FriConfig<FriMmcs>) -> Self {
         Self {
             dft,
+            verifier: TwoAdicFriVerifier::new(mmcs, fri),
+            padding: None,
+        }
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, QuerySampler, Pow>
+    TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, QuerySampler, Pow>
+{
+    /// Derive FRI query indices with `query_sampler` rather than [`SampleLowBits`]. Proofs can
+    /// only be verified by a PCS or verifier using the same sampler.
+    pub fn with_query_sampler<S>(
+        self,
+        query_sampler: S,
+    ) -> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, S, Pow> {
+        TwoAdicFriPcs {
+            dft: self.dft,
+            verifier: self.verifier.with_query_sampler(query_sampler),
+            padding: self.padding,
+        }
+    }
+
+    /// Grind for FRI's proof-of-work witness with `pow` rather than [`ChallengerPow`], e.g. to use
+    /// a hash which is faster on a CPU than the challenger's permutation. Proofs can only be
+    /// verified by a PCS or verifier using the same strategy.
+    pub fn with_pow_strategy<P>(
+        self,
+        pow: P,
+    ) -> TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, QuerySampler, P> {
+        TwoAdicFriPcs {
+            dft: self.dft,
+            verifier: self.verifier.with_pow_strategy(pow),
+            padding: self.padding,
+        }
+    }
+
+    /// Accept matrices of any height, padding each one up to the next power of two before its
+    /// LDE is computed. `natural_domain_for_degree` rounds up accordingly, and the opened values
+    /// are those of the padded matrix.
+    pub fn with_trace_padding(mut self, padding: TracePadding) -> Self {
+        self.padding = Some(padding);
+        self
+    }
+
+    /// Commit to the batched DEEP quotient of each height with the FRI MMCS, and open it at every
+    /// query alongside the inputs.
+    ///
+    /// The verifier runs FRI on the quotient openings, so its per-query FRI work doesn't depend on
+    /// the number of columns. The input openings are reduced once afterwards, to check them against
+    /// the quotient openings, which a recursive verifier may defer. This costs one extra
+    /// commitment and its openings.
+    pub fn with_committed_deep_quotient(mut self) -> Self {
+        self.verifier = self.verifier.with_committed_deep_quotient();
+        self
+    }
+
+    /// Commit to each LDE in natural order, rather than bit-reversed, for MMCSs or downstream
+    /// consumers which expect rows in the order of the coset.
+    ///
+    /// The MMCS opens shorter matrices of a batch at the query index shifted right, which only
+    /// picks out the matching row in bit-reversed order, so in this mode every matrix committed in
+    /// one batch must have the same height. Batches of different heights are fine.
+    pub fn with_natural_order_ldes(mut self) -> Self {
+        self.verifier = self.verifier.with_natural_order_ldes();
+        self
+    }
+
+    /// The verifying half of this PCS.
+    pub const fn verifier(
+        &self,
+    ) -> &TwoAdicFriVerifier<Val, InputMmcs, FriMmcs, QuerySampler, Pow> {
+        &self.verifier
+    }
+}
+
+/// The verifying half of [`TwoAdicFriPcs`], for verifiers which have no use for a DFT.
+///
+/// `TwoAdicFriPcs` verifies through this type, so the two always accept the same proofs.
+#[derive(Debug)]
+pub struct TwoAdicFriVerifier<
+    Val,
+    InputMmcs,
+    FriMmcs,
+    QuerySampler = SampleLowBits,
+    Pow = ChallengerPow,
+> {
+    mmcs: InputMmcs,
+    fri: FriConfig<FriMmcs>,
+    /// Whether to commit to the batched DEEP quotients before running FRI on them.
+    commit_deep_quotient: bool,
+    /// Whether the input LDEs are committed in natural rather than bit-reversed order.
+    natural_order_ldes: bool,
+    query_sampler: QuerySampler,
+    pow: Pow,
+    _phantom: PhantomData<Val>,
+}
+
+impl<Val, InputMmcs, FriMmcs> TwoAdicFriVerifier<Val, InputMmcs, FriMmcs> {
+    pub const fn new(mmcs: InputMmcs, fri: FriConfig<FriMmcs>) -> Self {
+        Self {
             mmcs,
             fri,
+            commit_deep_quotient: false,
+            natural_order_ldes: false,
+            query_sampler: SampleLowBits,
+            pow: ChallengerPow,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<Val, InputMmcs, FriMmcs, QuerySampler, Pow>
+    TwoAdicFriVerifier<Val, InputMmcs, FriMmcs, QuerySampler, Pow>
+{
+    /// Expect proofs from a PCS configured with `TwoAdicFriPcs::with_query_sampler`.
+    pub fn with_query_sampler<S>(
+        self,
+        query_sampler: S,
+    ) -> TwoAdicFriVerifier<Val, InputMmcs, FriMmcs, S, Pow> {
+        TwoAdicFriVerifier {
+            mmcs: self.mmcs,
+            fri: self.fri,
+            commit_deep_quotient: self.commit_deep_quotient,
+            natural_order_ldes: self.natural_order_ldes,
+            query_sampler,
+            pow: self.pow,
             _phantom: PhantomData,
         }
     }
+
+    /// Expect proofs from a PCS configured with `TwoAdicFriPcs::with_pow_strategy`.
+    pub fn with_pow_strategy<P>(
+        self,
+        pow: P,
+    ) -> TwoAdicFriVerifier<Val, InputMmcs, FriMmcs, QuerySampler, P> {
+        TwoAdicFriVerifier {
+            mmcs: self.mmcs,
+            fri: self.fri,
+            commit_deep_quotient: self.commit_deep_quotient,
+            natural_order_ldes: self.natural_order_ldes,
+            query_sampler: self.query_sampler,
+            pow,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Expect proofs from a PCS configured with `TwoAdicFriPcs::with_committed_deep_quotient`.
+    pub fn with_committed_deep_quotient(mut self) -> Self {
+        self.commit_deep_quotient = true;
+        self
+    }
+
+    /// Expect proofs from a PCS configured with `TwoAdicFriPcs::with_natural_order_ldes`.
+    pub fn with_natural_order_ldes(mut self) -> Self {
+        self.natural_order_ldes = true;
+        self
+    }
+
+    /// The row of a committed batch of LDEs with max height `2^log_max_height` to open for the
+    /// given query index, already shifted down to that height.
+    fn input_row_index(&self, index: usize, log_max_height: usize) -> usize {
+        if self.natural_order_ldes {
+            reverse_bits_len(index, log_max_height)
+        } else {
+            index
+        }
+    }
+
+    /// Openings can only be aggregated under one FRI proof if both sides agree on how it is run.
+    fn assert_same_fri_params(&self, other: &Self) {
+        assert_eq!(
+            (
+                self.fri.log_blowup,
+                self.fri.log_final_poly_len,
+                self.fri.num_queries,
+                self.fri.proof_of_work_bits,
+                self.fri.omit_zero_bit_pow,
+                self.commit_deep_quotient,
+            ),
+            (
+                other.fri.log_blowup,
+                other.fri.log_final_poly_len,
+                other.fri.num_queries,
+                other.fri.proof_of_work_bits,
+                other.fri.omit_zero_bit_pow,
+                other.commit_deep_quotient,
+            ),
+            "aggregated PCSs must share their FRI parameters"
+        );
+    }
+}
+
+/// A `TwoAdicFriPcs` using the default DFT for `Val`, as chosen by `HasDefaultDft`.
+pub type DefaultTwoAdicFriPcs<Val, InputMmcs, FriMmcs> =
+    TwoAdicFriPcs<Val, <Val as HasDefaultDft>::DefaultDft, InputMmcs, FriMmcs>;
+
+impl<Val: HasDefaultDft, InputMmcs, FriMmcs> DefaultTwoAdicFriPcs<Val, InputMmcs, FriMmcs> {
+    /// Like `new`, but with the default DFT for `Val`.
+    pub fn with_default_dft(mmcs: InputMmcs, fri: FriConfig<FriMmcs>) -> Self {
+        Self::new(Default::default(), mmcs, fri)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct TwoAdicFriPcsProof<
+    Val: Field,
+    Challenge: Field,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge>,
+> {
+    pub fri_proof: FriProof<Challenge, FriMmcs, Val, ()>,
+    /// For each round, the input openings of every query, authenticated by a single multi-proof.
+    pub input_openings: Vec<BatchMultiOpening<Val, InputMmcs>>,
+    /// Present only when the PCS was configured with `with_committed_deep_quotient`.
+    pub deep_quotient: Option<DeepQuotientOpening<Challenge, FriMmcs>>,
+}
+
+impl<Val: Field, Challenge: Field, InputMmcs: Mmcs<Val>, FriMmcs: Mmcs<Challenge>>
+    TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>
+{
+    pub const fn fri_proof(&self) -> &FriProof<Challenge, FriMmcs, Val, ()> {
+        &self.fri_proof
+    }
+
+    pub fn input_openings(&self) -> &[BatchMultiOpening<Val, InputMmcs>] {
+        &self.input_openings
+    }
+
+    pub const fn deep_quotient(&self) -> Option<&DeepQuotientOpening<Challenge, FriMmcs>> {
+        self.deep_quotient.as_ref()
+    }
+
+    /// For each batch, the widths of its opened matrices.
+    pub fn opened_widths(&self) -> Vec<Vec<usize>> {
+        self.input_openings
+            .iter()
+            .map(BatchMultiOpening::opened_widths)
+            .collect()
+    }
+
+    /// The serialized size of each part of the proof, in bytes.
+    pub fn size_breakdown(&self) -> TwoAdicFriPcsProofSize {
+        TwoAdicFriPcsProofSize {
+            fri: self.fri_proof.size_breakdown(),
+            input_openings: self.input_openings.iter().map(serialized_size).collect(),
+            deep_quotient: self.deep_quotient.as_ref().map_or(0, serialized_size),
+        }
+    }
+
+    /// Counts of what this proof contains, e.g. for telemetry.
+    pub fn stats(&self) -> ProofStats {
+        ProofStats {
+            num_batches: self.input_openings.len(),
+            num_matrices: self
+                .input_openings
+                .iter()
+                .map(|batch| batch.opened_widths().len())
+                .sum(),
+            num_queries: self.fri_proof.num_queries(),
+            num_commit_phase_rounds: self.fri_proof.num_commit_phase_rounds(),
+            opened_rows: self
+                .input_openings
+                .iter()
+                .flat_map(|batch| &batch.opened_values)
+                .map(Vec::len)
+                .sum(),
+            opened_values: self
+                .input_openings
+                .iter()
+                .flat_map(|batch| batch.opened_values.iter().flatten())
+                .map(Vec::len)
+                .sum(),
+            opened_siblings: self.fri_proof.query_opening_lens().sum(),
+            has_deep_quotient: self.deep_quotient.is_some(),
+        }
+    }
+}
+
+/// What a [`TwoAdicFriPcsProof`] contains, as returned by [`TwoAdicFriPcsProof::stats`].
+///
+/// The MMCS opening proofs are opaque to the PCS, so their size is not included here; see
+/// [`TwoAdicFriPcsProof::size_breakdown`] for sizes in bytes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProofStats {
+    /// The number of committed batches (rounds) opened.
+    pub num_batches: usize,
+    /// The number of matrices opened, across all batches.
+    pub num_matrices: usize,
+    pub num_queries: usize,
+    pub num_commit_phase_rounds: usize,
+    /// The number of input rows opened, across all batches and queries.
+    pub opened_rows: usize,
+    /// The number of input values opened, across all batches and queries.
+    pub opened_values: usize,
+    /// The number of commit phase sibling values opened, across all queries.
+    pub opened_siblings: usize,
+    pub has_deep_quotient: bool,
+}
+
+/// The serialized size of each part of a [`TwoAdicFriPcsProof`], in bytes, as returned by
+/// [`TwoAdicFriPcsProof::size_breakdown`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TwoAdicFriPcsProofSize {
+    pub fri: FriProofSize,
+    /// For each batch, its opened rows at every query together with their multi-proof.
+    pub input_openings: Vec<usize>,
+    /// The DEEP quotient commitment and openings, or zero if there is none.
+    pub deep_quotient: usize,
+}
+
+impl TwoAdicFriPcsProofSize {
+    pub fn total(&self) -> usize {
+        self.fri.total() + self.input_openings.iter().sum::<usize>() + self.deep_quotient
+    }
+}
+
+/// A summary of the proof's shape and size, with one line per input batch.
+impl<Val: Field, Challenge: Field, InputMmcs: Mmcs<Val>, FriMmcs: Mmcs<Challenge>> Display
+    for TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        let size = self.size_breakdown();
+        writeln!(f, "{}", self.fri_proof)?;
+        for (i, (batch, bytes)) in izip!(&self.input_openings, &size.input_openings).enumerate() {
+            writeln!(
+                f,
+                "batch {i}: {} queries, widths {:?}, {bytes} B of input openings",
+                batch.num_queries(),
+                batch.opened_widths()
+            )?;
+        }
+        if self.deep_quotient.is_some() {
+            writeln!(f, "committed DEEP quotient: {} B", size.deep_quotient)?;
+        }
+        writeln!(f, "total: {} B", size.total())
+    }
+}
+
+impl<Val: Field, InputMmcs: Mmcs<Val>> BatchMultiOpening<Val, InputMmcs> {
+    pub fn num_queries(&self) -> usize {
+        self.opened_values.len()
+    }
+
+    /// The widths of the opened matrices, taken from the first query.
+    pub fn opened_widths(&self) -> Vec<usize> {
+        self.opened_values
+            .first()
+            .map(|rows| rows.iter().map(Vec::len).collect())
+            .unwrap_or_default()
+    }
 }
 
+/// A commitment to the batched DEEP quotients, one single-column matrix per height in
+/// descending order, together with their openings at every query.
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(bound = "")]
-pub struct BatchOpening<Val: Field, InputMmcs: Mmcs<Val>> {
-    pub opened_values: Vec<Vec<Val>>,
-    pub opening_proof: <InputMmcs as Mmcs<Val>>::Proof,
+pub struct DeepQuotientOpening<Challenge: Field, FriMmcs: Mmcs<Challenge>> {
+    pub commit: FriMmcs::Commitment,
+    pub opening: BatchMultiOpening<Challenge, FriMmcs>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(bound = "")]
+pub struct BatchMultiOpening<Val: Field, InputMmcs: Mmcs<Val>> {
+    /// The opened rows, indexed by query and then by matrix.
+    pub opened_values: Vec<Vec<Vec<Val>>>,
+    pub opening_proof: <InputMmcs as Mmcs<Val>>::MultiProof,
 }
 
 pub struct TwoAdicFriGenericConfig<InputProof, InputError>(
     pub PhantomData<(InputProof, InputError)>,
 );
 
-pub type TwoAdicFriGenericConfigForMmcs<F, M> =
-    TwoAdicFriGenericConfig<Vec<BatchOpening<F, M>>, <M as Mmcs<F>>::Error>;
+/// The input openings are batched across queries and checked outside of FRI, so the per-query
+/// input proof is empty.
+pub type TwoAdicFriGenericConfigForMmcs<F, M> = TwoAdicFriGenericConfig<(), <M as Mmcs<F>>::Error>;
 
 impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
     for TwoAdicFriGenericConfig<InputProof, InputError>
@@ -129,77 +564,211 @@ impl<F: TwoAdicField, InputProof, InputError: Debug> FriGenericConfig<F>
     }
 }
 
-impl<Val, Dft, InputMmcs, FriMmcs, Challenge, Challenger> Pcs<Challenge, Challenger>
-    for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs>
+impl<Val, Dft, InputMmcs, FriMmcs, QuerySampler, Pow>
+    TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, QuerySampler, Pow>
 where
     Val: TwoAdicField,
-    Dft: TwoAdicSubgroupDft<Val>,
+    Dft: CosetLde<Val>,
     InputMmcs: Mmcs<Val>,
-    FriMmcs: Mmcs<Challenge>,
-    Challenge: TwoAdicField + ExtensionField<Val>,
-    Challenger:
-        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
 {
-    type Domain = TwoAdicMultiplicativeCoset<Val>;
-    type Commitment = InputMmcs::Commitment;
-    type ProverData = InputMmcs::ProverData<RowMajorMatrix<Val>>;
-    type Proof = FriProof<Challenge, FriMmcs, Val, Vec<BatchOpening<Val, InputMmcs>>>;
-    type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
-
-    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
-        let log_n = log2_strict_usize(degree);
-        TwoAdicMultiplicativeCoset {
-            log_n,
-            shift: Val::ONE,
-        }
+    /// Like `Pcs::open`, but also returns the reduced openings that FRI is run on.
+    ///
+    /// There is one reduced opening per distinct LDE height, tallest first. Each is
+    /// `sum_i alpha^i (p_i(X) - p_i(z)) / (X - z)` over every column `p_i` of that height and every
+    /// point `z` it is opened at, evaluated over the LDE coset in bit-reversed order. This lets
+    /// external tools check the reduction independently.
+    #[allow(clippy::type_complexity)]
+    pub fn open_with_reduced_openings<Challenge, Challenger>(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &TwoAdicFriProverData<Val, InputMmcs>,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (
+        OpenedValues<Challenge>,
+        TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
+        Vec<Vec<Challenge>>,
+    )
+    where
+        FriMmcs: Mmcs<Challenge> + Sync,
+        FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+        FriMmcs::Proof: Send,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+        QuerySampler: QueryIndexSampler<Challenger>,
+        Pow: PowStrategy<Challenger>,
+    {
+        let rounds = rounds
+            .into_iter()
+            .map(|(data, points)| (&self.verifier, data, points))
+            .collect();
+        let (opened_values, proof, reduced_openings) = self.open_inner(rounds, challenger, true);
+        (opened_values, proof, reduced_openings.unwrap())
     }
 
-    fn commit(
+    /// Like `Pcs::commit`, but accepting evaluations in any matrix layout, such as a
+    /// `ColumnMajorMatrix` or a matrix whose rows are generated on demand. Each matrix is copied
+    /// into the row-major form the DFT works on as it is committed, so callers don't need to
+    /// convert (and hold) a row-major copy beforehand.
+    ///
+    /// Each matrix is extended to `domain.size() << log_blowup` rows, so the blowup is applied per
+    /// matrix, relative to its own domain. A polynomial of higher degree than the trace, such as a
+    /// quotient, is committed alongside the trace by evaluating it over a larger domain, e.g. from
+    /// `create_disjoint_domain`, rather than by raising the blowup of every matrix.
+    #[allow(clippy::type_complexity)]
+    pub fn commit_matrices<M: Matrix<Val>>(
         &self,
-        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
-    ) -> (Self::Commitment, Self::ProverData) {
+        evaluations: Vec<(TwoAdicMultiplicativeCoset<Val>, M)>,
+    ) -> (InputMmcs::Commitment, TwoAdicFriProverData<Val, InputMmcs>) {
+        if self.verifier.natural_order_ldes {
+            assert!(
+                evaluations
+                    .iter()
+                    .map(|(domain, _)| domain.log_n)
+                    .all_equal(),
+                "LDEs committed in natural order must all have the same height"
+            );
+        }
+        let original_heights = evaluations
+            .iter()
+            .map(|(_, evals)| evals.height())
+            .collect();
         let ldes: Vec<_> = evaluations
             .into_iter()
             .map(|(domain, evals)| {
+                // Copy the rows into a buffer with room for the LDE, which the DFT can extend in
+                // place.
+                let mut evals = evals.to_row_major_matrix_with_capacity(
+                    domain.size() << self.verifier.fri.log_blowup,
+                );
+                if let Some(padding) = self.padding {
+                    padding.pad(&mut evals, domain.size());
+                }
                 assert_eq!(domain.size(), evals.height());
+                let log_lde_height = domain.log_n + self.verifier.fri.log_blowup;
+                assert!(
+                    log_lde_height <= Val::TWO_ADICITY,
+                    "an LDE of height 2^{log_lde_height} exceeds the field's two-adicity of {}",
+                    Val::TWO_ADICITY
+                );
+                // FRI can't roll an input shorter than its final codeword into the fold, except
+                // for a constant, whose quotients vanish.
+                let log_final_poly_len = self.verifier.fri.log_final_poly_len;
+                assert!(
+                    domain.log_n == 0 || domain.log_n >= log_final_poly_len,
+                    "a domain of size 2^{} is smaller than the final polynomial's \
+                     2^{log_final_poly_len} coefficients",
+                    domain.log_n
+                );
                 let shift = Val::GENERATOR / domain.shift;
-                // Commit to the bit-reversed LDE.
-                self.dft
-                    .coset_lde_batch(evals, self.fri.log_blowup, shift)
-                    .bit_reverse_rows()
-                    .to_row_major_matrix()
+                let lde = self
+                    .dft
+                    .coset_lde_batch(evals, self.verifier.fri.log_blowup, shift);
+                // Commit to the bit-reversed LDE, unless asked for natural order.
+                if self.verifier.natural_order_ldes {
+                    lde.to_row_major_matrix()
+                } else {
+                    lde.bit_reverse_rows().to_row_major_matrix()
+                }
             })
             .collect();
 
-        self.mmcs.commit(ldes)
+        let (commit, mmcs_data) = self.verifier.mmcs.commit(ldes);
+        let prover_data = TwoAdicFriProverData {
+            mmcs_data,
+            original_heights,
+        };
+        (commit, prover_data)
     }
 
-    fn get_evaluations_on_domain<'a>(
-        &self,
-        prover_data: &'a Self::ProverData,
-        idx: usize,
-        domain: Self::Domain,
-    ) -> impl Matrix<Val> + 'a {
-        // todo: handle extrapolation for LDEs we don't have
-        assert_eq!(domain.shift, Val::GENERATOR);
-        let lde = self.mmcs.get_matrices(prover_data)[idx];
-        assert!(lde.height() >= domain.size());
-        lde.split_rows(domain.size()).0.bit_reverse_rows()
+    /// Open rounds committed by several PCSs under a single FRI proof.
+    ///
+    /// Every PCS must share the same FRI parameters, and FRI is run with the FRI MMCS of the first
+    /// one. Rather than a commit phase, a grind and a query phase per PCS, there is one of each,
+    /// covering the reduced openings of every height across all groups. The opened values are
+    /// returned per group; the proof is checked by [`TwoAdicFriVerifier::verify_aggregated`].
+    #[allow(clippy::type_complexity)]
+    pub fn open_aggregated<Challenge, Challenger>(
+        // For each group, its PCS and the rounds to open, as in `Pcs::open`.
+        groups: Vec<(
+            &Self,
+            Vec<(&TwoAdicFriProverData<Val, InputMmcs>, Vec<Vec<Challenge>>)>,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (
+        Vec<OpenedValues<Challenge>>,
+        TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
+    )
+    where
+        FriMmcs: Mmcs<Challenge> + Sync,
+        FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+        FriMmcs::Proof: Send,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+        QuerySampler: QueryIndexSampler<Challenger>,
+        Pow: PowStrategy<Challenger>,
+    {
+        let first = groups.first().expect("nothing to aggregate").0;
+        let mut group_sizes = vec![];
+        let rounds = groups
+            .into_iter()
+            .flat_map(|(pcs, rounds)| {
+                first.verifier.assert_same_fri_params(&pcs.verifier);
+                group_sizes.push(rounds.len());
+                rounds
+                    .into_iter()
+                    .map(move |(data, points)| (&pcs.verifier, data, points))
+            })
+            .collect();
+
+        let (opened_values, proof, _) = first.open_inner(rounds, challenger, false);
+        let mut opened_values = opened_values.into_iter();
+        let opened_values = group_sizes
+            .into_iter()
+            .map(|size| opened_values.by_ref().take(size).collect())
+            .collect();
+        (opened_values, proof)
     }
 
-    fn open(
+    /// Each round carries the verifier of the PCS it was committed with, for its MMCS and LDE
+    /// ordering, which may be another PCS sharing this one's FRI parameters. The reduced openings
+    /// are only copied out if `keep_reduced_openings` is set.
+    #[allow(clippy::type_complexity)]
+    fn open_inner<Challenge, Challenger>(
         &self,
-        // For each round,
         rounds: Vec<(
-            &Self::ProverData,
-            // for each matrix,
-            Vec<
-                // points to open
-                Vec<Challenge>,
-            >,
+            &TwoAdicFriVerifier<Val, InputMmcs, FriMmcs, QuerySampler, Pow>,
+            &TwoAdicFriProverData<Val, InputMmcs>,
+            Vec<Vec<Challenge>>,
         )>,
         challenger: &mut Challenger,
-    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        keep_reduced_openings: bool,
+    ) -> (
+        OpenedValues<Challenge>,
+        TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
+        Option<Vec<Vec<Challenge>>>,
+    )
+    where
+        FriMmcs: Mmcs<Challenge> + Sync,
+        FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+        FriMmcs::Proof: Send,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+        QuerySampler: QueryIndexSampler<Challenger>,
+        Pow: PowStrategy<Challenger>,
+    {
         /*
 
         A quick rundown of the optimizations in this function:
@@ -242,10 +811,11 @@ where
 
         let mats_and_points = rounds
             .iter()
-            .map(|(data, points)| {
+            .map(|(verifier, data, points)| {
                 (
-                    self.mmcs
-                        .get_matrices(data)
+                    verifier
+                        .mmcs
+                        .get_matrices(&data.mmcs_data)
                         .into_iter()
                         .map(|m| m.as_view())
                         .collect_vec(),
@@ -258,6 +828,31 @@ where
             .flat_map(|(mats, _)| mats)
             .collect_vec();
 
+        if mats.is_empty() {
+            // Nothing was committed, so there is nothing for FRI to prove.
+            let input_openings = rounds
+                .iter()
+                .map(|(verifier, data, _)| {
+                    let (opened_values, opening_proof) =
+                        verifier.mmcs.open_multi_batch(&[], &data.mmcs_data);
+                    BatchMultiOpening {
+                        opened_values,
+                        opening_proof,
+                    }
+                })
+                .collect();
+            let fri_proof = FriProof::new(vec![], vec![], vec![], None);
+            return (
+                rounds.iter().map(|_| vec![]).collect(),
+                TwoAdicFriPcsProof {
+                    fri_proof,
+                    input_openings,
+                    deep_quotient: None,
+                },
+                keep_reduced_openings.then(Vec::new),
+            );
+        }
+
         let global_max_height = mats.iter().map(|m| m.height()).max().unwrap();
         let log_global_max_height = log2_strict_usize(global_max_height);
 
@@ -265,17 +860,27 @@ where
         // for that point, and precompute 1/(X - z) for the largest subgroup (in bitrev order).
         let inv_denoms = compute_inverse_denominators(&mats_and_points, Val::GENERATOR);
 
+        // The barycentric interpolation scalars only depend on the height and the point, so they
+        // are computed once and shared between matrices (and rounds) of equal height.
+        let interpolation_scalars = compute_interpolation_scalars(
+            &mats_and_points,
+            self.verifier.fri.log_blowup,
+            Val::GENERATOR,
+        );
+
         let mut all_opened_values: OpenedValues<Challenge> = vec![];
 
-        let mut reduced_openings: [_; 32] = core::array::from_fn(|_| None);
-        let mut num_reduced = [0; 32];
+        // log_height -> (num_reduced, reduced_opening), shared by every matrix of that height
+        // across all rounds.
+        let mut reduced_openings = BTreeMap::<usize, (usize, Vec<Challenge>)>::new();
 
-        for (mats, points) in mats_and_points {
+        for ((mats, points), (verifier, _, _)) in izip!(mats_and_points, &rounds) {
             let opened_values_for_round = all_opened_values.pushed_mut(vec![]);
             for (mat, points_for_mat) in izip!(mats, points) {
                 let log_height = log2_strict_usize(mat.height());
-                let reduced_opening_for_log_height = reduced_openings[log_height]
-                    .get_or_insert_with(|| vec![Challenge::ZERO; mat.height()]);
+                let (num_reduced, reduced_opening_for_log_height) = reduced_openings
+                    .entry(log_height)
+                    .or_insert_with(|| (0, vec![Challenge::ZERO; mat.height()]));
                 debug_assert_eq!(reduced_opening_for_log_height.len(), mat.height());
 
                 let opened_values_for_mat = opened_values_for_round.pushed_mut(vec![]);
@@ -286,57 +891,235 @@ where
                     // Use Barycentric interpolation to evaluate the matrix at the given point.
                     let ys = info_span!("compute opened values with Lagrange interpolation")
                         .in_scope(|| {
-                            let (low_coset, _) =
-                                mat.split_rows(mat.height() >> self.fri.log_blowup);
-                            interpolate_coset(
-                                &BitReversalPerm::new_view(low_coset),
-                                Val::GENERATOR,
-                                point,
-                            )
+                            let log_blowup = self.verifier.fri.log_blowup;
+                            let scalars = interpolation_scalars
+                                .get(&(log_height - log_blowup, point))
+                                .unwrap();
+                            // The low coset in natural order, which is a prefix of the
+                            // bit-reversed LDE or every `2^log_blowup`th row of the natural one.
+                            if verifier.natural_order_ldes {
+                                interpolate_coset_with_scalars(
+                                    &mat.as_view().vertically_strided(1 << log_blowup, 0),
+                                    Val::GENERATOR,
+                                    point,
+                                    scalars,
+                                )
+                            } else {
+                                let low_coset = mat.row_window(0..mat.height() >> log_blowup);
+                                interpolate_coset_with_scalars(
+                                    &BitReversalPerm::new_view(low_coset),
+                                    Val::GENERATOR,
+                                    point,
+                                    scalars,
+                                )
+                            }
                         });
 
-                    let alpha_pow_offset = alpha.exp_u64(num_reduced[log_height] as u64);
+                    let alpha_pow_offset = alpha.exp_u64(*num_reduced as u64);
                     let reduced_ys: Challenge = dot_product(alpha.powers(), ys.iter().copied());
 
                     info_span!("reduce rows").in_scope(|| {
-                        mat.dot_ext_powers(alpha)
-                            .zip(reduced_opening_for_log_height.par_iter_mut())
-                            // This might be longer, but zip will truncate to smaller subgroup
-                            // (which is ok because it's bitrev)
-                            .zip(inv_denoms.get(&point).unwrap().par_iter())
-                            .for_each(|((reduced_row, ro), &inv_denom)| {
-                                *ro += alpha_pow_offset * (reduced_row - reduced_ys) * inv_denom
-                            })
+                        // This might be longer, but zip will truncate to smaller subgroup
+                        // (which is ok because it's bitrev)
+                        let inv_denoms = inv_denoms.get(&point).unwrap().par_iter();
+                        let natural_order_ldes = verifier.natural_order_ldes;
+                        reduced_opening_for_log_height
+                            .par_iter_mut()
+                            .zip(inv_denoms)
+                            .enumerate()
+                            .for_each(|(r, (ro, &inv_denom))| {
+                                // The reduced openings are always in bit-reversed order.
+                                let r = if natural_order_ldes {
+                                    reverse_bits_len(r, log_height)
+                                } else {
+                                    r
+                                };
+                                *ro += alpha_pow_offset
+                                    * reduce_row_at_query(
+                                        alpha,
+                                        &mat.row_slice(r),
+                                        reduced_ys,
+                                        inv_denom,
+                                    );
+                            });
                     });
 
-                    num_reduced[log_height] += mat.width();
+                    *num_reduced += mat.width();
                     opened_values_for_mat.push(ys);
                 }
             }
         }
 
-        let fri_input = reduced_openings.into_iter().rev().flatten().collect_vec();
+        // FRI expects its inputs tallest first.
+        let fri_input = reduced_openings
+            .into_values()
+            .rev()
+            .map(|(_, ro)| ro)
+            .collect_vec();
+        let reduced_openings = keep_reduced_openings.then(|| fri_input.clone());
+
+        let deep_quotient_data = self.verifier.commit_deep_quotient.then(|| {
+            let quotient_mats = fri_input
+                .iter()
+                .map(|ro| RowMajorMatrix::new_col(ro.clone()))
+                .collect_vec();
+            let (commit, data) = self.verifier.fri.mmcs.commit(quotient_mats);
+            challenger.observe(commit.clone());
+            (commit, data)
+        });
 
         let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
             TwoAdicFriGenericConfig(PhantomData);
 
-        let fri_proof = prover::prove(&g, &self.fri, fri_input, challenger, |index| {
-            rounds
-                .iter()
-                .map(|(data, _)| {
-                    let log_max_height = log2_strict_usize(self.mmcs.get_max_height(data));
-                    let bits_reduced = log_global_max_height - log_max_height;
-                    let reduced_index = index >> bits_reduced;
-                    let (opened_values, opening_proof) = self.mmcs.open_batch(reduced_index, data);
-                    BatchOpening {
-                        opened_values,
-                        opening_proof,
-                    }
-                })
-                .collect()
+        // Record the query indices so that each round can be opened with a single multi-proof.
+        let query_indices = RefCell::new(vec![]);
+        let fri_proof = prover::prove(
+            &g,
+            &self.verifier.fri,
+            &self.verifier.query_sampler,
+            &self.verifier.pow,
+            fri_input,
+            challenger,
+            |index| {
+                query_indices.borrow_mut().push(index);
+            },
+        );
+        let query_indices = query_indices.into_inner();
+
+        let input_openings = rounds
+            .iter()
+            .map(|(verifier, data, _)| {
+                // An empty batch has max height 0, and any index will do for it.
+                let log_max_height = log2_ceil_usize(verifier.mmcs.get_max_height(&data.mmcs_data));
+                let bits_reduced = log_global_max_height - log_max_height;
+                let reduced_indices = query_indices
+                    .iter()
+                    .map(|&index| verifier.input_row_index(index >> bits_reduced, log_max_height))
+                    .collect_vec();
+                let (opened_values, opening_proof) = verifier
+                    .mmcs
+                    .open_multi_batch(&reduced_indices, &data.mmcs_data);
+                BatchMultiOpening {
+                    opened_values,
+                    opening_proof,
+                }
+            })
+            .collect();
+
+        // The tallest quotient has the global max height, so the query indices are used as is.
+        let deep_quotient = deep_quotient_data.map(|(commit, data)| {
+            let (opened_values, opening_proof) = self
+                .verifier
+                .fri
+                .mmcs
+                .open_multi_batch(&query_indices, &data);
+            DeepQuotientOpening {
+                commit,
+                opening: BatchMultiOpening {
+                    opened_values,
+                    opening_proof,
+                },
+            }
         });
 
-        (all_opened_values, fri_proof)
+        (
+            all_opened_values,
+            TwoAdicFriPcsProof {
+                fri_proof,
+                input_openings,
+                deep_quotient,
+            },
+            reduced_openings,
+        )
+    }
+}
+
+impl<Val, Dft, InputMmcs, FriMmcs, QuerySampler, Pow, Challenge, Challenger>
+    Pcs<Challenge, Challenger> for TwoAdicFriPcs<Val, Dft, InputMmcs, FriMmcs, QuerySampler, Pow>
+where
+    Val: TwoAdicField,
+    Dft: CosetLde<Val>,
+    InputMmcs: Mmcs<Val>,
+    FriMmcs: Mmcs<Challenge> + Sync,
+    FriMmcs::ProverData<RowMajorMatrix<Challenge>>: Sync,
+    FriMmcs::Proof: Send,
+    Challenge: TwoAdicField + ExtensionField<Val>,
+    Challenger:
+        FieldChallenger<Val> + CanObserve<FriMmcs::Commitment> + GrindingChallenger<Witness = Val>,
+    QuerySampler: QueryIndexSampler<Challenger>,
+    Pow: PowStrategy<Challenger>,
+{
+    type Domain = TwoAdicMultiplicativeCoset<Val>;
+    type Commitment = InputMmcs::Commitment;
+    type ProverData = TwoAdicFriProverData<Val, InputMmcs>;
+    type Proof = TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>;
+    type Error = FriError<FriMmcs::Error, InputMmcs::Error>;
+
+    fn natural_domain_for_degree(&self, degree: usize) -> Self::Domain {
+        let log_n = if self.padding.is_some() {
+            log2_ceil_usize(degree)
+        } else {
+            log2_strict_usize(degree)
+        };
+        TwoAdicMultiplicativeCoset {
+            log_n,
+            shift: Val::ONE,
+        }
+    }
+
+    fn commit(
+        &self,
+        evaluations: Vec<(Self::Domain, RowMajorMatrix<Val>)>,
+    ) -> (Self::Commitment, Self::ProverData) {
+        self.commit_matrices(evaluations)
+    }
+
+    fn get_evaluations_on_domain<'a>(
+        &self,
+        prover_data: &'a Self::ProverData,
+        idx: usize,
+        domain: Self::Domain,
+    ) -> impl Matrix<Val> + 'a {
+        // todo: handle extrapolation for LDEs we don't have
+        assert_eq!(domain.shift, Val::GENERATOR);
+        let lde = self.verifier.mmcs.get_matrices(&prover_data.mmcs_data)[idx];
+        assert!(lde.height() >= domain.size());
+        if self.verifier.natural_order_ldes {
+            let view = lde
+                .as_view()
+                .vertically_strided(lde.height() / domain.size(), 0);
+            RowIndexMappedView {
+                index_map: DomainRows::Strided(view.index_map),
+                inner: view.inner,
+            }
+        } else {
+            let view = lde.row_window(0..domain.size()).bit_reverse_rows();
+            RowIndexMappedView {
+                index_map: DomainRows::BitReversed(view.index_map),
+                inner: view.inner,
+            }
+        }
+    }
+
+    fn open(
+        &self,
+        // For each round,
+        rounds: Vec<(
+            &Self::ProverData,
+            // for each matrix,
+            Vec<
+                // points to open
+                Vec<Challenge>,
+            >,
+        )>,
+        challenger: &mut Challenger,
+    ) -> (OpenedValues<Challenge>, Self::Proof) {
+        let rounds = rounds
+            .into_iter()
+            .map(|(data, points)| (&self.verifier, data, points))
+            .collect();
+        let (opened_values, proof, _) = self.open_inner(rounds, challenger, false);
+        (opened_values, proof)
     }
 
     fn verify(
@@ -360,45 +1143,306 @@ where
         proof: &Self::Proof,
         challenger: &mut Challenger,
     ) -> Result<(), Self::Error> {
+        self.verifier.verify(rounds, proof, challenger)
+    }
+}
+
+impl<Val, InputMmcs, FriMmcs, QuerySampler, Pow>
+    TwoAdicFriVerifier<Val, InputMmcs, FriMmcs, QuerySampler, Pow>
+where
+    Val: TwoAdicField,
+    InputMmcs: Mmcs<Val>,
+{
+    /// Verify openings produced by `TwoAdicFriPcs::open` with the same MMCS and FRI config.
+    #[allow(clippy::type_complexity)]
+    pub fn verify<Challenge, Challenger>(
+        &self,
+        // For each round:
+        rounds: Vec<(
+            InputMmcs::Commitment,
+            // for each matrix:
+            Vec<(
+                // its domain,
+                TwoAdicMultiplicativeCoset<Val>,
+                // for each point:
+                Vec<(
+                    // the point,
+                    Challenge,
+                    // values at the point
+                    Vec<Challenge>,
+                )>,
+            )>,
+        )>,
+        proof: &TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
+        challenger: &mut Challenger,
+    ) -> Result<(), FriError<FriMmcs::Error, InputMmcs::Error>>
+    where
+        FriMmcs: Mmcs<Challenge>,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+        QuerySampler: QueryIndexSampler<Challenger>,
+        Pow: PowStrategy<Challenger>,
+    {
+        let rounds = rounds
+            .into_iter()
+            .map(|(commit, mats)| (self, commit, mats))
+            .collect();
+        self.verify_inner(rounds, proof, challenger)
+    }
+
+    /// Verify a proof produced by `TwoAdicFriPcs::open_aggregated`, given the verifier of each
+    /// group's PCS along with that group's claims, in the order they were opened.
+    #[allow(clippy::type_complexity)]
+    pub fn verify_aggregated<Challenge, Challenger>(
+        // For each group, its verifier and the rounds to verify, as in `verify`.
+        groups: Vec<(
+            &Self,
+            Vec<(
+                InputMmcs::Commitment,
+                Vec<(
+                    TwoAdicMultiplicativeCoset<Val>,
+                    Vec<(Challenge, Vec<Challenge>)>,
+                )>,
+            )>,
+        )>,
+        proof: &TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
+        challenger: &mut Challenger,
+    ) -> Result<(), FriError<FriMmcs::Error, InputMmcs::Error>>
+    where
+        FriMmcs: Mmcs<Challenge>,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+        QuerySampler: QueryIndexSampler<Challenger>,
+        Pow: PowStrategy<Challenger>,
+    {
+        let first = groups.first().expect("nothing to aggregate").0;
+        let rounds = groups
+            .into_iter()
+            .flat_map(|(verifier, rounds)| {
+                first.assert_same_fri_params(verifier);
+                rounds
+                    .into_iter()
+                    .map(move |(commit, mats)| (verifier, commit, mats))
+            })
+            .collect();
+        first.verify_inner(rounds, proof, challenger)
+    }
+
+    /// Each round carries the verifier it was committed for, as in `TwoAdicFriPcs::open_inner`.
+    #[allow(clippy::type_complexity)]
+    fn verify_inner<Challenge, Challenger>(
+        &self,
+        rounds: Vec<(
+            &Self,
+            InputMmcs::Commitment,
+            Vec<(
+                TwoAdicMultiplicativeCoset<Val>,
+                Vec<(Challenge, Vec<Challenge>)>,
+            )>,
+        )>,
+        proof: &TwoAdicFriPcsProof<Val, Challenge, InputMmcs, FriMmcs>,
+        challenger: &mut Challenger,
+    ) -> Result<(), FriError<FriMmcs::Error, InputMmcs::Error>>
+    where
+        FriMmcs: Mmcs<Challenge>,
+        Challenge: TwoAdicField + ExtensionField<Val>,
+        Challenger: FieldChallenger<Val>
+            + CanObserve<FriMmcs::Commitment>
+            + GrindingChallenger<Witness = Val>,
+        QuerySampler: QueryIndexSampler<Challenger>,
+        Pow: PowStrategy<Challenger>,
+    {
         // Batch combination challenge
         let alpha: Challenge = challenger.sample_ext_element();
 
-        let log_global_max_height = proof.commit_phase_commits.len() + self.fri.log_blowup;
+        let fri_proof = &proof.fri_proof;
+        let log_global_max_height =
+            fri_proof.num_commit_phase_rounds() + self.fri.log_blowup + self.fri.log_final_poly_len;
+
+        if rounds.iter().all(|(_, _, mats)| mats.is_empty()) {
+            // Nothing was committed, so the proof must be empty too.
+            if proof.input_openings.len() != rounds.len() {
+                return Err(FriError::InvalidProofShape(ProofShapeError::NumBatches {
+                    expected: rounds.len(),
+                    got: proof.input_openings.len(),
+                }));
+            }
+            if !fri_proof.commit_phase_commits().is_empty()
+                || !fri_proof.query_proofs().is_empty()
+                || !fri_proof.final_poly().is_empty()
+                || fri_proof.pow_witness().is_some()
+                || proof.deep_quotient.is_some()
+            {
+                return Err(FriError::InvalidProofShape(ProofShapeError::NotEmpty));
+            }
+            for (batch, (batch_opening, (verifier, batch_commit, _))) in
+                izip!(&proof.input_openings, &rounds).enumerate()
+            {
+                verifier
+                    .mmcs
+                    .verify_multi_batch(
+                        batch_commit,
+                        &[],
+                        &[],
+                        &batch_opening.opened_values,
+                        &batch_opening.opening_proof,
+                    )
+                    .map_err(|source| FriError::InputBatchError { batch, source })?;
+            }
+            return Ok(());
+        }
 
-        let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
-            TwoAdicFriGenericConfig(PhantomData);
+        // Check the claimed domains against the field and the proof before computing anything
+        // from their heights.
+        let mut claimed_log_max_height = 0;
+        for (batch, (_, _, mats)) in rounds.iter().enumerate() {
+            for (matrix, (domain, _)) in mats.iter().enumerate() {
+                let log_height = domain.log_n.saturating_add(self.fri.log_blowup);
+                if log_height > Val::TWO_ADICITY {
+                    return Err(FriError::LogHeightTooLarge {
+                        batch,
+                        matrix,
+                        log_height,
+                    });
+                }
+                if domain.log_n != 0 && domain.log_n < self.fri.log_final_poly_len {
+                    return Err(FriError::LogHeightBelowFinalHeight {
+                        batch,
+                        matrix,
+                        log_height,
+                    });
+                }
+                claimed_log_max_height = claimed_log_max_height.max(log_height);
+            }
+        }
+        if claimed_log_max_height != log_global_max_height {
+            return Err(FriError::LogMaxHeightMismatch {
+                claimed: claimed_log_max_height,
+                proof: log_global_max_height,
+            });
+        }
 
-        verifier::verify(&g, &self.fri, proof, challenger, |index, input_proof| {
-            // TODO: separate this out into functions
+        // Check that the input openings have one entry per round, per query and per matrix.
+        if proof.input_openings.len() != rounds.len() {
+            return Err(FriError::InvalidProofShape(ProofShapeError::NumBatches {
+                expected: rounds.len(),
+                got: proof.input_openings.len(),
+            }));
+        }
+        for (batch, (batch_opening, (_, _, mats))) in
+            izip!(&proof.input_openings, &rounds).enumerate()
+        {
+            if batch_opening.opened_values.len() != self.fri.num_queries {
+                return Err(FriError::InvalidProofShape(ProofShapeError::BatchQueries {
+                    batch,
+                    expected: self.fri.num_queries,
+                    got: batch_opening.opened_values.len(),
+                }));
+            }
+            for (query, opened_values) in batch_opening.opened_values.iter().enumerate() {
+                if opened_values.len() != mats.len() {
+                    return Err(FriError::InvalidProofShape(
+                        ProofShapeError::BatchMatrices {
+                            batch,
+                            query,
+                            expected: mats.len(),
+                            got: opened_values.len(),
+                        },
+                    ));
+                }
+            }
+        }
 
-            // log_height -> (alpha_pow, reduced_opening)
-            let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
+        // Each opened row must have one value per column claimed at every point.
+        for (batch, (batch_opening, (_, _, mats))) in
+            izip!(&proof.input_openings, &rounds).enumerate()
+        {
+            for (query, opened_values) in batch_opening.opened_values.iter().enumerate() {
+                for (matrix, (row, (_, points_and_values))) in
+                    izip!(opened_values, mats).enumerate()
+                {
+                    for (_, values) in points_and_values {
+                        if row.len() != values.len() {
+                            return Err(FriError::OpenedWidthMismatch {
+                                batch,
+                                matrix,
+                                query,
+                                expected: values.len(),
+                                got: row.len(),
+                            });
+                        }
+                    }
+                }
+            }
+        }
 
-            for (batch_opening, (batch_commit, mats)) in izip!(input_proof, &rounds) {
-                let batch_heights = mats
-                    .iter()
+        // One single-column quotient per distinct height, tallest first.
+        let quotient_dims = rounds
+            .iter()
+            .flat_map(|(_, _, mats)| {
+                mats.iter()
                     .map(|(domain, _)| domain.size() << self.fri.log_blowup)
-                    .collect_vec();
-                let batch_dims = batch_heights
-                    .iter()
+            })
+            .sorted_by(|a, b| b.cmp(a))
+            .dedup()
+            .map(|height| Dimensions { width: 1, height })
+            .collect_vec();
+        match (&proof.deep_quotient, self.commit_deep_quotient) {
+            (Some(deep_quotient), true) => {
+                let opened_values = &deep_quotient.opening.opened_values;
+                if opened_values.len() != self.fri.num_queries {
+                    return Err(FriError::InvalidProofShape(
+                        ProofShapeError::DeepQuotientQueries {
+                            expected: self.fri.num_queries,
+                            got: opened_values.len(),
+                        },
+                    ));
+                }
+                if let Some(query) = opened_values.iter().position(|quotients| {
+                    quotients.len() != quotient_dims.len() || quotients.iter().any(|q| q.len() != 1)
+                }) {
+                    return Err(FriError::InvalidProofShape(
+                        ProofShapeError::DeepQuotientOpening { query },
+                    ));
+                }
+                challenger.observe(deep_quotient.commit.clone());
+            }
+            (None, false) => {}
+            (_, expected) => {
+                return Err(FriError::InvalidProofShape(
+                    ProofShapeError::DeepQuotientPresence { expected },
+                ))
+            }
+        }
+
+        let batch_dims = rounds
+            .iter()
+            .map(|(_, _, mats)| {
+                mats.iter()
                     // TODO: MMCS doesn't really need width; we put 0 for now.
-                    .map(|&height| Dimensions { width: 0, height })
-                    .collect_vec();
+                    .map(|(domain, _)| Dimensions {
+                        width: 0,
+                        height: domain.size() << self.fri.log_blowup,
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
 
-                let batch_max_height = batch_heights.iter().max().expect("Empty batch?");
-                let log_batch_max_height = log2_strict_usize(*batch_max_height);
-                let bits_reduced = log_global_max_height - log_batch_max_height;
-                let reduced_index = index >> bits_reduced;
-
-                self.mmcs.verify_batch(
-                    batch_commit,
-                    &batch_dims,
-                    reduced_index,
-                    &batch_opening.opened_values,
-                    &batch_opening.opening_proof,
-                )?;
+        let g: TwoAdicFriGenericConfigForMmcs<Val, InputMmcs> =
+            TwoAdicFriGenericConfig(PhantomData);
+
+        // For one query, the reduced opening of every height, computed from the input openings.
+        let reduce_input_openings = |query: usize, index: usize| {
+            // log_height -> (alpha_pow, reduced_opening)
+            let mut reduced_openings = BTreeMap::<usize, (Challenge, Challenge)>::new();
+
+            for (batch_opening, (_, _, mats)) in izip!(&proof.input_openings, &rounds) {
                 for (mat_opening, (mat_domain, mat_points_and_values)) in
-                    izip!(&batch_opening.opened_values, mats)
+                    izip!(&batch_opening.opened_values[query], mats)
                 {
                     let log_height = log2_strict_usize(mat_domain.size()) + self.fri.log_blowup;
 
@@ -415,33 +1459,228 @@ where
                         .or_insert((Challenge::ONE, Challenge::ZERO));
 
                     for (z, ps_at_z) in mat_points_and_values {
-                        for (&p_at_x, &p_at_z) in izip!(mat_opening, ps_at_z) {
-                            let quotient = (-p_at_z + p_at_x) / (-*z + x);
-                            *ro += *alpha_pow * quotient;
-                            *alpha_pow *= alpha;
-                        }
+                        *ro += *alpha_pow * reduce_at_query(alpha, mat_opening, ps_at_z, x, *z);
+                        *alpha_pow *= alpha.exp_u64(ps_at_z.len() as u64);
                     }
                 }
             }
 
-            // `reduced_openings` would have a log_height = log_blowup entry only if there was a
-            // trace matrix of height 1. In this case the reduced opening can be skipped as it will
-            // not be checked against any commit phase commit.
-            if let Some((_alpha_pow, ro)) = reduced_openings.remove(&self.fri.log_blowup) {
-                debug_assert!(ro.is_zero());
-            }
-
-            // Return reduced openings descending by log_height.
-            Ok(reduced_openings
+            reduced_openings
                 .into_iter()
-                .rev()
                 .map(|(log_height, (_alpha_pow, ro))| (log_height, ro))
-                .collect())
-        })
-        .expect("fri err");
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let challenges = verifier::verify_shape_and_sample_challenges(
+            &g,
+            &self.fri,
+            &self.query_sampler,
+            &self.pow,
+            fri_proof,
+            challenger,
+        )?;
+        let query_indices = &challenges.query_indices;
+
+        let next_query = Cell::new(0);
+        let fri_result =
+            verifier::verify_challenges(&g, &self.fri, fri_proof, &challenges, |index, _| {
+                let query = next_query.replace(next_query.get() + 1);
+
+                let mut reduced_openings = match &proof.deep_quotient {
+                    // FRI runs on the committed quotients, without touching the input openings.
+                    // They are checked against each other once FRI is done.
+                    Some(deep_quotient) => {
+                        izip!(&quotient_dims, &deep_quotient.opening.opened_values[query])
+                            .map(|(dims, quotient)| (log2_strict_usize(dims.height), quotient[0]))
+                            .collect()
+                    }
+                    None => reduce_input_openings(query, index),
+                };
+
+                // `reduced_openings` would have a log_height = log_blowup entry only if there was a
+                // trace matrix of height 1. In this case the reduced opening can be skipped as it will
+                // not be checked against any commit phase commit.
+                if let Some(ro) = reduced_openings.remove(&self.fri.log_blowup) {
+                    debug_assert!(proof.deep_quotient.is_some() || ro.is_zero());
+                }
+
+                // Return reduced openings descending by log_height.
+                Ok(reduced_openings.into_iter().rev().collect())
+            });
+
+        let fri_result = fri_result.and_then(|()| {
+            let Some(deep_quotient) = &proof.deep_quotient else {
+                return Ok(());
+            };
+            let opened_values = &deep_quotient.opening.opened_values;
+            self.fri
+                .mmcs
+                .verify_multi_batch(
+                    &deep_quotient.commit,
+                    &quotient_dims,
+                    query_indices,
+                    opened_values,
+                    &deep_quotient.opening.opening_proof,
+                )
+                .map_err(FriError::DeepQuotientMmcsError)?;
+
+            // This is the only place the input openings are reduced, and it is independent of
+            // FRI, so a recursive verifier may defer it.
+            for (query, (&index, quotients)) in izip!(query_indices, opened_values).enumerate() {
+                let reduced_openings = reduce_input_openings(query, index);
+                for (dims, quotient) in izip!(&quotient_dims, quotients) {
+                    let log_height = log2_strict_usize(dims.height);
+                    if reduced_openings.get(&log_height) != Some(&quotient[0]) {
+                        return Err(FriError::DeepQuotientMismatch { query, log_height });
+                    }
+                }
+            }
+            Ok(())
+        });
+
+        let input_result = izip!(&proof.input_openings, &rounds, &batch_dims)
+            .enumerate()
+            .try_for_each(
+                |(batch, (batch_opening, (verifier, batch_commit, _), batch_dims))| {
+                    // An empty batch has max height 0, and any index will do for it.
+                    let batch_max_height =
+                        batch_dims.iter().map(|dims| dims.height).max().unwrap_or(0);
+                    let log_batch_max_height = log2_ceil_usize(batch_max_height);
+                    let bits_reduced = log_global_max_height - log_batch_max_height;
+                    let reduced_indices = query_indices
+                        .iter()
+                        .map(|&index| {
+                            verifier.input_row_index(index >> bits_reduced, log_batch_max_height)
+                        })
+                        .collect_vec();
+
+                    verifier
+                        .mmcs
+                        .verify_multi_batch(
+                            batch_commit,
+                            batch_dims,
+                            &reduced_indices,
+                            &batch_opening.opened_values,
+                            &batch_opening.opening_proof,
+                        )
+                        .map_err(|source| (batch, source))
+                },
+            );
+
+        // A batch's multi-proof covers all of its queries at once, so its failure alone doesn't
+        // say which query's openings are wrong. Wrong openings also throw off that query's check
+        // in FRI or against the DEEP quotient, though, which pins them down.
+        match (fri_result, input_result) {
+            (Ok(()), Ok(())) => Ok(()),
+            (Err(err), Ok(())) => Err(err),
+            (Ok(()), Err((batch, source))) => Err(FriError::InputBatchError { batch, source }),
+            (Err(err), Err((batch, source))) => Err(match err.query() {
+                Some(query) => FriError::InputMmcsError {
+                    query,
+                    batch,
+                    source,
+                },
+                None => FriError::InputBatchError { batch, source },
+            }),
+        }
+    }
+}
 
-        Ok(())
+/// The rows of a committed LDE which lie in a domain it extends, in the domain's natural order:
+/// a prefix of the LDE bit-reversed, or a strided subset of it in natural order.
+enum DomainRows {
+    BitReversed(BitReversalPerm),
+    Strided(VerticallyStridedRowIndexMap),
+}
+
+impl RowIndexMap for DomainRows {
+    fn height(&self) -> usize {
+        match self {
+            Self::BitReversed(perm) => perm.height(),
+            Self::Strided(strided) => strided.height(),
+        }
     }
+
+    fn map_row_index(&self, r: usize) -> usize {
+        match self {
+            Self::BitReversed(perm) => perm.map_row_index(r),
+            Self::Strided(strided) => strided.map_row_index(r),
+        }
+    }
+
+    fn to_row_major_matrix<T: Clone + Send + Sync, Inner: Matrix<T>>(
+        &self,
+        inner: Inner,
+    ) -> RowMajorMatrix<T> {
+        match self {
+            Self::BitReversed(perm) => perm.to_row_major_matrix(inner),
+            Self::Strided(strided) => strided.to_row_major_matrix(inner),
+        }
+    }
+}
+
+/// One matrix's contribution to a reduced opening at the query point `x`, when opened at `z`:
+/// `sum_i alpha^i (p_i(x) - p_i(z)) / (x - z)`, where `opened_row` holds the `p_i(x)` and
+/// `opened_values` the `p_i(z)`. Callers scale it by the power of `alpha` used by the matrices
+/// and points reduced before it.
+///
+/// The verifier calls this at each query, and the prover reduces every row through the same
+/// `reduce_row_at_query`, with the opened values reduced and `1 / (x - z)` batch inverted once.
+pub fn reduce_at_query<F: Field, EF: ExtensionField<F>>(
+    alpha: EF,
+    opened_row: &[F],
+    opened_values: &[EF],
+    x: F,
+    z: EF,
+) -> EF {
+    let reduced_ys: EF = dot_product(alpha.powers(), opened_values.iter().copied());
+    reduce_row_at_query(
+        alpha,
+        opened_row,
+        reduced_ys,
+        (EF::from_base(x) - z).inverse(),
+    )
+}
+
+/// `(p(x) - p(z)) / (x - z)` for the random linear combination `p` of columns, given the opened
+/// row, `p(z)` and `1 / (x - z)`.
+#[inline]
+fn reduce_row_at_query<F: Field, EF: ExtensionField<F>>(
+    alpha: EF,
+    opened_row: &[F],
+    reduced_ys: EF,
+    inv_denom: EF,
+) -> EF {
+    let reduced_row: EF = dot_product(alpha.powers(), opened_row.iter().copied());
+    (reduced_row - reduced_ys) * inv_denom
+}
+
+/// For each distinct pair of a (pre-LDE) log height and a point opened at that height, compute the
+/// scalars used to interpolate a matrix of that height at that point.
+#[instrument(skip_all)]
+fn compute_interpolation_scalars<F: TwoAdicField, EF: ExtensionField<F>, M: Matrix<F>>(
+    mats_and_points: &[(Vec<M>, &Vec<Vec<EF>>)],
+    log_blowup: usize,
+    coset_shift: F,
+) -> LinearMap<(usize, EF), Vec<EF>> {
+    // The subgroup's share of the weights doesn't depend on the point, so it is computed once per
+    // height.
+    let mut weights = LinearMap::new();
+    let mut scalars = LinearMap::new();
+    for (mats, points) in mats_and_points {
+        for (mat, points_for_mat) in izip!(mats, *points) {
+            let log_height = log2_strict_usize(mat.height()) - log_blowup;
+            for &z in points_for_mat {
+                scalars.get_or_insert_with((log_height, z), || {
+                    let weights = weights.get_or_insert_with(log_height, || {
+                        subgroup_barycentric_weights(log_height)
+                    });
+                    coset_interpolation_scalars_with_weights(weights, coset_shift, z)
+                });
+            }
+        }
+    }
+    scalars
 }
 
 #[instrument(skip_all)]
@@ -488,3 +1727,87 @@ fn compute_inverse_denominators<F: TwoAdicField, EF: ExtensionField<F>, M: Matri
         })
         .collect()
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use itertools::{izip, Itertools};
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::{AbstractExtensionField, AbstractField, Field, TwoAdicField};
+    use p3_matrix::dense::RowMajorMatrix;
+    use rand::{thread_rng, Rng};
+
+    use super::{compute_interpolation_scalars, reduce_at_query};
+
+    type F = BabyBear;
+    type EF = BinomialExtensionField<F, 4>;
+
+    #[test]
+    fn interpolation_scalars_shared_between_batches() {
+        let log_blowup = 1;
+        let zeta = EF::from_canonical_u32(1234);
+        let zeta_next = zeta * F::two_adic_generator(4);
+        let points = vec![vec![zeta, zeta_next]];
+
+        // Three batches, each holding one matrix of the same height, opened at the same points.
+        let batches = (0..3)
+            .map(|_| vec![RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << 5, 3)])
+            .collect_vec();
+        let mats_and_points = batches
+            .iter()
+            .map(|mats| (mats.iter().map(|m| m.as_view()).collect_vec(), &points))
+            .collect_vec();
+
+        let scalars = compute_interpolation_scalars(&mats_and_points, log_blowup, F::GENERATOR);
+        assert_eq!(scalars.values().count(), 2);
+        assert!(scalars.values().all(|s| s.len() == 1 << 4));
+    }
+
+    #[test]
+    fn reduce_at_query_small_inputs() {
+        let alpha = EF::from_canonical_u32(3);
+        let x = F::from_canonical_u32(5);
+        let z = EF::from_canonical_u32(2);
+        let row = [7, 11].map(F::from_canonical_u32);
+        let values = [4, 8].map(EF::from_canonical_u32);
+
+        // (7 - 4) / (5 - 2) + 3 * (11 - 8) / (5 - 2) = 1 + 3.
+        assert_eq!(
+            reduce_at_query(alpha, &row, &values, x, z),
+            EF::from_canonical_u32(4)
+        );
+        assert_eq!(reduce_at_query(alpha, &[], &[], x, z), EF::ZERO);
+        // A column that agrees at `x` and `z` contributes nothing.
+        assert_eq!(
+            reduce_at_query(alpha, &row[..1], &[EF::from_base(row[0])], x, z),
+            EF::ZERO
+        );
+    }
+
+    #[test]
+    fn reduce_at_query_splits_across_columns() {
+        let mut rng = thread_rng();
+        let alpha: EF = rng.gen();
+        let x: F = rng.gen();
+        let z: EF = rng.gen();
+        let row: Vec<F> = (0..5).map(|_| rng.gen()).collect();
+        let values: Vec<EF> = (0..5).map(|_| rng.gen()).collect();
+
+        let expected = izip!(&row, &values, alpha.powers())
+            .map(|(&p_x, &p_z, alpha_pow)| alpha_pow * (-p_z + p_x) / (EF::from_base(x) - z))
+            .sum::<EF>();
+        assert_eq!(reduce_at_query(alpha, &row, &values, x, z), expected);
+
+        // Reducing the columns in two parts, offsetting the second by the first's width, agrees.
+        let (row_lo, row_hi) = row.split_at(2);
+        let (values_lo, values_hi) = values.split_at(2);
+        assert_eq!(
+            reduce_at_query(alpha, row_lo, values_lo, x, z)
+                + alpha.exp_u64(2) * reduce_at_query(alpha, row_hi, values_hi, x, z),
+            expected
+        );
+    }
+}
@@ -1,82 +1,527 @@
 use alloc::vec;
 use alloc::vec::Vec;
+use core::fmt::{Debug, Display, Formatter};
 
 use itertools::{izip, Itertools};
 use p3_challenger::{CanObserve, FieldChallenger, GrindingChallenger};
 use p3_commit::Mmcs;
-use p3_field::{ExtensionField, Field};
+use p3_field::{ExtensionField, Field, TwoAdicField};
 use p3_matrix::Dimensions;
+use p3_util::reverse_bits_len;
 
-use crate::{CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof};
+use crate::prover::eval_poly;
+use crate::{
+    CommitPhaseProofStep, FriConfig, FriGenericConfig, FriProof, PowStrategy, QueryIndexSampler,
+};
 
+/// An error from verifying a FRI proof. Where possible, variants identify the query (in the order
+/// they were sampled), commit phase round or input batch at which verification failed.
 #[derive(Debug)]
 pub enum FriError<CommitMmcsErr, InputError> {
-    InvalidProofShape,
-    CommitPhaseMmcsError(CommitMmcsErr),
-    InputError(InputError),
-    FinalPolyMismatch,
+    InvalidProofShape(ProofShapeError),
+    /// A commit phase opening didn't verify against the round's commitment. `log_height` is the
+    /// log size of the codeword committed to in that round.
+    CommitPhaseMmcsError {
+        query: usize,
+        round: usize,
+        log_height: usize,
+        source: CommitMmcsErr,
+    },
+    /// The input openings of a single query were rejected.
+    InputError {
+        query: usize,
+        source: InputError,
+    },
+    /// A batch of input openings, shared by all queries, was rejected.
+    InputBatchError {
+        batch: usize,
+        source: InputError,
+    },
+    /// A batch of input openings was rejected, and the openings of `query` are the ones at fault:
+    /// they also disagree with the rest of the proof for that query.
+    InputMmcsError {
+        query: usize,
+        batch: usize,
+        source: InputError,
+    },
+    /// Folding a query's openings didn't arrive at the final polynomial.
+    FinalPolyMismatch {
+        query: usize,
+    },
     InvalidPowWitness,
+    /// The committed DEEP quotient disagrees with the quotient recomputed from the input openings.
+    /// `log_height` is the log LDE height of the first quotient that disagrees.
+    DeepQuotientMismatch {
+        query: usize,
+        log_height: usize,
+    },
+    /// The openings of the committed DEEP quotient didn't verify against its commitment.
+    DeepQuotientMmcsError(CommitMmcsErr),
+    /// The LDE of a claimed domain would be taller than the field's two-adicity allows.
+    LogHeightTooLarge {
+        batch: usize,
+        matrix: usize,
+        log_height: usize,
+    },
+    /// A claimed LDE is shorter than the final codeword, which FRI can't roll it into, and isn't
+    /// a constant, which it could skip.
+    LogHeightBelowFinalHeight {
+        batch: usize,
+        matrix: usize,
+        log_height: usize,
+    },
+    /// The tallest claimed LDE doesn't have the height implied by the number of commit phase
+    /// rounds in the proof.
+    LogMaxHeightMismatch {
+        claimed: usize,
+        proof: usize,
+    },
+    /// An opened row doesn't have as many values as were claimed for its matrix.
+    OpenedWidthMismatch {
+        batch: usize,
+        matrix: usize,
+        query: usize,
+        expected: usize,
+        got: usize,
+    },
+}
+
+/// How a proof's shape differs from what the verifier expects, as carried by
+/// [`FriError::InvalidProofShape`]. Queries are numbered in the order they were sampled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofShapeError {
+    FinalPolyLen {
+        expected: usize,
+        got: usize,
+    },
+    NumQueries {
+        expected: usize,
+        got: usize,
+    },
+    /// A query doesn't open every commit phase round.
+    CommitPhaseOpenings {
+        query: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// A query's folds, whose arity depends on the heights of its inputs, don't use up exactly the
+    /// commit phase rounds in the proof.
+    FoldingRounds {
+        query: usize,
+    },
+    /// A commit phase opening doesn't have one sibling value for each other row its fold reads.
+    SiblingValues {
+        query: usize,
+        round: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// The proof has a proof of work witness, but the config doesn't grind.
+    UnexpectedPowWitness,
+    /// A query has an input shorter than the final codeword, which can't be rolled into the fold.
+    InputBelowFinalHeight {
+        query: usize,
+        log_height: usize,
+    },
+    /// The proof doesn't have openings for every input batch.
+    NumBatches {
+        expected: usize,
+        got: usize,
+    },
+    /// An input batch doesn't have openings for every query.
+    BatchQueries {
+        batch: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// A query doesn't open every matrix of an input batch.
+    BatchMatrices {
+        batch: usize,
+        query: usize,
+        expected: usize,
+        got: usize,
+    },
+    /// The proof has a committed DEEP quotient exactly when the verifier doesn't expect one.
+    DeepQuotientPresence {
+        expected: bool,
+    },
+    /// The committed DEEP quotient doesn't have openings for every query.
+    DeepQuotientQueries {
+        expected: usize,
+        got: usize,
+    },
+    /// A query doesn't open a single value of each committed DEEP quotient.
+    DeepQuotientOpening {
+        query: usize,
+    },
+    /// Nothing was claimed, so the proof should be empty, but isn't.
+    NotEmpty,
+    /// Nothing was claimed, which this verifier has no proof for.
+    NoClaims,
+}
+
+impl ProofShapeError {
+    /// The query whose openings are malformed, if the error is specific to one.
+    pub const fn query(&self) -> Option<usize> {
+        match self {
+            Self::CommitPhaseOpenings { query, .. }
+            | Self::FoldingRounds { query }
+            | Self::SiblingValues { query, .. }
+            | Self::InputBelowFinalHeight { query, .. }
+            | Self::BatchMatrices { query, .. }
+            | Self::DeepQuotientOpening { query } => Some(*query),
+            _ => None,
+        }
+    }
+}
+
+impl Display for ProofShapeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::FinalPolyLen { expected, got } => write!(
+                f,
+                "final polynomial has {got} coefficients, expected {expected}"
+            ),
+            Self::NumQueries { expected, got } => {
+                write!(f, "{got} queries, expected {expected}")
+            }
+            Self::CommitPhaseOpenings {
+                query,
+                expected,
+                got,
+            } => write!(
+                f,
+                "query {query}: {got} commit phase openings, expected {expected}"
+            ),
+            Self::FoldingRounds { query } => write!(
+                f,
+                "query {query}: folds don't use up exactly the commit phase rounds"
+            ),
+            Self::SiblingValues {
+                query,
+                round,
+                expected,
+                got,
+            } => write!(
+                f,
+                "query {query}: commit phase opening of round {round} has {got} sibling values, \
+                 expected {expected}"
+            ),
+            Self::UnexpectedPowWitness => {
+                write!(
+                    f,
+                    "proof of work witness present, but no grinding is configured"
+                )
+            }
+            Self::InputBelowFinalHeight { query, log_height } => write!(
+                f,
+                "query {query}: input of height 2^{log_height} is shorter than the final codeword"
+            ),
+            Self::NumBatches { expected, got } => {
+                write!(f, "openings for {got} input batches, expected {expected}")
+            }
+            Self::BatchQueries {
+                batch,
+                expected,
+                got,
+            } => write!(
+                f,
+                "input batch {batch}: openings for {got} queries, expected {expected}"
+            ),
+            Self::BatchMatrices {
+                batch,
+                query,
+                expected,
+                got,
+            } => write!(
+                f,
+                "query {query}: input batch {batch} opens {got} matrices, expected {expected}"
+            ),
+            Self::DeepQuotientPresence { expected: true } => {
+                write!(f, "committed DEEP quotient missing")
+            }
+            Self::DeepQuotientPresence { expected: false } => {
+                write!(f, "unexpected committed DEEP quotient")
+            }
+            Self::DeepQuotientQueries { expected, got } => write!(
+                f,
+                "committed DEEP quotient has openings for {got} queries, expected {expected}"
+            ),
+            Self::DeepQuotientOpening { query } => write!(
+                f,
+                "query {query}: committed DEEP quotient opening has the wrong shape"
+            ),
+            Self::NotEmpty => write!(f, "nothing was claimed, but the proof is not empty"),
+            Self::NoClaims => write!(f, "nothing was claimed"),
+        }
+    }
+}
+
+impl<CommitMmcsErr, InputError> FriError<CommitMmcsErr, InputError> {
+    /// The query at which verification failed, if the error is specific to one.
+    pub const fn query(&self) -> Option<usize> {
+        match self {
+            Self::InvalidProofShape(shape) => shape.query(),
+            Self::CommitPhaseMmcsError { query, .. }
+            | Self::InputError { query, .. }
+            | Self::InputMmcsError { query, .. }
+            | Self::FinalPolyMismatch { query }
+            | Self::DeepQuotientMismatch { query, .. }
+            | Self::OpenedWidthMismatch { query, .. } => Some(*query),
+            _ => None,
+        }
+    }
+}
+
+impl<CommitMmcsErr: Debug, InputError: Debug> Display for FriError<CommitMmcsErr, InputError> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidProofShape(shape) => {
+                write!(f, "proof does not have the expected shape: {shape}")
+            }
+            Self::CommitPhaseMmcsError {
+                query,
+                round,
+                log_height,
+                source,
+            } => write!(
+                f,
+                "query {query}: commit phase opening of round {round} (codeword of height \
+                 2^{log_height}) failed to verify: {source:?}"
+            ),
+            Self::InputError { query, source } => {
+                write!(
+                    f,
+                    "query {query}: input openings failed to verify: {source:?}"
+                )
+            }
+            Self::InputBatchError { batch, source } => {
+                write!(
+                    f,
+                    "input batch {batch}: openings failed to verify: {source:?}"
+                )
+            }
+            Self::InputMmcsError {
+                query,
+                batch,
+                source,
+            } => write!(
+                f,
+                "query {query}: input batch {batch}: openings failed to verify: {source:?}"
+            ),
+            Self::FinalPolyMismatch { query } => write!(
+                f,
+                "query {query}: folded evaluation does not match the final polynomial"
+            ),
+            Self::InvalidPowWitness => write!(f, "proof of work witness is invalid"),
+            Self::DeepQuotientMismatch { query, log_height } => write!(
+                f,
+                "query {query}: committed DEEP quotient of height 2^{log_height} does not match \
+                 the input openings"
+            ),
+            Self::DeepQuotientMmcsError(source) => write!(
+                f,
+                "committed DEEP quotient openings failed to verify: {source:?}"
+            ),
+            Self::LogHeightTooLarge {
+                batch,
+                matrix,
+                log_height,
+            } => write!(
+                f,
+                "input batch {batch}, matrix {matrix}: LDE height 2^{log_height} exceeds the \
+                 field's two-adicity"
+            ),
+            Self::LogHeightBelowFinalHeight {
+                batch,
+                matrix,
+                log_height,
+            } => write!(
+                f,
+                "input batch {batch}, matrix {matrix}: LDE height 2^{log_height} is shorter than \
+                 the final codeword"
+            ),
+            Self::LogMaxHeightMismatch { claimed, proof } => write!(
+                f,
+                "tallest claimed LDE has height 2^{claimed}, but the proof implies 2^{proof}"
+            ),
+            Self::OpenedWidthMismatch {
+                batch,
+                matrix,
+                query,
+                expected,
+                got,
+            } => write!(
+                f,
+                "query {query}: input batch {batch}, matrix {matrix} has {got} opened values, \
+                 expected {expected}"
+            ),
+        }
+    }
 }
 
 pub fn verify<G, Val, Challenge, M, Challenger>(
     g: &G,
     config: &FriConfig<M>,
+    query_sampler: &impl QueryIndexSampler<Challenger>,
+    pow: &impl PowStrategy<Challenger>,
     proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
     challenger: &mut Challenger,
     open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
 ) -> Result<(), FriError<M::Error, G::InputError>>
 where
     Val: Field,
-    Challenge: ExtensionField<Val>,
+    Challenge: ExtensionField<Val> + TwoAdicField,
     M: Mmcs<Challenge>,
     Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
     G: FriGenericConfig<Challenge>,
 {
+    let challenges =
+        verify_shape_and_sample_challenges(g, config, query_sampler, pow, proof, challenger)?;
+    verify_challenges(g, config, proof, &challenges, open_input)
+}
+
+/// The verifier's challenges for a FRI proof, as sampled by `verify_shape_and_sample_challenges`.
+#[derive(Clone, Debug)]
+pub struct FriChallenges<F> {
+    /// The folding challenge of each commit phase round.
+    pub betas: Vec<F>,
+    /// The index of each query, in the order they were sampled.
+    pub query_indices: Vec<usize>,
+}
+
+/// The transcript half of `verify`: check the proof's shape and its proof of work, and sample the
+/// folding challenges and query indices. Nothing is observed after this, so a caller may use the
+/// query indices, e.g. to authenticate input openings, before the queries themselves are checked
+/// by `verify_challenges`.
+pub fn verify_shape_and_sample_challenges<G, Val, Challenge, M, Challenger>(
+    g: &G,
+    config: &FriConfig<M>,
+    query_sampler: &impl QueryIndexSampler<Challenger>,
+    pow: &impl PowStrategy<Challenger>,
+    proof: &FriProof<Challenge, M, Challenger::Witness, G::InputProof>,
+    challenger: &mut Challenger,
+) -> Result<FriChallenges<Challenge>, FriError<M::Error, G::InputError>>
+where
+    Val: Field,
+    Challenge: ExtensionField<Val> + TwoAdicField,
+    M: Mmcs<Challenge>,
+    Challenger: FieldChallenger<Val> + GrindingChallenger + CanObserve<M::Commitment>,
+    G: FriGenericConfig<Challenge>,
+{
+    if proof.final_poly_len() != config.final_poly_len() {
+        return Err(FriError::InvalidProofShape(ProofShapeError::FinalPolyLen {
+            expected: config.final_poly_len(),
+            got: proof.final_poly_len(),
+        }));
+    }
+
     let betas: Vec<Challenge> = proof
-        .commit_phase_commits
+        .commit_phase_commits()
         .iter()
         .map(|comm| {
             challenger.observe(comm.clone());
             challenger.sample_ext_element()
         })
         .collect();
-    challenger.observe_ext_element(proof.final_poly);
+    for &c in proof.final_poly() {
+        challenger.observe_ext_element(c);
+    }
 
-    if proof.query_proofs.len() != config.num_queries {
-        return Err(FriError::InvalidProofShape);
+    if proof.num_queries() != config.num_queries {
+        return Err(FriError::InvalidProofShape(ProofShapeError::NumQueries {
+            expected: config.num_queries,
+            got: proof.num_queries(),
+        }));
+    }
+    for (query, qp) in proof.query_proofs().iter().enumerate() {
+        if qp.commit_phase_openings.len() != proof.num_commit_phase_rounds() {
+            return Err(FriError::InvalidProofShape(
+                ProofShapeError::CommitPhaseOpenings {
+                    query,
+                    expected: proof.num_commit_phase_rounds(),
+                    got: qp.commit_phase_openings.len(),
+                },
+            ));
+        }
     }
 
-    // Check PoW.
-    if !challenger.check_witness(config.proof_of_work_bits, proof.pow_witness) {
-        return Err(FriError::InvalidPowWitness);
+    // Check PoW. A witness is present exactly when the config asks for grinding.
+    match (config.grinds(), proof.pow_witness().copied()) {
+        (false, None) => {}
+        (false, Some(_)) => {
+            return Err(FriError::InvalidProofShape(
+                ProofShapeError::UnexpectedPowWitness,
+            ))
+        }
+        (true, None) => return Err(FriError::InvalidPowWitness),
+        (true, Some(witness)) => {
+            if !pow.check_witness(challenger, config.proof_of_work_bits, witness) {
+                return Err(FriError::InvalidPowWitness);
+            }
+        }
     }
 
-    let log_max_height = proof.commit_phase_commits.len() + config.log_blowup;
+    let log_max_height =
+        proof.num_commit_phase_rounds() + config.log_blowup + config.log_final_poly_len;
+    let query_indices = (0..config.num_queries)
+        .map(|_| {
+            query_sampler.sample_index(challenger, log_max_height + g.extra_query_index_bits())
+        })
+        .collect();
+
+    Ok(FriChallenges {
+        betas,
+        query_indices,
+    })
+}
+
+/// The query half of `verify`: check each query against the challenges sampled by
+/// `verify_shape_and_sample_challenges` for the same proof.
+pub fn verify_challenges<G, Challenge, M, Witness>(
+    g: &G,
+    config: &FriConfig<M>,
+    proof: &FriProof<Challenge, M, Witness, G::InputProof>,
+    challenges: &FriChallenges<Challenge>,
+    open_input: impl Fn(usize, &G::InputProof) -> Result<Vec<(usize, Challenge)>, G::InputError>,
+) -> Result<(), FriError<M::Error, G::InputError>>
+where
+    Challenge: TwoAdicField,
+    M: Mmcs<Challenge>,
+    G: FriGenericConfig<Challenge>,
+{
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
+    let log_max_height = proof.num_commit_phase_rounds() + log_final_height;
+    let final_generator = Challenge::two_adic_generator(log_final_height);
 
-    for qp in &proof.query_proofs {
-        let index = challenger.sample_bits(log_max_height + g.extra_query_index_bits());
-        let ro = open_input(index, &qp.input_proof).map_err(FriError::InputError)?;
+    for (query, (qp, &index)) in izip!(proof.query_proofs(), &challenges.query_indices).enumerate()
+    {
+        let ro = open_input(index, &qp.input_proof)
+            .map_err(|source| FriError::InputError { query, source })?;
 
         debug_assert!(
             ro.iter().tuple_windows().all(|((l, _), (r, _))| l > r),
             "reduced openings sorted by height descending"
         );
 
-        let folded_eval = verify_query(
+        let (folded_eval, final_index) = verify_query(
             g,
             config,
+            query,
             index >> g.extra_query_index_bits(),
             izip!(
-                &betas,
-                &proof.commit_phase_commits,
+                &challenges.betas,
+                proof.commit_phase_commits(),
                 &qp.commit_phase_openings
             ),
             ro,
             log_max_height,
         )?;
 
-        if folded_eval != proof.final_poly {
-            return Err(FriError::FinalPolyMismatch);
+        // The final codeword is in bit-reversed order, like every committed codeword.
+        let x = final_generator.exp_u64(reverse_bits_len(final_index, log_final_height) as u64);
+        if folded_eval != eval_poly(proof.final_poly(), x) {
+            return Err(FriError::FinalPolyMismatch { query });
         }
     }
 
@@ -92,20 +537,24 @@ type CommitStep<'a, F, M> = (
 fn verify_query<'a, G, F, M>(
     g: &G,
     config: &FriConfig<M>,
+    query: usize,
     mut index: usize,
     steps: impl Iterator<Item = CommitStep<'a, F, M>>,
     reduced_openings: Vec<(usize, F)>,
     log_max_height: usize,
-) -> Result<F, FriError<M::Error, G::InputError>>
+) -> Result<(F, usize), FriError<M::Error, G::InputError>>
 where
     F: Field,
     M: Mmcs<F> + 'a,
     G: FriGenericConfig<F>,
 {
+    let log_final_height = config.log_blowup + config.log_final_poly_len;
     let mut folded_eval = F::ZERO;
     let mut ro_iter = reduced_openings.into_iter().peekable();
 
-    for (log_folded_height, (&beta, comm, opening)) in izip!((0..log_max_height).rev(), steps) {
+    for (round, (log_folded_height, (&beta, comm, opening))) in
+        izip!((log_final_height..log_max_height).rev(), steps).enumerate()
+    {
         if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_folded_height + 1) {
             folded_eval += ro;
         }
@@ -129,18 +578,29 @@ where
                 &[evals.clone()],
                 &opening.opening_proof,
             )
-            .map_err(FriError::CommitPhaseMmcsError)?;
+            .map_err(|source| FriError::CommitPhaseMmcsError {
+                query,
+                round,
+                log_height: log_folded_height + 1,
+                source,
+            })?;
 
         index = index_pair;
 
         folded_eval = g.fold_row(index, log_folded_height, beta, evals.into_iter());
     }
 
-    debug_assert!(index < config.blowup(), "index was {}", index);
-    debug_assert!(
-        ro_iter.next().is_none(),
-        "verifier reduced_openings were not in descending order?"
-    );
+    // An input as tall as the final codeword is rolled in after the last round.
+    if let Some((_, ro)) = ro_iter.next_if(|(lh, _)| *lh == log_final_height) {
+        folded_eval += ro;
+    }
+    debug_assert!(index < 1 << log_final_height, "index was {}", index);
+    // Anything left is shorter than the final codeword, which the prover can't roll in either.
+    if let Some((log_height, _)) = ro_iter.next() {
+        return Err(FriError::InvalidProofShape(
+            ProofShapeError::InputBelowFinalHeight { query, log_height },
+        ));
+    }
 
-    Ok(folded_eval)
+    Ok((folded_eval, index))
 }
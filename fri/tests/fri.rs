@@ -2,12 +2,15 @@ use core::cmp::Reverse;
 use std::marker::PhantomData;
 
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
-use p3_challenger::{CanSampleBits, DuplexChallenger, FieldChallenger};
+use p3_challenger::{CanObserve, CanSampleBits, DuplexChallenger, FieldChallenger};
 use p3_commit::ExtensionMmcs;
 use p3_dft::{Radix2Dit, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
 use p3_field::{AbstractField, Field};
-use p3_fri::{prover, verifier, FriConfig, TwoAdicFriGenericConfig};
+use p3_fri::{
+    prover, verifier, ChallengerPow, FriConfig, FriGenericConfig, SampleLowBits,
+    TwoAdicFriGenericConfig,
+};
 use p3_matrix::dense::RowMajorMatrix;
 use p3_matrix::util::reverse_matrix_index_bits;
 use p3_matrix::Matrix;
@@ -29,22 +32,24 @@ type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
 type MyFriConfig = FriConfig<ChallengeMmcs>;
 
-fn get_ldt_for_testing<R: Rng>(rng: &mut R) -> (Perm, MyFriConfig) {
+fn get_ldt_for_testing<R: Rng>(rng: &mut R, log_final_poly_len: usize) -> (Perm, MyFriConfig) {
     let perm = Perm::new_from_rng_128(rng);
     let hash = MyHash::new(perm.clone());
     let compress = MyCompress::new(perm.clone());
     let mmcs = ChallengeMmcs::new(ValMmcs::new(hash, compress));
     let fri_config = FriConfig {
         log_blowup: 1,
+        log_final_poly_len,
         num_queries: 10,
         proof_of_work_bits: 8,
+        omit_zero_bit_pow: false,
         mmcs,
     };
     (perm, fri_config)
 }
 
-fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
-    let (perm, fc) = get_ldt_for_testing(rng);
+fn do_test_fri_ldt<R: Rng>(rng: &mut R, log_final_poly_len: usize) {
+    let (perm, fc) = get_ldt_for_testing(rng, log_final_poly_len);
     let dft = Radix2Dit::default();
 
     let shift = Val::GENERATOR;
@@ -58,7 +63,7 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
         })
         .collect();
 
-    let (proof, p_sample) = {
+    let (proof, p_sample, input) = {
         // Prover world
         let mut chal = Challenger::new(perm.clone());
         let alpha: Challenge = chal.sample_ext_element();
@@ -91,6 +96,8 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
         let proof = prover::prove(
             &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
             &fc,
+            &SampleLowBits,
+            &ChallengerPow,
             input.clone(),
             &mut chal,
             |idx| {
@@ -105,14 +112,36 @@ fn do_test_fri_ldt<R: Rng>(rng: &mut R) {
             },
         );
 
-        (proof, chal.sample_bits(8))
+        (proof, chal.sample_bits(8), input)
     };
 
+    assert_eq!(proof.final_poly_len(), fc.final_poly_len());
+
+    // Folding the inputs with the proof's betas, independently of the prover, ends at the final
+    // codeword.
+    let g = TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData);
+    let mut f_challenger = Challenger::new(perm.clone());
+    let _alpha: Challenge = f_challenger.sample_ext_element();
+    let mut inputs_iter = input.into_iter().peekable();
+    let mut folded = inputs_iter.next().unwrap();
+    for commit in proof.commit_phase_commits() {
+        f_challenger.observe(*commit);
+        let beta: Challenge = f_challenger.sample_ext_element();
+        folded = g.fold_matrix(beta, RowMajorMatrix::new(folded, 2));
+        if let Some(v) = inputs_iter.next_if(|v| v.len() == folded.len()) {
+            folded.iter_mut().zip(v).for_each(|(c, x)| *c += x);
+        }
+    }
+    assert!(inputs_iter.next().is_none());
+    assert_eq!(folded, proof.final_codeword(fc.log_blowup));
+
     let mut v_challenger = Challenger::new(perm);
     let _alpha: Challenge = v_challenger.sample_ext_element();
     verifier::verify(
         &TwoAdicFriGenericConfig::<Vec<(usize, Challenge)>, ()>(PhantomData),
         &fc,
+        &SampleLowBits,
+        &ChallengerPow,
         &proof,
         &mut v_challenger,
         |_index, proof| Ok(proof.clone()),
@@ -131,6 +160,16 @@ fn test_fri_ldt() {
     // FRI is kind of flaky depending on indexing luck
     for i in 0..4 {
         let mut rng = ChaCha20Rng::seed_from_u64(i);
-        do_test_fri_ldt(&mut rng);
+        do_test_fri_ldt(&mut rng, 0);
+    }
+}
+
+#[test]
+fn test_fri_ldt_with_final_poly() {
+    for log_final_poly_len in 0..3 {
+        for i in 0..4 {
+            let mut rng = ChaCha20Rng::seed_from_u64(i);
+            do_test_fri_ldt(&mut rng, log_final_poly_len);
+        }
     }
 }
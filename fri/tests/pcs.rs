@@ -1,14 +1,30 @@
+use std::panic::AssertUnwindSafe;
+
 use itertools::{izip, Itertools};
 use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
 use p3_challenger::{CanObserve, DuplexChallenger, FieldChallenger};
-use p3_commit::{ExtensionMmcs, Pcs, PolynomialSpace};
-use p3_dft::Radix2DitParallel;
+use p3_commit::{
+    iter_flat, iter_flat_claims, ExtensionMmcs, Mmcs, Pcs, PolynomialSpace,
+    TwoAdicMultiplicativeCoset,
+};
+use p3_dft::{HasDefaultDft, Radix2DitParallel, TwoAdicSubgroupDft};
 use p3_field::extension::BinomialExtensionField;
-use p3_field::{ExtensionField, Field};
-use p3_fri::{FriConfig, TwoAdicFriPcs};
+use p3_field::{AbstractExtensionField, AbstractField, ExtensionField, Field, TwoAdicField};
+use p3_fri::verifier::{FriError, ProofShapeError};
+use p3_fri::{
+    DefaultTwoAdicFriPcs, FriConfig, FriProof, ProofStats, QueryProof, TracePadding, TwoAdicFriPcs,
+    TwoAdicFriVerifier,
+};
+use p3_interpolation::eval_cols_at_point;
+use p3_matrix::assert_matrices_eq;
+use p3_matrix::column_major::ColumnMajorMatrix;
 use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::periodic::PeriodicMatrix;
+use p3_matrix::sparse::CsrMatrix;
+use p3_matrix::Matrix;
 use p3_merkle_tree::MerkleTreeMmcs;
-use p3_symmetric::{PaddingFreeSponge, TruncatedPermutation};
+use p3_symmetric::{CryptographicPermutation, PaddingFreeSponge, TruncatedPermutation};
+use p3_util::reverse_slice_index_bits;
 use rand::distributions::{Distribution, Standard};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha20Rng;
@@ -17,6 +33,75 @@ fn seeded_rng() -> impl Rng {
     ChaCha20Rng::seed_from_u64(0)
 }
 
+/// A Merkle tree MMCS over `Val`, hashing with a sponge over `Perm` and compressing with `Perm`.
+type PermMmcs<Val, Perm, const WIDTH: usize, const RATE: usize, const DIGEST_ELEMS: usize> =
+    MerkleTreeMmcs<
+        <Val as Field>::Packing,
+        <Val as Field>::Packing,
+        PaddingFreeSponge<Perm, WIDTH, RATE, DIGEST_ELEMS>,
+        TruncatedPermutation<Perm, 2, DIGEST_ELEMS, WIDTH>,
+        DIGEST_ELEMS,
+    >;
+
+/// The PCS the tests use over any two-adic field: inputs are committed with `ValMmcs`, and FRI
+/// commits to its `Challenge` layers with the same MMCS, extended.
+type TestFriPcs<Val, Challenge, ValMmcs> =
+    DefaultTwoAdicFriPcs<Val, ValMmcs, ExtensionMmcs<Val, Challenge, ValMmcs>>;
+
+/// An MMCS and a duplex challenger both built on the permutation `perm`.
+fn perm_mmcs_and_challenger<
+    Val,
+    Perm,
+    const WIDTH: usize,
+    const RATE: usize,
+    const DIGEST_ELEMS: usize,
+>(
+    perm: Perm,
+) -> (
+    PermMmcs<Val, Perm, WIDTH, RATE, DIGEST_ELEMS>,
+    DuplexChallenger<Val, Perm, WIDTH, RATE>,
+)
+where
+    Val: Field,
+    Perm: CryptographicPermutation<[Val; WIDTH]>,
+{
+    let hash = PaddingFreeSponge::new(perm.clone());
+    let compress = TruncatedPermutation::new(perm.clone());
+    (
+        MerkleTreeMmcs::new(hash, compress),
+        DuplexChallenger::new(perm),
+    )
+}
+
+/// Sets up a FRI PCS over `Val` with openings in `Challenge`, committing through `val_mmcs`.
+///
+/// Together with `perm_mmcs_and_challenger`, this lets the same tests run over any field with a
+/// permutation to hash with.
+fn make_test_fri_pcs<Val, Challenge, ValMmcs, Challenger>(
+    val_mmcs: ValMmcs,
+    challenger: Challenger,
+    log_blowup: usize,
+    num_queries: usize,
+    proof_of_work_bits: usize,
+) -> (TestFriPcs<Val, Challenge, ValMmcs>, Challenger)
+where
+    Val: HasDefaultDft,
+    ValMmcs: Clone,
+{
+    let fri_config = FriConfig {
+        log_blowup,
+        log_final_poly_len: 0,
+        num_queries,
+        proof_of_work_bits,
+        omit_zero_bit_pow: true,
+        mmcs: ExtensionMmcs::new(val_mmcs.clone()),
+    };
+    (
+        TestFriPcs::with_default_dft(val_mmcs, fri_config),
+        challenger,
+    )
+}
+
 fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
     (pcs, challenger): &(P, Challenger),
     log_degrees_by_round: &[&[usize]],
@@ -68,6 +153,7 @@ fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
     let data_and_points = data_by_round.iter().zip(points_by_round).collect();
     let (opening_by_round, proof) = pcs.open(data_and_points, &mut p_challenger);
     assert_eq!(opening_by_round.len(), num_rounds);
+    let prover_flat = iter_flat(&opening_by_round).copied().collect_vec();
 
     // Verify the proof.
     let mut v_challenger = challenger.clone();
@@ -90,6 +176,13 @@ fn do_test_fri_pcs<Val, Challenge, Challenger, P>(
     })
     .collect_vec();
     assert_eq!(commits_and_claims_by_round.len(), num_rounds);
+    // Both sides would observe the opened values in the same order.
+    assert_eq!(
+        iter_flat_claims(&commits_and_claims_by_round)
+            .copied()
+            .collect_vec(),
+        prover_flat
+    );
 
     pcs.verify(commits_and_claims_by_round, &proof, &mut v_challenger)
         .unwrap()
@@ -147,10 +240,23 @@ macro_rules! make_tests_for_pcs {
             $crate::do_test_fri_pcs(&p, &[&[3, 3], &[2, 2]]);
             $crate::do_test_fri_pcs(&p, &[&[2], &[3, 3]]);
         }
+
+        #[test]
+        fn repeated_heights_across_rounds() {
+            let p = $p;
+            $crate::do_test_fri_pcs(&p, &[&[4, 2, 4], &[2, 3, 4], &[3]]);
+            $crate::do_test_fri_pcs(&p, &[&[5; 3], &[5, 5], &[5]]);
+        }
     };
 }
 
 mod babybear_fri_pcs {
+    use p3_challenger::CanSample;
+    use p3_field::PrimeField32;
+    use p3_fri::PowStrategy;
+    use p3_keccak::Keccak256Hash;
+    use p3_symmetric::CryptographicHasher;
+
     use super::*;
 
     type Val = BabyBear;
@@ -169,22 +275,1547 @@ mod babybear_fri_pcs {
     type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
 
     fn get_pcs(log_blowup: usize) -> (MyPcs, Challenger) {
+        get_pcs_with_queries(log_blowup, 10)
+    }
+
+    fn get_pcs_with_queries(log_blowup: usize, num_queries: usize) -> (MyPcs, Challenger) {
+        get_pcs_with_params(log_blowup, num_queries, 8)
+    }
+
+    fn get_pcs_with_params(
+        log_blowup: usize,
+        num_queries: usize,
+        proof_of_work_bits: usize,
+    ) -> (MyPcs, Challenger) {
         let perm = Perm::new_from_rng_128(&mut seeded_rng());
-        let hash = MyHash::new(perm.clone());
-        let compress = MyCompress::new(perm.clone());
+        let (val_mmcs, challenger) = perm_mmcs_and_challenger(perm);
+        make_test_fri_pcs(
+            val_mmcs,
+            challenger,
+            log_blowup,
+            num_queries,
+            proof_of_work_bits,
+        )
+    }
 
-        let val_mmcs = ValMmcs::new(hash, compress);
-        let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1));
+    }
+    mod blowup_2 {
+        make_tests_for_pcs!(super::get_pcs(2));
+    }
+
+    type PcsError = <MyPcs as Pcs<Challenge, Challenger>>::Error;
+
+    type ClaimsByRound = Vec<(
+        <MyPcs as Pcs<Challenge, Challenger>>::Commitment,
+        Vec<(
+            TwoAdicMultiplicativeCoset<Val>,
+            Vec<(Challenge, Vec<Challenge>)>,
+        )>,
+    )>;
+
+    /// Commit to and open a single random matrix, returning the pieces needed to verify.
+    fn open_single(
+        pcs: &MyPcs,
+        challenger: &Challenger,
+        log_degree: usize,
+        width: usize,
+    ) -> (ClaimsByRound, <MyPcs as Pcs<Challenge, Challenger>>::Proof) {
+        let domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(pcs, 1 << log_degree);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, width);
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(pcs, vec![(domain, evals)]);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opened_values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+
+        let claims = vec![(
+            commit,
+            vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])],
+        )];
+        (claims, proof)
+    }
+
+    fn verify_single(
+        pcs: &MyPcs,
+        challenger: &Challenger,
+        claims: ClaimsByRound,
+        proof: &<MyPcs as Pcs<Challenge, Challenger>>::Proof,
+    ) -> Result<(), PcsError> {
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(claims[0].0);
+        let _zeta: Challenge = v_challenger.sample_ext_element();
+        pcs.verify(claims, proof, &mut v_challenger)
+    }
+
+    /// Rebuild the FRI part of `proof` after `tamper` has edited its commitments, query proofs and
+    /// proof-of-work witness.
+    fn tamper_fri_proof(
+        proof: &mut <MyPcs as Pcs<Challenge, Challenger>>::Proof,
+        tamper: impl FnOnce(
+            &mut Vec<<ChallengeMmcs as Mmcs<Challenge>>::Commitment>,
+            &mut Vec<QueryProof<Challenge, ChallengeMmcs, ()>>,
+            &mut Option<Val>,
+        ),
+    ) {
+        let fri_proof = &proof.fri_proof;
+        let mut commits = fri_proof.commit_phase_commits().to_vec();
+        let mut query_proofs = fri_proof.query_proofs().to_vec();
+        let final_poly = fri_proof.final_poly().to_vec();
+        let mut pow_witness = fri_proof.pow_witness().copied();
+        tamper(&mut commits, &mut query_proofs, &mut pow_witness);
+        proof.fri_proof = FriProof::new(commits, query_proofs, final_poly, pow_witness);
+    }
 
+    #[test]
+    fn input_openings_share_paths() {
+        let (pcs, challenger) = get_pcs_with_queries(1, 100);
+        let log_degree = 6;
+        let (claims, proof) = open_single(&pcs, &challenger, log_degree, 4);
+
+        // Opening each query separately would cost one digest per layer per query. With 100
+        // queries into a 128-leaf tree, most upper nodes are shared between paths.
+        let log_lde_height = log_degree + 1;
+        let unbatched_len = 100 * log_lde_height;
+        let batched_len = proof.input_openings[0].opening_proof.len();
+        assert!(
+            batched_len * 2 <= unbatched_len,
+            "multi-proof has {batched_len} digests, unbatched proof would have {unbatched_len}"
+        );
+
+        verify_single(&pcs, &challenger, claims, &proof).unwrap();
+    }
+
+    #[test]
+    fn empty_batches() {
+        type P = MyPcs;
+        let (pcs, challenger) = get_pcs(1);
+
+        // A single round with no matrices.
+        let (commit, data) = <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![]);
+        let (opened_values, proof) = <P as Pcs<Challenge, Challenger>>::open(
+            &pcs,
+            vec![(&data, vec![])],
+            &mut challenger.clone(),
+        );
+        assert_eq!(opened_values, vec![Vec::<Vec<Vec<Challenge>>>::new()]);
+        <P as Pcs<Challenge, Challenger>>::verify(
+            &pcs,
+            vec![(commit, vec![])],
+            &proof,
+            &mut challenger.clone(),
+        )
+        .expect("empty round should verify");
+
+        // No rounds at all.
+        let (opened_values, proof) =
+            <P as Pcs<Challenge, Challenger>>::open(&pcs, vec![], &mut challenger.clone());
+        assert!(opened_values.is_empty());
+        <P as Pcs<Challenge, Challenger>>::verify(&pcs, vec![], &proof, &mut challenger.clone())
+            .expect("empty proof should verify");
+
+        // Empty rounds mixed in with non-empty ones.
+        do_test_fri_pcs(&(pcs, challenger), &[&[3], &[], &[4, 2]]);
+    }
+
+    #[test]
+    fn empty_proof_with_extra_parts_fails() {
+        let (pcs, challenger) = get_pcs(1);
+        let (commit, data) = <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![]);
+        let (_, proof) = pcs.open(vec![(&data, vec![])], &mut challenger.clone());
+        let (_, non_empty_proof) = open_single(&pcs, &challenger, 3, 2);
+        let verify = |proof: &<MyPcs as Pcs<Challenge, Challenger>>::Proof| {
+            <MyPcs as Pcs<Challenge, Challenger>>::verify(
+                &pcs,
+                vec![(commit, vec![])],
+                proof,
+                &mut challenger.clone(),
+            )
+        };
+        verify(&proof).expect("empty round should verify");
+
+        let mut with_pow_witness = proof.clone();
+        tamper_fri_proof(&mut with_pow_witness, |_, _, pow_witness| {
+            *pow_witness = Some(Val::ONE);
+        });
+        let mut with_commit = proof.clone();
+        tamper_fri_proof(&mut with_commit, |commits, _, _| {
+            commits.push(non_empty_proof.fri_proof.commit_phase_commits()[0]);
+        });
+        let mut with_query = proof;
+        tamper_fri_proof(&mut with_query, |_, query_proofs, _| {
+            query_proofs.push(non_empty_proof.fri_proof.query_proofs()[0].clone());
+        });
+        for bad in [with_pow_witness, with_commit, with_query] {
+            let err = verify(&bad).unwrap_err();
+            assert!(
+                matches!(err, FriError::InvalidProofShape(ProofShapeError::NotEmpty)),
+                "{err:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn proof_shape_matches_config() {
+        let log_blowup = 2;
+        let (pcs, challenger) = get_pcs_with_queries(log_blowup, 12);
+        let log_degree = 5;
+        let (_, proof) = open_single(&pcs, &challenger, log_degree, 7);
+
+        let fri_proof = proof.fri_proof();
+        assert_eq!(fri_proof.num_queries(), 12);
+        // FRI folds the tallest LDE down to the blowup.
+        let log_max_height = log_degree + log_blowup;
+        assert_eq!(
+            fri_proof.num_commit_phase_rounds(),
+            log_max_height - log_blowup
+        );
+        assert_eq!(fri_proof.final_poly_len(), 1);
+        assert_eq!(fri_proof.commit_phase_commits().len(), log_degree);
+        assert!(fri_proof.query_opening_lens().all(|len| len == log_degree));
+
+        assert_eq!(proof.input_openings().len(), 1);
+        assert_eq!(proof.input_openings()[0].num_queries(), 12);
+        assert_eq!(proof.opened_widths(), vec![vec![7]]);
+        assert!(proof.deep_quotient().is_none());
+
+        let size = proof.size_breakdown();
+        assert_eq!(
+            size.fri.pow_witness,
+            postcard::to_allocvec(&fri_proof.pow_witness())
+                .unwrap()
+                .len()
+        );
+        assert_eq!(
+            size.fri.final_poly,
+            postcard::to_allocvec(fri_proof.final_poly()).unwrap().len()
+        );
+        // The input openings are checked outside of FRI, so its input proofs are empty.
+        assert_eq!(size.fri.input_proofs, 0);
+        assert!(size.fri.commit_phase_commits > 0);
+        assert!(size.fri.commit_phase_openings > 0);
+        assert_eq!(size.input_openings.len(), 1);
+        // At least a byte for each opened value: 12 queries of 7 BabyBear elements each.
+        assert!(size.input_openings[0] > 12 * 7);
+        assert_eq!(size.deep_quotient, 0);
+
+        let summary = proof.to_string();
+        assert!(summary.contains(&format!(
+            "{} B of commit phase openings",
+            size.fri.commit_phase_openings
+        )));
+        assert!(summary.contains(&format!(
+            "batch 0: 12 queries, widths [7], {} B of input openings",
+            size.input_openings[0]
+        )));
+        assert!(summary.contains(&format!("total: {} B", size.total())));
+    }
+
+    #[test]
+    fn zero_pow_bits_omits_witness() {
+        let (pcs, challenger) = get_pcs_with_params(1, 10, 0);
+        let (claims, proof) = open_single(&pcs, &challenger, 5, 4);
+        assert!(proof.fri_proof.pow_witness().is_none());
+        verify_single(&pcs, &challenger, claims.clone(), &proof).unwrap();
+
+        // A verifier expecting grinding must not accept a proof without a witness.
+        let (grinding_pcs, _) = get_pcs_with_params(1, 10, 8);
+        let err = verify_single(&grinding_pcs, &challenger, claims.clone(), &proof).unwrap_err();
+        assert!(matches!(err, FriError::InvalidPowWitness), "{err:?}");
+
+        // Nor should a verifier without grinding accept a witness it didn't ask for.
+        let (grinding_claims, grinding_proof) = open_single(&grinding_pcs, &challenger, 5, 4);
+        assert!(grinding_proof.fri_proof.pow_witness().is_some());
+        verify_single(
+            &grinding_pcs,
+            &challenger,
+            grinding_claims.clone(),
+            &grinding_proof,
+        )
+        .unwrap();
+        let err = verify_single(&pcs, &challenger, grinding_claims, &grinding_proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InvalidProofShape(ProofShapeError::UnexpectedPowWitness)
+            ),
+            "{err:?}"
+        );
+
+        // Without `omit_zero_bit_pow`, zero bits still grind and observe a witness, as older
+        // proofs expect, and neither transcript accepts the other's proofs.
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let (val_mmcs, _) = perm_mmcs_and_challenger(perm);
         let fri_config = FriConfig {
-            log_blowup,
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 0,
+            omit_zero_bit_pow: false,
+            mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+        };
+        let legacy_pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+        let (legacy_claims, legacy_proof) = open_single(&legacy_pcs, &challenger, 5, 4);
+        assert!(legacy_proof.fri_proof.pow_witness().is_some());
+        verify_single(
+            &legacy_pcs,
+            &challenger,
+            legacy_claims.clone(),
+            &legacy_proof,
+        )
+        .unwrap();
+        let err = verify_single(&pcs, &challenger, legacy_claims, &legacy_proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InvalidProofShape(ProofShapeError::UnexpectedPowWitness)
+            ),
+            "{err:?}"
+        );
+        let err = verify_single(&legacy_pcs, &challenger, claims, &proof).unwrap_err();
+        assert!(matches!(err, FriError::InvalidPowWitness), "{err:?}");
+    }
+
+    #[test]
+    #[should_panic(expected = "exceeds the field's two-adicity of 27")]
+    fn lde_height_beyond_two_adicity() {
+        // A tiny matrix with a huge blowup takes the LDE just past BabyBear's two-adicity.
+        let (pcs, _) = get_pcs(27);
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 2);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 2, 1);
+        <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+    }
+
+    #[test]
+    fn opened_values_match_horner_evaluation() {
+        type P = MyPcs;
+        let (pcs, challenger) = get_pcs(2);
+        let log_degree = 6;
+        let domain =
+            <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_degree);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, 5);
+        let coeffs = Dft::default().idft_batch(evals.clone());
+        let (commit, data) = <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opened_values, _) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+
+        assert_eq!(opened_values[0][0][0], eval_cols_at_point(&coeffs, zeta));
+    }
+
+    #[test]
+    fn trace_and_higher_degree_quotient_in_one_commitment() {
+        type P = MyPcs;
+        let (pcs, challenger) = get_pcs(1);
+        let (log_degree, log_quotient_degree) = (4, 2);
+        let trace_domain =
+            <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_degree);
+        let trace = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, 3);
+        let trace_coeffs = Dft::default().idft_batch(trace.clone());
+
+        // The quotient has four times the trace's degree, so it is committed over a quotient
+        // domain four times the size, and its LDE is four times as tall as the trace's.
+        let quotient_domain =
+            trace_domain.create_disjoint_domain(1 << (log_degree + log_quotient_degree));
+        let quotient_coeffs =
+            RowMajorMatrix::<Val>::rand(&mut seeded_rng(), quotient_domain.size(), 2);
+        let quotient = Dft::default()
+            .coset_dft_batch(quotient_coeffs.clone(), quotient_domain.shift)
+            .to_row_major_matrix();
+
+        let (commit, data) = <P as Pcs<Challenge, Challenger>>::commit(
+            &pcs,
+            vec![(trace_domain, trace), (quotient_domain, quotient)],
+        );
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opened_values, proof) = pcs.open(
+            vec![(&data, vec![vec![zeta], vec![zeta]])],
+            &mut p_challenger,
+        );
+        assert_eq!(
+            opened_values[0][0][0],
+            eval_cols_at_point(&trace_coeffs, zeta)
+        );
+        assert_eq!(
+            opened_values[0][1][0],
+            eval_cols_at_point(&quotient_coeffs, zeta)
+        );
+
+        let claims = vec![(
+            commit,
+            vec![
+                (trace_domain, vec![(zeta, opened_values[0][0][0].clone())]),
+                (
+                    quotient_domain,
+                    vec![(zeta, opened_values[0][1][0].clone())],
+                ),
+            ],
+        )];
+        verify_single(&pcs, &challenger, claims, &proof).unwrap();
+    }
+
+    #[test]
+    fn column_major_commitment_matches_row_major() {
+        type P = MyPcs;
+        let (pcs, _) = get_pcs(1);
+        let domains = [4, 6].map(|log_degree| {
+            <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_degree)
+        });
+        let row_major =
+            domains.map(|domain| RowMajorMatrix::<Val>::rand(&mut seeded_rng(), domain.size(), 7));
+        let column_major = row_major.clone().map(|mat| {
+            ColumnMajorMatrix::new(
+                (0..mat.width())
+                    .map(|c| (0..mat.height()).map(|r| mat.get(r, c)).collect())
+                    .collect(),
+            )
+        });
+
+        let (row_major_commit, _) = <P as Pcs<Challenge, Challenger>>::commit(
+            &pcs,
+            domains.into_iter().zip(row_major).collect(),
+        );
+        let (column_major_commit, _) =
+            pcs.commit_matrices(domains.into_iter().zip(column_major).collect());
+        assert_eq!(row_major_commit, column_major_commit);
+    }
+
+    /// A trace whose rows are computed when read, as a trace generator might produce them.
+    struct LazyTrace {
+        height: usize,
+        width: usize,
+    }
+
+    impl LazyTrace {
+        fn entry(r: usize, c: usize) -> Val {
+            Val::from_canonical_usize(r + 1).exp_u64(c as u64 + 3)
+        }
+    }
+
+    impl Matrix<Val> for LazyTrace {
+        fn width(&self) -> usize {
+            self.width
+        }
+
+        fn height(&self) -> usize {
+            self.height
+        }
+
+        type Row<'a> = std::vec::IntoIter<Val>;
+
+        fn row(&self, r: usize) -> Self::Row<'_> {
+            (0..self.width)
+                .map(|c| Self::entry(r, c))
+                .collect_vec()
+                .into_iter()
+        }
+    }
+
+    #[test]
+    fn lazy_commitment_matches_eager() {
+        type P = MyPcs;
+        let (pcs, _) = get_pcs(2);
+        let domains = [3, 5].map(|log_degree| {
+            <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_degree)
+        });
+        let eager = domains.map(|domain| {
+            RowMajorMatrix::new(
+                (0..domain.size())
+                    .flat_map(|r| (0..4).map(move |c| LazyTrace::entry(r, c)))
+                    .collect(),
+                4,
+            )
+        });
+        let lazy = domains.map(|domain| LazyTrace {
+            height: domain.size(),
+            width: 4,
+        });
+
+        let (eager_commit, _) = <P as Pcs<Challenge, Challenger>>::commit(
+            &pcs,
+            domains.into_iter().zip(eager).collect(),
+        );
+        let (lazy_commit, _) = pcs.commit_matrices(domains.into_iter().zip(lazy).collect());
+        assert_eq!(eager_commit, lazy_commit);
+    }
+
+    #[test]
+    fn structured_selector_commitments_match_dense() {
+        type P = MyPcs;
+        let (pcs, _) = get_pcs(1);
+        let domain = <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+        let n = domain.size();
+        // Two selector columns: one for the first row of every group of four, one for the last.
+        let is_selected =
+            |r: usize, c: usize| (r.is_multiple_of(4) && c == 0) || (r % 4 == 3 && c == 1);
+        let dense = RowMajorMatrix::new(
+            (0..n)
+                .flat_map(|r| (0..2).map(move |c| Val::from_bool(is_selected(r, c))))
+                .collect(),
+            2,
+        );
+        let periodic = PeriodicMatrix::new(RowMajorMatrix::new(dense.values[..8].to_vec(), 2), n);
+        let sparse = CsrMatrix::from_triples(
+            2,
+            n,
+            (0..n)
+                .flat_map(|r| (0..2).map(move |c| (r, c)))
+                .filter(|&(r, c)| is_selected(r, c))
+                .map(|(r, c)| (r, c, Val::ONE)),
+        );
+
+        let (dense_commit, _) =
+            <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, dense)]);
+        let (periodic_commit, _) = pcs.commit_matrices(vec![(domain, periodic)]);
+        let (sparse_commit, _) = pcs.commit_matrices(vec![(domain, sparse)]);
+        assert_eq!(dense_commit, periodic_commit);
+        assert_eq!(dense_commit, sparse_commit);
+    }
+
+    #[test]
+    fn padded_trace_opens_correctly() {
+        type P = MyPcs;
+        for padding in [TracePadding::Zeros, TracePadding::RepeatLastRow] {
+            let (pcs, challenger) = get_pcs(2);
+            let pcs = pcs.with_trace_padding(padding);
+            let domain = <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 5);
+            assert_eq!(domain.size(), 8);
+
+            let trace = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 5, 3);
+            let mut padded = trace.clone();
+            let fill = match padding {
+                TracePadding::Zeros => vec![Val::ZERO; 3],
+                TracePadding::RepeatLastRow => trace.row(4).collect(),
+            };
+            for _ in 5..8 {
+                padded.values.extend_from_slice(&fill);
+            }
+            let coeffs = Dft::default().idft_batch(padded);
+
+            let (commit, data) =
+                <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, trace)]);
+            assert_eq!(data.original_heights(), &[5]);
+
+            let mut p_challenger = challenger.clone();
+            p_challenger.observe(commit);
+            let zeta: Challenge = p_challenger.sample_ext_element();
+            let (opened_values, proof) =
+                pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+            assert_eq!(opened_values[0][0][0], eval_cols_at_point(&coeffs, zeta));
+
+            let claims = vec![(
+                commit,
+                vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])],
+            )];
+            verify_single(&pcs, &challenger, claims, &proof).expect("padded trace should verify");
+        }
+    }
+
+    #[test]
+    fn reduced_openings_match_external_reduction() {
+        type P = MyPcs;
+        let (pcs, challenger) = get_pcs(1);
+        let log_degree = 5;
+        let domain =
+            <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_degree);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, 3);
+        let (commit, data) = <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        // The first thing `open` samples is the batch combination challenge.
+        let alpha: Challenge = p_challenger.clone().sample_ext_element();
+        let (opened_values, _, reduced_openings) =
+            pcs.open_with_reduced_openings(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+        let ys = &opened_values[0][0][0];
+
+        // Recompute sum_i alpha^i (p_i(X) - y_i) / (X - zeta) over the LDE from scratch.
+        let lde_domain = TwoAdicMultiplicativeCoset {
+            log_n: log_degree + 1,
+            shift: Val::GENERATOR,
+        };
+        let lde = <P as Pcs<Challenge, Challenger>>::get_evaluations_on_domain(
+            &pcs, &data, 0, lde_domain,
+        );
+        let xs = Val::two_adic_generator(lde_domain.log_n)
+            .shifted_powers(Val::GENERATOR)
+            .take(lde.height());
+        let mut expected = lde
+            .rows()
+            .zip(xs)
+            .map(|(row, x)| {
+                izip!(row, ys, alpha.powers())
+                    .map(|(p, &y, alpha_pow)| {
+                        alpha_pow * (-y + p) / (Challenge::from_base(x) - zeta)
+                    })
+                    .sum::<Challenge>()
+            })
+            .collect_vec();
+        // FRI consumes the reduced openings in bit-reversed order, like the committed LDE.
+        reverse_slice_index_bits(&mut expected);
+
+        assert_eq!(reduced_openings, vec![expected]);
+    }
+
+    #[test]
+    fn natural_order_ldes() {
+        type P = MyPcs;
+        for log_blowup in [1, 2] {
+            let (pcs, challenger) = get_pcs(log_blowup);
+            let natural_pcs = get_pcs(log_blowup).0.with_natural_order_ldes();
+
+            // Either way, a domain's evaluations are read back in its natural order.
+            let log_degree = 4;
+            let domain =
+                <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << log_degree);
+            let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, 3);
+            let lde = Dft::default()
+                .coset_lde_batch(evals.clone(), log_blowup, Val::GENERATOR)
+                .to_row_major_matrix();
+            let (_, data) =
+                <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals.clone())]);
+            let (_, natural_data) =
+                <P as Pcs<Challenge, Challenger>>::commit(&natural_pcs, vec![(domain, evals)]);
+            for log_n in [log_degree, log_degree + log_blowup] {
+                let subdomain = TwoAdicMultiplicativeCoset {
+                    log_n,
+                    shift: Val::GENERATOR,
+                };
+                let expected = lde
+                    .as_view()
+                    .vertically_strided(1 << (log_degree + log_blowup - log_n), 0)
+                    .to_row_major_matrix();
+                for (p, d) in [(&pcs, &data), (&natural_pcs, &natural_data)] {
+                    let evals = <P as Pcs<Challenge, Challenger>>::get_evaluations_on_domain(
+                        p, d, 0, subdomain,
+                    );
+                    assert_matrices_eq!(expected, evals, "log_n = {log_n}");
+                }
+            }
+
+            // Both orderings produce proofs which verify, with rounds of different heights.
+            let log_degrees_by_round: &[&[usize]] = &[&[4; 3], &[3, 3], &[5], &[2]];
+            do_test_fri_pcs(&(pcs, challenger.clone()), log_degrees_by_round);
+            do_test_fri_pcs(&(natural_pcs, challenger), log_degrees_by_round);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "LDEs committed in natural order must all have the same height")]
+    fn natural_order_ldes_reject_mixed_heights() {
+        type P = MyPcs;
+        let pcs = get_pcs(1).0.with_natural_order_ldes();
+        let evaluations = [3, 4]
+            .map(|log_degree| {
+                (
+                    <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                        &pcs,
+                        1 << log_degree,
+                    ),
+                    RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, 2),
+                )
+            })
+            .to_vec();
+        <P as Pcs<Challenge, Challenger>>::commit(&pcs, evaluations);
+    }
+
+    /// Commit to one table of each degree with its own PCS, and open them all at one point under
+    /// a single FRI proof.
+    fn open_aggregated(
+        pcss: &[MyPcs],
+        challenger: &Challenger,
+        log_degrees: &[usize],
+    ) -> (
+        Vec<ClaimsByRound>,
+        <MyPcs as Pcs<Challenge, Challenger>>::Proof,
+    ) {
+        let (domains, commits_and_data): (Vec<_>, Vec<_>) = izip!(pcss, log_degrees)
+            .map(|(pcs, &log_degree)| {
+                let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    pcs,
+                    1 << log_degree,
+                );
+                let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, 4);
+                let commit_and_data =
+                    <MyPcs as Pcs<Challenge, Challenger>>::commit(pcs, vec![(domain, evals)]);
+                (domain, commit_and_data)
+            })
+            .unzip();
+
+        let mut p_challenger = challenger.clone();
+        for (commit, _) in &commits_and_data {
+            p_challenger.observe(*commit);
+        }
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let groups = izip!(pcss, &commits_and_data)
+            .map(|(pcs, (_, data))| (pcs, vec![(data, vec![vec![zeta]])]))
+            .collect();
+        let (opened_values, proof) = MyPcs::open_aggregated(groups, &mut p_challenger);
+
+        let claims = izip!(domains, &commits_and_data, opened_values)
+            .map(|(domain, (commit, _), opened_values)| {
+                vec![(
+                    *commit,
+                    vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])],
+                )]
+            })
+            .collect();
+        (claims, proof)
+    }
+
+    fn verify_aggregated(
+        pcss: &[MyPcs],
+        challenger: &Challenger,
+        claims: Vec<ClaimsByRound>,
+        proof: &<MyPcs as Pcs<Challenge, Challenger>>::Proof,
+    ) -> Result<(), PcsError> {
+        let mut v_challenger = challenger.clone();
+        for group_claims in &claims {
+            v_challenger.observe(group_claims[0].0);
+        }
+        let _zeta: Challenge = v_challenger.sample_ext_element();
+        let groups = izip!(pcss, claims)
+            .map(|(pcs, claims)| (pcs.verifier(), claims))
+            .collect();
+        TwoAdicFriVerifier::verify_aggregated(groups, proof, &mut v_challenger)
+    }
+
+    #[test]
+    fn aggregate_tables_of_different_heights() {
+        let pcss = [get_pcs(1).0, get_pcs(1).0, get_pcs(1).0];
+        let (_, challenger) = get_pcs(1);
+        let (claims, proof) = open_aggregated(&pcss, &challenger, &[8, 10, 12]);
+
+        // One FRI instance, folding all the way down from the tallest LDE of height 2^13.
+        assert_eq!(proof.input_openings.len(), 3);
+        assert_eq!(proof.fri_proof.num_commit_phase_rounds(), 12);
+        assert_eq!(proof.fri_proof.num_queries(), 10);
+
+        verify_aggregated(&pcss, &challenger, claims, &proof).unwrap();
+    }
+
+    #[test]
+    fn aggregated_claims_out_of_order_fail() {
+        let pcss = [get_pcs(1).0, get_pcs(1).0, get_pcs(1).0];
+        let (_, challenger) = get_pcs(1);
+        let (mut claims, proof) = open_aggregated(&pcss, &challenger, &[8, 10, 12]);
+
+        claims.swap(0, 2);
+        assert!(verify_aggregated(&pcss, &challenger, claims, &proof).is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "aggregated PCSs must share their FRI parameters")]
+    fn aggregate_with_different_fri_params() {
+        let pcss = [get_pcs(1).0, get_pcs_with_queries(1, 20).0];
+        let (_, challenger) = get_pcs(1);
+        open_aggregated(&pcss, &challenger, &[8, 10]);
+    }
+
+    #[test]
+    fn verifier_only() {
+        let (pcs, challenger) = get_pcs(1);
+        let (claims, proof) = open_single(&pcs, &challenger, 5, 4);
+
+        // Build the verifier from scratch, without a DFT.
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let val_mmcs = ValMmcs::new(MyHash::new(perm.clone()), MyCompress::new(perm));
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 0,
             num_queries: 10,
             proof_of_work_bits: 8,
-            mmcs: challenge_mmcs,
+            omit_zero_bit_pow: false,
+            mmcs: ChallengeMmcs::new(val_mmcs.clone()),
         };
+        let verifier = TwoAdicFriVerifier::<Val, _, _>::new(val_mmcs, fri_config);
+
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(claims[0].0);
+        let _zeta: Challenge = v_challenger.sample_ext_element();
+        verifier.verify(claims, &proof, &mut v_challenger).unwrap();
+    }
+
+    /// Grinds with Keccak-256 instead of the challenger's permutation. The puzzle is a seed sampled
+    /// from the challenger, a witness is valid if hashing it after the seed gives `bits` leading
+    /// zero bits, and the witness is observed afterwards so that the query indices depend on it.
+    #[derive(Clone, Copy, Debug, Default)]
+    struct KeccakPow;
+
+    impl KeccakPow {
+        fn is_valid(seed: Val, bits: usize, witness: Val) -> bool {
+            let digest = Keccak256Hash.hash_iter(
+                [seed, witness]
+                    .into_iter()
+                    .flat_map(|x| x.as_canonical_u32().to_le_bytes()),
+            );
+            u64::from_be_bytes(digest[..8].try_into().unwrap()).leading_zeros() as usize >= bits
+        }
+    }
+
+    impl PowStrategy<Challenger> for KeccakPow {
+        fn grind(&self, challenger: &mut Challenger, bits: usize) -> Val {
+            let seed: Val = challenger.sample();
+            let witness = (0..Val::ORDER_U32)
+                .map(Val::from_canonical_u32)
+                .find(|&witness| Self::is_valid(seed, bits, witness))
+                .expect("failed to find witness");
+            challenger.observe(witness);
+            witness
+        }
+
+        fn check_witness(&self, challenger: &mut Challenger, bits: usize, witness: Val) -> bool {
+            let seed: Val = challenger.sample();
+            challenger.observe(witness);
+            Self::is_valid(seed, bits, witness)
+        }
+    }
+
+    #[test]
+    fn custom_pow_strategy() {
+        // Enough bits that a witness for one strategy won't pass the other by chance.
+        let (pcs, challenger) = get_pcs_with_params(1, 10, 16);
+        let keccak_pcs = get_pcs_with_params(1, 10, 16)
+            .0
+            .with_pow_strategy(KeccakPow);
+
+        let domain = <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 5, 4);
+        let (commit, data) =
+            Pcs::<Challenge, Challenger>::commit(&keccak_pcs, vec![(domain, evals)]);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opened_values, proof) =
+            keccak_pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+        let claims = vec![(
+            commit,
+            vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])],
+        )];
+
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commit);
+        let _zeta: Challenge = v_challenger.sample_ext_element();
+        keccak_pcs
+            .verify(claims.clone(), &proof, &mut v_challenger)
+            .unwrap();
+
+        // The commitment doesn't depend on the strategy, but the witness only satisfies the one it
+        // was ground with, in either direction.
+        let err = verify_single(&pcs, &challenger, claims, &proof).unwrap_err();
+        assert!(matches!(err, FriError::InvalidPowWitness), "{err:?}");
+
+        let (claims, proof) = open_single(&pcs, &challenger, 5, 4);
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(claims[0].0);
+        let _zeta: Challenge = v_challenger.sample_ext_element();
+        let err = keccak_pcs
+            .verify(claims, &proof, &mut v_challenger)
+            .unwrap_err();
+        assert!(matches!(err, FriError::InvalidPowWitness), "{err:?}");
+    }
+
+    #[test]
+    fn tampered_proofs_fail() {
+        let (pcs, challenger) = get_pcs(1);
+        let (claims, proof) = open_single(&pcs, &challenger, 5, 4);
+        verify_single(&pcs, &challenger, claims.clone(), &proof).unwrap();
+
+        // A wrong input value fails the batch's multi-proof, and also throws off the reduced
+        // opening of its query, which singles that query out.
+        let mut bad_values = proof.clone();
+        bad_values.input_openings[0].opened_values[3][0][0] += Val::ONE;
+        let err = verify_single(&pcs, &challenger, claims.clone(), &bad_values).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InputMmcsError {
+                    query: 3,
+                    batch: 0,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+        assert_eq!(err.query(), Some(3));
+        let message = err.to_string();
+        assert!(message.starts_with("query 3: input batch 0: "), "{message}");
+
+        let mut bad_siblings = proof.clone();
+        bad_siblings.input_openings[0].opening_proof[0][0] += Val::ONE;
+        let err = verify_single(&pcs, &challenger, claims.clone(), &bad_siblings).unwrap_err();
+        assert!(
+            matches!(err, FriError::InputBatchError { batch: 0, .. }),
+            "{err:?}"
+        );
+        assert!(err.to_string().starts_with("input batch 0: "), "{err}");
+
+        let mut bad_commit_phase = proof.clone();
+        tamper_fri_proof(&mut bad_commit_phase, |_, query_proofs, _| {
+            query_proofs[1].commit_phase_openings[2].sibling_value += Challenge::ONE;
+        });
+        let err = verify_single(&pcs, &challenger, claims.clone(), &bad_commit_phase).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::CommitPhaseMmcsError {
+                    query: 1,
+                    round: 2,
+                    log_height: 4,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+        let message = err.to_string();
+        assert!(message.starts_with("query 1: "), "{message}");
+        assert!(
+            message.contains("round 2 (codeword of height 2^4)"),
+            "{message}"
+        );
 
+        let mut bad_pow = proof.clone();
+        tamper_fri_proof(&mut bad_pow, |_, _, pow_witness| {
+            *pow_witness.as_mut().unwrap() += Val::ONE;
+        });
+        let err = verify_single(&pcs, &challenger, claims.clone(), &bad_pow).unwrap_err();
+        assert!(matches!(err, FriError::InvalidPowWitness), "{err:?}");
+
+        let mut missing_query = proof.clone();
+        missing_query.input_openings[0].opened_values.pop();
+        let err = verify_single(&pcs, &challenger, claims.clone(), &missing_query).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InvalidProofShape(ProofShapeError::BatchQueries {
+                    batch: 0,
+                    expected: 10,
+                    got: 9,
+                })
+            ),
+            "{err:?}"
+        );
+        assert_eq!(
+            err.to_string(),
+            "proof does not have the expected shape: input batch 0: openings for 9 queries, \
+             expected 10"
+        );
+
+        let mut missing_round = proof.clone();
+        tamper_fri_proof(&mut missing_round, |_, query_proofs, _| {
+            query_proofs[4].commit_phase_openings.pop();
+        });
+        let err = verify_single(&pcs, &challenger, claims.clone(), &missing_round).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InvalidProofShape(ProofShapeError::CommitPhaseOpenings {
+                    query: 4,
+                    expected: 5,
+                    got: 4,
+                })
+            ),
+            "{err:?}"
+        );
+        assert_eq!(err.query(), Some(4));
+        assert!(err.to_string().contains("query 4: "), "{err}");
+
+        let mut missing_matrix = proof;
+        missing_matrix.input_openings[0].opened_values[6].pop();
+        let err = verify_single(&pcs, &challenger, claims, &missing_matrix).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InvalidProofShape(ProofShapeError::BatchMatrices {
+                    batch: 0,
+                    query: 6,
+                    expected: 1,
+                    got: 0,
+                })
+            ),
+            "{err:?}"
+        );
+        assert!(
+            err.to_string()
+                .contains("query 6: input batch 0 opens 0 matrices"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn inconsistent_heights_and_widths_fail() {
+        let (pcs, challenger) = get_pcs(1);
+        let (claims, proof) = open_single(&pcs, &challenger, 5, 4);
+
+        let mut too_tall = claims.clone();
+        too_tall[0].1[0].0.log_n = 40;
+        let err = verify_single(&pcs, &challenger, too_tall, &proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::LogHeightTooLarge {
+                    batch: 0,
+                    matrix: 0,
+                    log_height: 41
+                }
+            ),
+            "{err:?}"
+        );
+
+        let mut too_short = claims.clone();
+        too_short[0].1[0].0.log_n = 3;
+        let err = verify_single(&pcs, &challenger, too_short, &proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::LogMaxHeightMismatch {
+                    claimed: 4,
+                    proof: 6
+                }
+            ),
+            "{err:?}"
+        );
+
+        let mut missing_round = proof.clone();
+        tamper_fri_proof(&mut missing_round, |commits, _, _| {
+            commits.pop();
+        });
+        let err = verify_single(&pcs, &challenger, claims.clone(), &missing_round).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::LogMaxHeightMismatch {
+                    claimed: 6,
+                    proof: 5
+                }
+            ),
+            "{err:?}"
+        );
+
+        let mut missing_value = claims.clone();
+        missing_value[0].1[0].1[0].1.pop();
+        let err = verify_single(&pcs, &challenger, missing_value, &proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::OpenedWidthMismatch {
+                    batch: 0,
+                    matrix: 0,
+                    query: 0,
+                    expected: 3,
+                    got: 4
+                }
+            ),
+            "{err:?}"
+        );
+
+        let mut short_row = proof;
+        short_row.input_openings[0].opened_values[7][0].pop();
+        let err = verify_single(&pcs, &challenger, claims, &short_row).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::OpenedWidthMismatch {
+                    query: 7,
+                    expected: 4,
+                    got: 3,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+        assert_eq!(
+            err.to_string(),
+            "query 7: input batch 0, matrix 0 has 3 opened values, expected 4"
+        );
+    }
+
+    /// Randomly mangle the claims and the proof, checking that verification fails cleanly
+    /// instead of panicking.
+    #[test]
+    fn malformed_inputs_never_panic() {
+        let (pcs, challenger) = get_pcs(1);
+        let (claims, proof) = open_single(&pcs, &challenger, 5, 4);
+        let mut rng = seeded_rng();
+
+        for _ in 0..200 {
+            let mut claims = claims.clone();
+            let mut proof = proof.clone();
+            match rng.gen_range(0..8) {
+                0 => {
+                    // Any height but the committed one.
+                    let log_n = rng.gen_range(0..63);
+                    claims[0].1[0].0.log_n = if log_n < 5 { log_n } else { log_n + 1 };
+                }
+                1 => claims[0].1[0].1[0].1.truncate(rng.gen_range(0..4)),
+                2 => claims[0].1[0].1[0].1.push(rng.gen()),
+                3 => {
+                    let query = rng.gen_range(0..10);
+                    let row = &mut proof.input_openings[0].opened_values[query][0];
+                    row.truncate(rng.gen_range(0..4));
+                }
+                4 => tamper_fri_proof(&mut proof, |commits, _, _| {
+                    commits.truncate(rng.gen_range(0..commits.len()));
+                }),
+                5 => tamper_fri_proof(&mut proof, |commits, _, _| {
+                    let extra = rng.gen_range(1..40);
+                    commits.extend(core::iter::repeat_n(commits[0], extra));
+                }),
+                6 => tamper_fri_proof(&mut proof, |_, query_proofs, _| {
+                    query_proofs.truncate(rng.gen_range(0..query_proofs.len()));
+                }),
+                _ => tamper_fri_proof(&mut proof, |_, query_proofs, _| {
+                    let query = rng.gen_range(0..10);
+                    let openings = &mut query_proofs[query].commit_phase_openings;
+                    openings.truncate(rng.gen_range(0..openings.len()));
+                }),
+            }
+            assert!(verify_single(&pcs, &challenger, claims, &proof).is_err());
+        }
+    }
+
+    /// Flip each byte of a serialized proof in turn. Whenever the result still deserializes, it
+    /// must be rejected by the verifier, without panicking.
+    #[test]
+    fn single_byte_flips_are_rejected() {
+        let (pcs, challenger) = get_pcs_with_queries(1, 2);
+        let (claims, proof) = open_single(&pcs, &challenger, 3, 2);
+        let bytes = postcard::to_allocvec(&proof).unwrap();
+        let mut rng = seeded_rng();
+
+        for i in 0..bytes.len() {
+            let mut flipped = bytes.clone();
+            flipped[i] ^= rng.gen_range(1..=u8::MAX);
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                postcard::from_bytes(&flipped)
+                    .ok()
+                    .map(|proof| verify_single(&pcs, &challenger, claims.clone(), &proof))
+            }));
+            match result {
+                Ok(None) => {}
+                Ok(Some(verified)) => {
+                    assert!(verified.is_err(), "accepted a proof with byte {i} flipped")
+                }
+                Err(_) => panic!("panicked on a proof with byte {i} flipped"),
+            }
+        }
+    }
+
+    #[test]
+    fn reopen_committed_data() {
+        type P = MyPcs;
+        let (pcs, challenger) = get_pcs(1);
+        let domain = <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 5, 4);
+        let (commit, data) = <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+        // Open the same data at a fresh point in each of two rounds.
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let openings = (0..2)
+            .map(|_| {
+                let zeta: Challenge = p_challenger.sample_ext_element();
+                let (opened_values, proof) =
+                    pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+                (zeta, opened_values[0][0][0].clone(), proof)
+            })
+            .collect_vec();
+        assert_ne!(openings[0].0, openings[1].0);
+
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commit);
+        for (zeta, values, proof) in openings {
+            assert_eq!(v_challenger.sample_ext_element::<Challenge>(), zeta);
+            let claims = vec![(commit, vec![(domain, vec![(zeta, values)])])];
+            pcs.verify(claims, &proof, &mut v_challenger)
+                .expect("each opening of the same data should verify");
+        }
+    }
+
+    #[test]
+    fn commit_and_observe() {
+        type P = MyPcs;
+        let (pcs, challenger) = get_pcs(1);
+        let domain = <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 5);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 5, 4);
+
+        // Forgetting to observe the commitment leaves the challenges independent of it.
+        let mut forgetful_challenger = challenger.clone();
+        let _ = <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals.clone())]);
+        let forgotten_zeta: Challenge = forgetful_challenger.sample_ext_element();
+
+        let mut p_challenger = challenger.clone();
+        let (commit, data) = pcs.commit_and_observe(vec![(domain, evals)], &mut p_challenger);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        assert_ne!(zeta, forgotten_zeta);
+        let (opened_values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+
+        let claims = vec![(
+            commit,
+            vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])],
+        )];
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commit);
+        assert_eq!(v_challenger.sample_ext_element::<Challenge>(), zeta);
+        pcs.verify(claims, &proof, &mut v_challenger)
+            .expect("the verifier should agree with a prover that observed its commitment");
+    }
+
+    #[test]
+    fn open_subset_of_matrices() {
+        type P = MyPcs;
+        let (pcs, challenger) = get_pcs(1);
+        // The skipped matrix is alone at its height, so that height's reduced opening is zero.
+        let domains_and_evals = [4, 3, 5]
+            .into_iter()
+            .map(|log_degree| {
+                let domain = <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+                    &pcs,
+                    1 << log_degree,
+                );
+                let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, 3);
+                (domain, evals)
+            })
+            .collect_vec();
+        let domains = domains_and_evals.iter().map(|(d, _)| *d).collect_vec();
+        let (commit, data) = <P as Pcs<Challenge, Challenger>>::commit(&pcs, domains_and_evals);
+
+        // Open only matrices 0 and 2.
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opened_values, proof) = pcs.open(
+            vec![(&data, vec![vec![zeta], vec![], vec![zeta]])],
+            &mut p_challenger,
+        );
+        assert!(opened_values[0][1].is_empty());
+
+        let claims = vec![(
+            commit,
+            izip!(domains, &opened_values[0])
+                .map(|(domain, values)| {
+                    let points_and_values = values.iter().map(|v| (zeta, v.clone())).collect();
+                    (domain, points_and_values)
+                })
+                .collect(),
+        )];
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commit);
+        let _zeta: Challenge = v_challenger.sample_ext_element();
+        pcs.verify(claims, &proof, &mut v_challenger)
+            .expect("opening a subset of the committed matrices should verify");
+    }
+
+    #[test]
+    fn independent_commit_phase_mmcs() {
+        // FRI commits to its layers with a wider permutation and shorter digests than the inputs.
+        type FriPerm = Poseidon2BabyBear<24>;
+        type FriValMmcs = PermMmcs<Val, FriPerm, 24, 16, 4>;
+        type FriMmcs = ExtensionMmcs<Val, Challenge, FriValMmcs>;
+        type IndependentPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, FriMmcs>;
+
+        let mut rng = seeded_rng();
+        let (val_mmcs, challenger) = perm_mmcs_and_challenger(Perm::new_from_rng_128(&mut rng));
+        let fri_perm = FriPerm::new_from_rng_128(&mut rng);
+        let fri_val_mmcs = MerkleTreeMmcs::new(
+            PaddingFreeSponge::new(fri_perm.clone()),
+            TruncatedPermutation::new(fri_perm),
+        );
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            omit_zero_bit_pow: false,
+            mmcs: FriMmcs::new(fri_val_mmcs),
+        };
+        let pcs = IndependentPcs::new(Dft::default(), val_mmcs, fri_config);
+        do_test_fri_pcs(&(pcs, challenger), &[&[3, 4], &[5]]);
+    }
+
+    #[test]
+    fn final_poly_of_higher_degree() {
+        for log_final_poly_len in 0..3 {
+            let perm = Perm::new_from_rng_128(&mut seeded_rng());
+            let (val_mmcs, challenger) = perm_mmcs_and_challenger(perm);
+            let fri_config = FriConfig {
+                log_blowup: 1,
+                log_final_poly_len,
+                num_queries: 10,
+                proof_of_work_bits: 8,
+                omit_zero_bit_pow: false,
+                mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+            };
+            let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
+
+            let (claims, proof) = open_single(&pcs, &challenger, 5, 3);
+            let fri_proof = proof.fri_proof();
+            assert_eq!(fri_proof.final_poly_len(), 1 << log_final_poly_len);
+            assert_eq!(fri_proof.num_commit_phase_rounds(), 5 - log_final_poly_len);
+            verify_single(&pcs, &challenger, claims, &proof).unwrap();
+
+            do_test_fri_pcs(&(pcs, challenger), &[&[3, 4], &[5]]);
+        }
+    }
+
+    #[test]
+    fn inputs_shorter_than_final_codeword_rejected() {
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let (val_mmcs, challenger) = perm_mmcs_and_challenger(perm);
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 2,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            omit_zero_bit_pow: false,
+            mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+        };
         let pcs = MyPcs::new(Dft::default(), val_mmcs, fri_config);
-        (pcs, Challenger::new(perm.clone()))
+        let small_domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 2);
+
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 2, 3);
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(small_domain, evals)])
+        }));
+        assert!(result.is_err());
+
+        let (mut claims, proof) = open_single(&pcs, &challenger, 5, 3);
+        claims[0].1.push((small_domain, vec![]));
+        let err = verify_single(&pcs, &challenger, claims, &proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::LogHeightBelowFinalHeight {
+                    batch: 0,
+                    matrix: 1,
+                    log_height: 2,
+                }
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn more_queries_than_positions() {
+        // An LDE of height 4, queried 10 times.
+        let (pcs, challenger) = get_pcs_with_queries(1, 10);
+        let (claims, proof) = open_single(&pcs, &challenger, 1, 3);
+
+        let opened_rows = &proof.input_openings()[0].opened_values;
+        assert_eq!(opened_rows.len(), 10);
+        assert!(opened_rows.iter().unique().count() <= 4);
+        verify_single(&pcs, &challenger, claims, &proof).unwrap();
+
+        // The repeats are part of the transcript, so proving again gives the same proof.
+        let (_, again) = open_single(&pcs, &challenger, 1, 3);
+        assert_eq!(
+            postcard::to_allocvec(&proof).unwrap(),
+            postcard::to_allocvec(&again).unwrap()
+        );
+    }
+
+    /// An LDE backend that implements only `CosetLde`, not `TwoAdicSubgroupDft`.
+    #[derive(Clone, Default)]
+    struct LdeOnly(Dft);
+
+    impl p3_dft::CosetLde<Val> for LdeOnly {
+        type Evaluations = <Dft as TwoAdicSubgroupDft<Val>>::Evaluations;
+
+        fn coset_lde_batch(
+            &self,
+            mat: RowMajorMatrix<Val>,
+            added_bits: usize,
+            shift: Val,
+        ) -> Self::Evaluations {
+            TwoAdicSubgroupDft::coset_lde_batch(&self.0, mat, added_bits, shift)
+        }
+    }
+
+    #[test]
+    fn pcs_over_coset_lde() {
+        type P = MyPcs;
+        type LdePcs = TwoAdicFriPcs<Val, LdeOnly, ValMmcs, ChallengeMmcs>;
+
+        let (pcs, challenger) = get_pcs(1);
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let (val_mmcs, _) = perm_mmcs_and_challenger(perm);
+        let fri_config = FriConfig {
+            log_blowup: 1,
+            log_final_poly_len: 0,
+            num_queries: 10,
+            proof_of_work_bits: 8,
+            omit_zero_bit_pow: false,
+            mmcs: ChallengeMmcs::new(val_mmcs.clone()),
+        };
+        let lde_pcs = LdePcs::new(LdeOnly::default(), val_mmcs, fri_config);
+
+        let domain = <P as Pcs<Challenge, Challenger>>::natural_domain_for_degree(&pcs, 1 << 4);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 4, 3);
+        let (commit, _) =
+            <P as Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals.clone())]);
+        let (lde_commit, _) =
+            <LdePcs as Pcs<Challenge, Challenger>>::commit(&lde_pcs, vec![(domain, evals)]);
+        assert_eq!(commit, lde_commit);
+
+        do_test_fri_pcs(&(lde_pcs, challenger), &[&[3, 4], &[5]]);
+    }
+
+    #[test]
+    fn proof_stats() {
+        let (pcs, challenger) = get_pcs(1);
+        let (_, proof) = open_single(&pcs, &challenger, 4, 3);
+        // Folding from height 2^5 down to 2^log_blowup takes 4 rounds, each opening one sibling
+        // per query.
+        assert_eq!(
+            proof.stats(),
+            ProofStats {
+                num_batches: 1,
+                num_matrices: 1,
+                num_queries: 10,
+                num_commit_phase_rounds: 4,
+                opened_rows: 10,
+                opened_values: 30,
+                opened_siblings: 40,
+                has_deep_quotient: false,
+            }
+        );
+    }
+
+    #[test]
+    fn committed_deep_quotient() {
+        let (pcs, challenger) = get_pcs(1);
+        let quotient_pcs = get_pcs(1).0.with_committed_deep_quotient();
+
+        let width = 512;
+        let (claims, proof) = open_single(&pcs, &challenger, 5, width);
+        let (quotient_claims, quotient_proof) = open_single(&quotient_pcs, &challenger, 5, width);
+        assert!(quotient_proof.deep_quotient.is_some());
+        assert!(quotient_proof.stats().has_deep_quotient);
+        verify_single(
+            &quotient_pcs,
+            &challenger,
+            quotient_claims.clone(),
+            &quotient_proof,
+        )
+        .unwrap();
+
+        // The two modes must not accept each other's proofs.
+        let err =
+            verify_single(&pcs, &challenger, quotient_claims.clone(), &quotient_proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InvalidProofShape(ProofShapeError::DeepQuotientPresence {
+                    expected: false
+                })
+            ),
+            "{err:?}"
+        );
+        let err = verify_single(&quotient_pcs, &challenger, claims, &proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InvalidProofShape(ProofShapeError::DeepQuotientPresence {
+                    expected: true
+                })
+            ),
+            "{err:?}"
+        );
+
+        // The quotient adds a constant amount of data per query, regardless of the width.
+        let size = postcard::to_allocvec(&proof).unwrap().len();
+        let quotient_size = postcard::to_allocvec(&quotient_proof).unwrap().len();
+        let input_openings_size = postcard::to_allocvec(&proof.input_openings).unwrap().len();
+        assert!(quotient_size > size);
+        assert!(quotient_size - size < input_openings_size / 4);
+
+        // FRI runs on the committed quotient, so a tampered quotient fails FRI itself.
+        let mut bad_quotient = quotient_proof.clone();
+        bad_quotient
+            .deep_quotient
+            .as_mut()
+            .unwrap()
+            .opening
+            .opened_values[0][0][0] += Challenge::ONE;
+        verify_single(
+            &quotient_pcs,
+            &challenger,
+            quotient_claims.clone(),
+            &bad_quotient,
+        )
+        .unwrap_err();
+
+        // Claims that disagree with the committed quotient pass FRI, and are caught when the input
+        // openings are checked against the quotient.
+        let mut bad_claims = quotient_claims.clone();
+        bad_claims[0].1[0].1[0].1[0] += Challenge::ONE;
+        let err =
+            verify_single(&quotient_pcs, &challenger, bad_claims, &quotient_proof).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::DeepQuotientMismatch {
+                    query: 0,
+                    log_height: 6
+                }
+            ),
+            "{err:?}"
+        );
+        assert_eq!(
+            err.to_string(),
+            "query 0: committed DEEP quotient of height 2^6 does not match the input openings"
+        );
+
+        // A tampered input opening is singled out by its disagreement with the quotient.
+        let mut bad_opening = quotient_proof;
+        bad_opening.input_openings[0].opened_values[2][0][0] += Val::ONE;
+        let err =
+            verify_single(&quotient_pcs, &challenger, quotient_claims, &bad_opening).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                FriError::InputMmcsError {
+                    query: 2,
+                    batch: 0,
+                    ..
+                }
+            ),
+            "{err:?}"
+        );
+    }
+}
+
+// The generic tests again, over other fields.
+mod koalabear_fri_pcs {
+    use p3_koala_bear::{KoalaBear, Poseidon2KoalaBear};
+
+    use super::*;
+
+    type Val = KoalaBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+    type Perm = Poseidon2KoalaBear<16>;
+
+    #[allow(clippy::type_complexity)]
+    fn get_pcs(
+        log_blowup: usize,
+    ) -> (
+        TestFriPcs<Val, Challenge, PermMmcs<Val, Perm, 16, 8, 8>>,
+        DuplexChallenger<Val, Perm, 16, 8>,
+    ) {
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let (val_mmcs, challenger) = perm_mmcs_and_challenger(perm);
+        make_test_fri_pcs(val_mmcs, challenger, log_blowup, 10, 8)
     }
 
     mod blowup_1 {
@@ -195,9 +1826,179 @@ mod babybear_fri_pcs {
     }
 }
 
-mod m31_fri_pcs {
-    use std::marker::PhantomData;
+mod goldilocks_fri_pcs {
+    use p3_goldilocks::{Goldilocks, Poseidon2Goldilocks};
+
+    use super::*;
+
+    type Val = Goldilocks;
+    type Challenge = BinomialExtensionField<Val, 2>;
+    type Perm = Poseidon2Goldilocks<8>;
+
+    #[allow(clippy::type_complexity)]
+    fn get_pcs(
+        log_blowup: usize,
+    ) -> (
+        TestFriPcs<Val, Challenge, PermMmcs<Val, Perm, 8, 4, 4>>,
+        DuplexChallenger<Val, Perm, 8, 4>,
+    ) {
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let (val_mmcs, challenger) = perm_mmcs_and_challenger(perm);
+        make_test_fri_pcs(val_mmcs, challenger, log_blowup, 10, 8)
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1));
+    }
+    mod blowup_2 {
+        make_tests_for_pcs!(super::get_pcs(2));
+    }
+}
+
+mod babybear_hiding_fri_pcs {
+    use p3_matrix::Matrix;
+    use p3_merkle_tree::MerkleTreeHidingMmcs;
+
+    use super::*;
+
+    type Val = BabyBear;
+    type Challenge = BinomialExtensionField<Val, 4>;
+
+    type Perm = Poseidon2BabyBear<16>;
+    type MyHash = PaddingFreeSponge<Perm, 16, 8, 8>;
+    type MyCompress = TruncatedPermutation<Perm, 2, 8, 16>;
+
+    type ValMmcs = MerkleTreeHidingMmcs<
+        <Val as Field>::Packing,
+        <Val as Field>::Packing,
+        MyHash,
+        MyCompress,
+        ChaCha20Rng,
+        8,
+        4,
+    >;
+    type ChallengeMmcs = ExtensionMmcs<Val, Challenge, ValMmcs>;
 
+    type Dft = Radix2DitParallel<Val>;
+    type Challenger = DuplexChallenger<Val, Perm, 16, 8>;
+    type MyPcs = TwoAdicFriPcs<Val, Dft, ValMmcs, ChallengeMmcs>;
+
+    /// The same PCS for every `salt_seed`, except for the randomness used to salt leaves.
+    fn get_pcs(log_blowup: usize, salt_seed: u64) -> (MyPcs, Challenger) {
+        get_pcs_with_pow_bits(log_blowup, salt_seed, 8)
+    }
+
+    fn get_pcs_with_pow_bits(
+        log_blowup: usize,
+        salt_seed: u64,
+        proof_of_work_bits: usize,
+    ) -> (MyPcs, Challenger) {
+        let perm = Perm::new_from_rng_128(&mut seeded_rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm.clone());
+
+        let val_mmcs = ValMmcs::new(hash, compress, ChaCha20Rng::seed_from_u64(salt_seed));
+        make_test_fri_pcs(
+            val_mmcs,
+            Challenger::new(perm),
+            log_blowup,
+            10,
+            proof_of_work_bits,
+        )
+    }
+
+    mod blowup_1 {
+        make_tests_for_pcs!(super::get_pcs(1, 1));
+    }
+    mod blowup_2 {
+        make_tests_for_pcs!(super::get_pcs(2, 1));
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn commit(
+        pcs: &MyPcs,
+        evals: RowMajorMatrix<Val>,
+    ) -> (
+        TwoAdicMultiplicativeCoset<Val>,
+        <MyPcs as Pcs<Challenge, Challenger>>::Commitment,
+        <MyPcs as Pcs<Challenge, Challenger>>::ProverData,
+    ) {
+        let domain =
+            <MyPcs as Pcs<Challenge, Challenger>>::natural_domain_for_degree(pcs, evals.height());
+        let (commit, data) =
+            <MyPcs as Pcs<Challenge, Challenger>>::commit(pcs, vec![(domain, evals)]);
+        (domain, commit, data)
+    }
+
+    #[test]
+    fn commitments_depend_on_salts() {
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 5, 4);
+        let (_, commit_1, _) = commit(&get_pcs(1, 1).0, evals.clone());
+        let (_, commit_1_again, _) = commit(&get_pcs(1, 1).0, evals.clone());
+        let (_, commit_2, _) = commit(&get_pcs(1, 2).0, evals);
+        assert_eq!(commit_1, commit_1_again);
+        assert_ne!(commit_1, commit_2);
+    }
+
+    #[test]
+    fn tampered_salt_fails() {
+        let (pcs, challenger) = get_pcs(1, 1);
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 5, 4);
+        let (domain, commit, data) = commit(&pcs, evals);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opened_values, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut p_challenger);
+        let claims = vec![(
+            commit,
+            vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])],
+        )];
+
+        let verify = |proof: &<MyPcs as Pcs<Challenge, Challenger>>::Proof| {
+            let mut v_challenger = challenger.clone();
+            v_challenger.observe(commit);
+            let _zeta: Challenge = v_challenger.sample_ext_element();
+            pcs.verify(claims.clone(), proof, &mut v_challenger)
+        };
+        verify(&proof).unwrap();
+
+        // The salts of query 2 are part of the input multi-proof.
+        let mut bad_salt = proof;
+        bad_salt.input_openings[0].opening_proof.0[2][0][0] += Val::ONE;
+        let err = verify(&bad_salt).unwrap_err();
+        assert!(
+            matches!(err, FriError::InputBatchError { batch: 0, .. }),
+            "{err:?}"
+        );
+    }
+
+    /// Queries are answered in parallel, through an MMCS whose RNG is shared between threads, but
+    /// the proof doesn't depend on how many there are. Grinding is left out, since it takes
+    /// whichever witness a thread finds first.
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn proof_independent_of_thread_count() {
+        let prove_with_threads = |num_threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let (pcs, mut challenger) = get_pcs_with_pow_bits(1, 1, 0);
+                let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << 8, 16);
+                let (_, commit, data) = commit(&pcs, evals);
+                challenger.observe(commit);
+                let zeta: Challenge = challenger.sample_ext_element();
+                let (_, proof) = pcs.open(vec![(&data, vec![vec![zeta]])], &mut challenger);
+                postcard::to_allocvec(&proof).unwrap()
+            })
+        };
+        assert_eq!(prove_with_threads(1), prove_with_threads(4));
+    }
+}
+
+mod m31_fri_pcs {
     use p3_challenger::{HashChallenger, SerializingChallenger32};
     use p3_circle::CirclePcs;
     use p3_keccak::Keccak256Hash;
@@ -223,6 +2024,10 @@ mod m31_fri_pcs {
     type Pcs = CirclePcs<Val, ValMmcs, ChallengeMmcs>;
 
     fn get_pcs(log_blowup: usize) -> (Pcs, Challenger) {
+        get_pcs_with_arity(log_blowup, 1)
+    }
+
+    fn get_pcs_with_arity(log_blowup: usize, log_folding_arity: usize) -> (Pcs, Challenger) {
         let byte_hash = ByteHash {};
         let field_hash = FieldHash::new(byte_hash);
         let compress = MyCompress::new(byte_hash);
@@ -230,15 +2035,13 @@ mod m31_fri_pcs {
         let challenge_mmcs = ChallengeMmcs::new(val_mmcs.clone());
         let fri_config = FriConfig {
             log_blowup,
+            log_final_poly_len: 0,
             num_queries: 10,
             proof_of_work_bits: 8,
+            omit_zero_bit_pow: false,
             mmcs: challenge_mmcs,
         };
-        let pcs = Pcs {
-            mmcs: val_mmcs,
-            fri_config,
-            _phantom: PhantomData,
-        };
+        let pcs = Pcs::new(val_mmcs, fri_config).with_log_folding_arity(log_folding_arity);
         (pcs, Challenger::from_hasher(vec![], byte_hash))
     }
 
@@ -248,4 +2051,51 @@ mod m31_fri_pcs {
     mod blowup_2 {
         make_tests_for_pcs!(super::get_pcs(2));
     }
+    mod blowup_1_arity_4 {
+        make_tests_for_pcs!(super::get_pcs_with_arity(1, 2));
+    }
+    mod blowup_2_arity_4 {
+        make_tests_for_pcs!(super::get_pcs_with_arity(2, 2));
+    }
+
+    #[test]
+    fn proof_survives_serialization() {
+        type P = Pcs;
+        let (pcs, challenger) = get_pcs(1);
+        let log_degree = 6;
+        let domain = <P as p3_commit::Pcs<Challenge, Challenger>>::natural_domain_for_degree(
+            &pcs,
+            1 << log_degree,
+        );
+        let evals = RowMajorMatrix::<Val>::rand(&mut seeded_rng(), 1 << log_degree, 3);
+        let (commit, data) =
+            <P as p3_commit::Pcs<Challenge, Challenger>>::commit(&pcs, vec![(domain, evals)]);
+
+        let mut p_challenger = challenger.clone();
+        p_challenger.observe(commit);
+        let zeta: Challenge = p_challenger.sample_ext_element();
+        let (opened_values, proof) = <P as p3_commit::Pcs<Challenge, Challenger>>::open(
+            &pcs,
+            vec![(&data, vec![vec![zeta]])],
+            &mut p_challenger,
+        );
+
+        let bytes = postcard::to_allocvec(&proof).unwrap();
+        let proof: <P as p3_commit::Pcs<Challenge, Challenger>>::Proof =
+            postcard::from_bytes(&bytes).unwrap();
+
+        let mut v_challenger = challenger.clone();
+        v_challenger.observe(commit);
+        let _zeta: Challenge = v_challenger.sample_ext_element();
+        <P as p3_commit::Pcs<Challenge, Challenger>>::verify(
+            &pcs,
+            vec![(
+                commit,
+                vec![(domain, vec![(zeta, opened_values[0][0][0].clone())])],
+            )],
+            &proof,
+            &mut v_challenger,
+        )
+        .unwrap();
+    }
 }
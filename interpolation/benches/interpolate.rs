@@ -0,0 +1,70 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use p3_baby_bear::BabyBear;
+use p3_field::extension::BinomialExtensionField;
+use p3_field::Field;
+use p3_interpolation::{
+    coset_interpolation_scalars, coset_interpolation_scalars_with_weights, interpolate_coset,
+    subgroup_barycentric_weights,
+};
+use p3_matrix::dense::RowMajorMatrix;
+use rand::{random, thread_rng};
+
+type F = BabyBear;
+type EF = BinomialExtensionField<F, 4>;
+
+/// Opening an LDE at an out-of-domain point, as the PCS does, on the coset shifted by the field
+/// generator.
+fn bench_interpolate_coset(c: &mut Criterion) {
+    let mut g = c.benchmark_group("interpolate_coset");
+    g.sample_size(10);
+    for log_height in [16, 20] {
+        let evals = RowMajorMatrix::<F>::rand(&mut thread_rng(), 1 << log_height, 8);
+        let point: EF = random();
+        g.bench_with_input(
+            BenchmarkId::from_parameter(log_height),
+            &evals,
+            |b, evals| b.iter(|| interpolate_coset(evals, F::GENERATOR, point)),
+        );
+    }
+}
+
+/// Computing interpolation scalars at many points on domains of one size, as a prover opening many
+/// matrices of the same height does, with and without the subgroup's weights computed once.
+fn bench_scalars_many_points(c: &mut Criterion) {
+    const NUM_POINTS: usize = 16;
+    let mut g = c.benchmark_group("coset_interpolation_scalars");
+    g.sample_size(10);
+    for log_height in [16, 20] {
+        let points: Vec<EF> = (0..NUM_POINTS).map(|_| random()).collect();
+        g.bench_with_input(
+            BenchmarkId::new("recomputed_weights", log_height),
+            &points,
+            |b, points| {
+                b.iter(|| {
+                    points
+                        .iter()
+                        .map(|&z| coset_interpolation_scalars(log_height, F::GENERATOR, z))
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+        g.bench_with_input(
+            BenchmarkId::new("cached_weights", log_height),
+            &points,
+            |b, points| {
+                b.iter(|| {
+                    let weights = subgroup_barycentric_weights::<F>(log_height);
+                    points
+                        .iter()
+                        .map(|&z| {
+                            coset_interpolation_scalars_with_weights(&weights, F::GENERATOR, z)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            },
+        );
+    }
+}
+
+criterion_group!(benches, bench_interpolate_coset, bench_scalars_many_points);
+criterion_main!(benches);
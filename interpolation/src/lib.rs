@@ -4,11 +4,12 @@
 
 extern crate alloc;
 
+use alloc::vec;
 use alloc::vec::Vec;
 
 use p3_field::{
-    batch_multiplicative_inverse, cyclic_subgroup_coset_known_order, scale_vec,
-    two_adic_coset_zerofier, ExtensionField, TwoAdicField,
+    batch_multiplicative_inverse, scale_vec, two_adic_coset_zerofier, ExtensionField, Field,
+    TwoAdicField,
 };
 use p3_matrix::Matrix;
 use p3_util::log2_strict_usize;
@@ -36,41 +37,136 @@ where
     EF: ExtensionField<F> + TwoAdicField,
     Mat: Matrix<F>,
 {
-    // Slight variation of this approach: https://hackmd.io/@vbuterin/barycentric_evaluation
+    let log_height = log2_strict_usize(coset_evals.height());
+    let col_scale = coset_interpolation_scalars(log_height, shift, point);
+    interpolate_coset_with_scalars(coset_evals, shift, point, &col_scale)
+}
 
-    let height = coset_evals.height();
-    let log_height = log2_strict_usize(height);
-    let g = F::two_adic_generator(log_height);
+/// Compute `g^i / (point - shift g^i)` for each element `shift g^i` of the coset of size
+/// `2^log_height`.
+///
+/// These are the only part of `interpolate_coset` which needs a batch inversion, and they depend
+/// on the coset and point but not the evaluations, so they can be shared between matrices.
+pub fn coset_interpolation_scalars<F, EF>(log_height: usize, shift: F, point: EF) -> Vec<EF>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F>,
+{
+    coset_interpolation_scalars_with_weights(
+        &subgroup_barycentric_weights(log_height),
+        shift,
+        point,
+    )
+}
 
-    let diffs: Vec<EF> = cyclic_subgroup_coset_known_order(g, shift, height)
-        .map(|subgroup_i| point - subgroup_i)
-        .collect();
-    let diff_invs = batch_multiplicative_inverse(&diffs);
+/// The powers `g^-i` of the inverse generator of the subgroup of size `2^log_height`.
+///
+/// These are the part of the barycentric weights that depends only on the subgroup, so a prover
+/// opening many points on domains of the same size can compute them once, and pass them to
+/// `coset_interpolation_scalars_with_weights` for each point.
+pub fn subgroup_barycentric_weights<F: TwoAdicField>(log_height: usize) -> Vec<F> {
+    F::two_adic_generator(log_height)
+        .inverse()
+        .powers()
+        .take(1 << log_height)
+        .collect()
+}
 
-    // TODO: Make this faster
+/// Like `coset_interpolation_scalars`, but with the subgroup's weights precomputed by
+/// `subgroup_barycentric_weights`.
+pub fn coset_interpolation_scalars_with_weights<F, EF>(
+    weights: &[F],
+    shift: F,
+    point: EF,
+) -> Vec<EF>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F>,
+{
+    // Slight variation of this approach: https://hackmd.io/@vbuterin/barycentric_evaluation
 
-    let col_scale: Vec<_> = g
-        .powers()
-        .zip(diff_invs)
-        .map(|(sg, diff_inv)| diff_inv * sg)
+    // Since g^i / (point - shift g^i) = 1 / (point g^-i - shift), stepping through the inverse
+    // powers of g spares both the shift multiplication per coset point and the multiplication by
+    // g^i after inverting. Nothing further simplifies for any particular shift, such as the
+    // field generator used for LDEs, so all shifts share this path.
+    let diffs: Vec<EF> = weights
+        .iter()
+        .map(|&g_inv_i| point * g_inv_i - shift)
         .collect();
+    batch_multiplicative_inverse(&diffs)
+}
 
-    let sum = coset_evals.columnwise_dot_product(&col_scale);
+/// Like `interpolate_coset`, but with `col_scale` precomputed by `coset_interpolation_scalars`.
+pub fn interpolate_coset_with_scalars<F, EF, Mat>(
+    coset_evals: &Mat,
+    shift: F,
+    point: EF,
+    col_scale: &[EF],
+) -> Vec<EF>
+where
+    F: TwoAdicField,
+    EF: ExtensionField<F> + TwoAdicField,
+    Mat: Matrix<F>,
+{
+    let height = coset_evals.height();
+    let log_height = log2_strict_usize(height);
+    debug_assert_eq!(col_scale.len(), height);
+
+    let sum = coset_evals.columnwise_dot_product(col_scale);
 
     let zerofier = two_adic_coset_zerofier::<EF>(log_height, EF::from_base(shift), point);
     let denominator = F::from_canonical_usize(height) * shift.exp_u64(height as u64 - 1);
     scale_vec(zerofier * denominator.inverse(), sum)
 }
 
+/// Evaluate the polynomial with the given coefficients, constant term first, at `point` using
+/// Horner's method.
+///
+/// Unlike the interpolation functions above, this needs no structure in the domain, which makes it
+/// handy for cross-checking them, e.g. against the inverse DFT of a committed matrix.
+pub fn eval_at_point<F, EF>(coeffs: &[F], point: EF) -> EF
+where
+    F: Field,
+    EF: ExtensionField<F>,
+{
+    coeffs
+        .iter()
+        .rev()
+        .fold(EF::ZERO, |acc, &coeff| acc * point + coeff)
+}
+
+/// Like `eval_at_point`, but for each column of `coeffs`, whose row `i` holds the coefficients of
+/// `x^i`.
+pub fn eval_cols_at_point<F, EF, Mat>(coeffs: &Mat, point: EF) -> Vec<EF>
+where
+    F: Field,
+    EF: ExtensionField<F>,
+    Mat: Matrix<F>,
+{
+    let mut evals = vec![EF::ZERO; coeffs.width()];
+    for r in (0..coeffs.height()).rev() {
+        for (eval, coeff) in evals.iter_mut().zip(coeffs.row(r)) {
+            *eval = *eval * point + coeff;
+        }
+    }
+    evals
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use alloc::vec::Vec;
 
     use p3_baby_bear::BabyBear;
-    use p3_field::{AbstractField, Field};
+    use p3_field::{AbstractExtensionField, AbstractField, Field, TwoAdicField};
     use p3_matrix::dense::RowMajorMatrix;
 
-    use crate::{interpolate_coset, interpolate_subgroup};
+    use p3_field::extension::BinomialExtensionField;
+
+    use crate::{
+        coset_interpolation_scalars, coset_interpolation_scalars_with_weights, eval_at_point,
+        eval_cols_at_point, interpolate_coset, interpolate_subgroup, subgroup_barycentric_weights,
+    };
 
     #[test]
     fn test_interpolate_subgroup() {
@@ -100,4 +196,56 @@ mod tests {
         let result = interpolate_coset(&evals_mat, shift, point);
         assert_eq!(result, vec![F::from_canonical_u32(10203)]);
     }
+
+    #[test]
+    fn test_coset_interpolation_scalars() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let point = EF::from_base_slice(&[1, 2, 3, 4].map(F::from_canonical_u32));
+        for shift in [F::ONE, F::GENERATOR, F::from_canonical_u32(12345)] {
+            for log_height in 0..6 {
+                // The definition, g^i / (point - shift g^i), computed directly.
+                let g = F::two_adic_generator(log_height);
+                let expected: Vec<EF> = g
+                    .powers()
+                    .take(1 << log_height)
+                    .map(|g_i| (point - shift * g_i).inverse() * g_i)
+                    .collect();
+                assert_eq!(
+                    coset_interpolation_scalars(log_height, shift, point),
+                    expected,
+                    "shift = {shift}, log_height = {log_height}"
+                );
+                let weights = subgroup_barycentric_weights(log_height);
+                assert_eq!(
+                    coset_interpolation_scalars_with_weights(&weights, shift, point),
+                    expected,
+                    "shift = {shift}, log_height = {log_height}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_eval_at_point() {
+        // x^2 + 2 x + 3
+        type F = BabyBear;
+        type EF = BinomialExtensionField<F, 4>;
+        let coeffs = [3, 2, 1].map(F::from_canonical_u32);
+        let point = F::from_canonical_u32(100);
+        assert_eq!(eval_at_point(&coeffs, point), F::from_canonical_u32(10203));
+        assert_eq!(
+            eval_at_point(&coeffs, EF::from_base(point)),
+            EF::from_canonical_u32(10203)
+        );
+        assert_eq!(eval_at_point::<F, F>(&[], point), F::ZERO);
+
+        // The same polynomial next to 5 x.
+        let coeffs_mat =
+            RowMajorMatrix::new([3, 0, 2, 5, 1, 0].map(F::from_canonical_u32).to_vec(), 2);
+        assert_eq!(
+            eval_cols_at_point(&coeffs_mat, point),
+            vec![F::from_canonical_u32(10203), F::from_canonical_u32(500)]
+        );
+    }
 }
@@ -55,8 +55,10 @@ fn main() -> Result<(), impl Debug> {
 
     let fri_config = FriConfig {
         log_blowup: 1,
+        log_final_poly_len: 0,
         num_queries: 100,
         proof_of_work_bits: 16,
+        omit_zero_bit_pow: false,
         mmcs: challenge_mmcs,
     };
     type Dft = RecursiveDft<Val>;
@@ -0,0 +1,59 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use p3_baby_bear::BabyBear;
+use p3_field::AbstractField;
+use p3_matrix::bitrev::BitReversalPerm;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::Matrix;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+
+type F = BabyBear;
+
+fn composed_views(c: &mut Criterion) {
+    let mut rng = ChaChaRng::seed_from_u64(0);
+    let log_rows = 20;
+    // Every other row of a matrix twice as tall, then bit-reversed, leaving 2^20 rows.
+    let m = RowMajorMatrix::<F>::rand_nonzero(&mut rng, 2 << log_rows, 8);
+    let stack = || BitReversalPerm::new_view(m.as_view().vertically_strided(2, 1));
+    let composed = stack().compose();
+    let indices: Vec<usize> = (0..1 << 16)
+        .map(|_| rng.gen_range(0..1 << log_rows))
+        .collect();
+
+    let mut group = c.benchmark_group("composed_views");
+    group.sample_size(10);
+
+    group.bench_function("random_access/stacked", |b| {
+        let view = stack();
+        b.iter(|| indices.iter().fold(F::ZERO, |acc, &r| acc + view.get(r, 0)));
+    });
+    group.bench_function("random_access/composed", |b| {
+        b.iter(|| {
+            indices
+                .iter()
+                .fold(F::ZERO, |acc, &r| acc + composed.get(r, 0))
+        });
+    });
+
+    group.bench_function("to_row_major_matrix/stacked", |b| {
+        b.iter_batched(
+            stack,
+            |view| black_box(view.to_row_major_matrix()),
+            BatchSize::PerIteration,
+        );
+    });
+    group.bench_function("to_row_major_matrix/composed", |b| {
+        b.iter_batched(
+            || composed.clone(),
+            |view| black_box(view.to_row_major_matrix()),
+            BatchSize::PerIteration,
+        );
+    });
+    group.bench_function("compose", |b| {
+        b.iter_batched(stack, |view| view.compose(), BatchSize::PerIteration);
+    });
+    group.finish();
+}
+
+criterion_group!(benches, composed_views);
+criterion_main!(benches);
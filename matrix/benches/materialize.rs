@@ -0,0 +1,71 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use p3_baby_bear::BabyBear;
+use p3_matrix::bitrev::BitReversableMatrix;
+use p3_matrix::dense::RowMajorMatrix;
+use p3_matrix::util::reverse_matrix_index_bits;
+use p3_matrix::Matrix;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+type F = BabyBear;
+
+/// Materialize a bit-reversed 2^21 x 100 matrix, as done after every LDE. `row_by_row` is the
+/// generic path, which clones each row out of the view in turn.
+fn materialize_bit_reversed(c: &mut Criterion) {
+    let mut rng = ChaChaRng::seed_from_u64(0);
+    let m = RowMajorMatrix::<F>::rand_nonzero(&mut rng, 1 << 21, 100);
+
+    let mut group = c.benchmark_group("materialize_bit_reversed");
+    group.sample_size(10);
+
+    group.bench_function("row_by_row", |b| {
+        b.iter_batched(
+            || m.clone().bit_reverse_rows(),
+            |view| {
+                black_box(RowMajorMatrix::new(
+                    (0..view.height()).flat_map(|r| view.row(r)).collect(),
+                    view.width(),
+                ))
+            },
+            BatchSize::PerIteration,
+        );
+    });
+    group.bench_function("owned", |b| {
+        b.iter_batched(
+            || m.clone().bit_reverse_rows(),
+            |view| black_box(view.into_row_major_matrix()),
+            BatchSize::PerIteration,
+        );
+    });
+    group.bench_function("borrowed", |b| {
+        b.iter(|| black_box(m.as_view().bit_reverse_rows().to_row_major_matrix()));
+    });
+    group.finish();
+}
+
+/// Bit-reverse the rows of a tall, narrow matrix in place, as `dft_batch` does twice.
+fn reverse_index_bits_in_place(c: &mut Criterion) {
+    let mut rng = ChaChaRng::seed_from_u64(0);
+    let m = RowMajorMatrix::<F>::rand_nonzero(&mut rng, 1 << 22, 8);
+
+    let mut group = c.benchmark_group("reverse_matrix_index_bits");
+    group.sample_size(10);
+    group.bench_function("2^22 x 8", |b| {
+        b.iter_batched(
+            || m.clone(),
+            |mut m| {
+                reverse_matrix_index_bits(&mut m);
+                black_box(m)
+            },
+            BatchSize::PerIteration,
+        );
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    materialize_bit_reversed,
+    reverse_index_bits_in_place
+);
+criterion_main!(benches);
@@ -1,4 +1,5 @@
 use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput};
+use p3_goldilocks::Goldilocks;
 use p3_matrix::dense::RowMajorMatrix;
 use rand::thread_rng;
 
@@ -37,5 +38,26 @@ fn transpose_benchmark(c: &mut Criterion) {
     inner(&mut g, &LARGE_DIMS);
 }
 
-criterion_group!(benches, transpose_benchmark);
+/// A large square matrix of 64-bit field elements, as in a four-step FFT, out of and in place.
+fn transpose_square_benchmark(c: &mut Criterion) {
+    const LG_N: usize = 12;
+    let n = 1 << LG_N;
+    let matrix = RowMajorMatrix::<Goldilocks>::rand(&mut thread_rng(), n, n);
+
+    let mut g = c.benchmark_group("transpose_goldilocks");
+    g.sample_size(10);
+    g.throughput(Throughput::Bytes(
+        (n * n * core::mem::size_of::<Goldilocks>()) as u64,
+    ));
+    let name = format!("2^{LG_N} x 2^{LG_N}");
+    g.bench_function(format!("{name} transpose"), |b| {
+        b.iter(|| matrix.transpose())
+    });
+    let mut matrix = matrix;
+    g.bench_function(format!("{name} in place"), |b| {
+        b.iter(|| matrix.transpose_square_in_place())
+    });
+}
+
+criterion_group!(benches, transpose_benchmark, transpose_square_benchmark);
 criterion_main!(benches);
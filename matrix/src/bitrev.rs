@@ -1,6 +1,11 @@
+use core::borrow::BorrowMut;
+
+use p3_maybe_rayon::prelude::*;
 use p3_util::{log2_strict_usize, reverse_bits_len};
 
-use crate::dense::{DenseMatrix, DenseStorage, RowMajorMatrix};
+use crate::dense::{
+    DenseMatrix, DenseStorage, RowMajorMatrix, RowMajorMatrixView, RowMajorMatrixViewMut,
+};
 use crate::row_index_mapped::{RowIndexMap, RowIndexMappedView};
 use crate::util::reverse_matrix_index_bits;
 use crate::Matrix;
@@ -13,7 +18,7 @@ pub trait BitReversableMatrix<T: Send + Sync>: Matrix<T> {
     fn bit_reverse_rows(self) -> Self::BitRev;
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug)]
 pub struct BitReversalPerm {
     log_height: usize,
 }
@@ -39,8 +44,9 @@ impl RowIndexMap for BitReversalPerm {
     fn map_row_index(&self, r: usize) -> usize {
         reverse_bits_len(r, self.log_height)
     }
-    // This might not be more efficient than the lazy generic impl
-    // if we have a nested view.
+    // Materializing an owned dense matrix just moves it, so its rows are then swapped in place
+    // without any copy, and a borrowed one is copied once. This might not be more efficient than
+    // the lazy generic impl if we have a nested view.
     fn to_row_major_matrix<T: Clone + Send + Sync, Inner: Matrix<T>>(
         &self,
         inner: Inner,
@@ -53,6 +59,80 @@ impl RowIndexMap for BitReversalPerm {
 
 pub type BitReversedMatrixView<Inner> = RowIndexMappedView<BitReversalPerm, Inner>;
 
+impl<Inner> BitReversedMatrixView<Inner> {
+    /// Take back the matrix this view reads from, with its rows in their original order.
+    pub fn into_inner(self) -> Inner {
+        self.inner
+    }
+}
+
+impl<T: Clone + Send + Sync> BitReversedMatrixView<RowMajorMatrix<T>> {
+    /// Materialize the view by permuting the rows of the owned inner matrix in place, without
+    /// allocating a second one.
+    pub fn into_row_major_matrix(self) -> RowMajorMatrix<T> {
+        let mut inner = self.inner;
+        reverse_matrix_index_bits(&mut inner);
+        inner
+    }
+}
+
+/// A bit-reversed view through which the rows of a dense matrix can be written, as well as read.
+pub type BitReversedMatrixViewMut<'a, T> = BitReversedMatrixView<RowMajorMatrixViewMut<'a, T>>;
+
+impl<T: Clone + Send + Sync, S: DenseStorage<T>> BitReversedMatrixView<DenseMatrix<T, S>> {
+    /// Borrow the view, keeping the bit-reversed order.
+    pub fn as_view(&self) -> BitReversedMatrixView<RowMajorMatrixView<'_, T>> {
+        RowIndexMappedView {
+            index_map: self.index_map,
+            inner: self.inner.as_view(),
+        }
+    }
+
+    /// Mutably borrow the view, keeping the bit-reversed order.
+    pub fn as_view_mut(&mut self) -> BitReversedMatrixViewMut<'_, T>
+    where
+        S: BorrowMut<[T]>,
+    {
+        RowIndexMappedView {
+            index_map: self.index_map,
+            inner: self.inner.as_view_mut(),
+        }
+    }
+
+    /// Row `r` of the view, i.e. the inner matrix's row at the bit reversal of `r`.
+    pub fn row_mut(&mut self, r: usize) -> &mut [T]
+    where
+        S: BorrowMut<[T]>,
+    {
+        let r = self.index_map.map_row_index(r);
+        self.inner.row_mut(r)
+    }
+
+    /// All rows, each with its index in the view. Rows come in the inner matrix's order, which is
+    /// what lets them be split across threads without copying, so the indices are bit-reversed.
+    pub fn par_rows_mut<'a>(
+        &'a mut self,
+    ) -> impl IndexedParallelIterator<Item = (usize, &'a mut [T])>
+    where
+        T: 'a,
+        S: BorrowMut<[T]>,
+    {
+        let index_map = self.index_map;
+        self.inner
+            .par_rows_mut()
+            .enumerate()
+            .map(move |(i, row)| (index_map.map_row_index(i), row))
+    }
+}
+
+impl<T: Clone + Send + Sync, S: DenseStorage<T> + BorrowMut<[T]>> DenseMatrix<T, S> {
+    /// A mutable view of the matrix with its rows in bit-reversed order, for writing rows by their
+    /// bit-reversed index. Assumes the height is a power of two; panics otherwise.
+    pub fn bit_reverse_rows_mut(&mut self) -> BitReversedMatrixViewMut<'_, T> {
+        BitReversalPerm::new_view(self.as_view_mut())
+    }
+}
+
 impl<T: Clone + Send + Sync, S: DenseStorage<T>> BitReversableMatrix<T>
     for BitReversedMatrixView<DenseMatrix<T, S>>
 {
@@ -68,3 +148,98 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> BitReversableMatrix<T> for Dens
         BitReversalPerm::new_view(self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    fn bit_reversed_rows(matrix: &RowMajorMatrix<usize>) -> Vec<usize> {
+        let log_height = log2_strict_usize(matrix.height());
+        (0..matrix.height())
+            .flat_map(|r| matrix.row(reverse_bits_len(r, log_height)))
+            .collect()
+    }
+
+    #[test]
+    fn to_row_major_matrix_permutes_in_place() {
+        let matrix = RowMajorMatrix::new((0..48).collect::<Vec<_>>(), 3);
+        let expected = bit_reversed_rows(&matrix);
+
+        // Reversing a borrowed matrix needs one copy.
+        let copied = matrix.as_view().bit_reverse_rows().to_row_major_matrix();
+        assert_eq!(copied.values, expected);
+        assert_eq!(copied.width, 3);
+
+        // Reversing an owned matrix reuses its allocation.
+        let ptr = matrix.values.as_ptr();
+        let permuted = matrix.bit_reverse_rows().to_row_major_matrix();
+        assert_eq!(permuted.values.as_ptr(), ptr);
+        assert_eq!(permuted, copied);
+
+        // Reversing twice is a no-op, even materialized.
+        let twice = permuted.clone().bit_reverse_rows().to_row_major_matrix();
+        assert_eq!(twice.values, (0..48).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn write_through_mutable_view() {
+        let mut matrix = RowMajorMatrix::new(vec![0; 3 << 4], 3);
+        {
+            let mut view = matrix.bit_reverse_rows_mut();
+            for r in 0..view.height() {
+                view.row_mut(r).fill(r);
+            }
+            // The mutable view reads back what was written, as does a borrowed immutable one.
+            assert!((0..16).all(|r| view.row(r).all(|x| x == r)));
+            assert!((0..16).all(|r| view.as_view().row(r).all(|x| x == r)));
+        }
+        // Undoing the bit reversal with the immutable view gives the rows in the order written.
+        let expected: Vec<usize> = (0..16).flat_map(|r| [r; 3]).collect();
+        assert_eq!(
+            matrix
+                .as_view()
+                .bit_reverse_rows()
+                .to_row_major_matrix()
+                .values,
+            expected
+        );
+
+        // Writing in parallel through the view puts each row in the same place.
+        let mut par_matrix = RowMajorMatrix::new(vec![0; 3 << 4], 3);
+        par_matrix
+            .bit_reverse_rows_mut()
+            .par_rows_mut()
+            .for_each(|(r, row)| row.fill(r));
+        assert_eq!(par_matrix, matrix);
+
+        // A mutable view of an owned bit-reversed view writes to the same rows.
+        let mut owned = RowMajorMatrix::new(vec![0; 3 << 4], 3).bit_reverse_rows();
+        for r in 0..16 {
+            owned.as_view_mut().row_mut(r).fill(r);
+        }
+        assert_eq!(owned.into_inner(), matrix);
+    }
+
+    #[test]
+    fn into_row_major_matrix_reuses_inner() {
+        for log_height in 0..6 {
+            let matrix = RowMajorMatrix::new((0..5 << log_height).collect::<Vec<_>>(), 5);
+            let expected = bit_reversed_rows(&matrix);
+
+            let view = matrix.clone().bit_reverse_rows();
+            assert_eq!(view.to_row_major_matrix().values, expected);
+
+            let ptr = matrix.values.as_ptr();
+            let permuted = BitReversalPerm::new_view(matrix).into_row_major_matrix();
+            assert_eq!(permuted.values.as_ptr(), ptr);
+            assert_eq!(permuted.values, expected);
+
+            // Unwrapping the view leaves the rows untouched.
+            let inner = permuted.clone().bit_reverse_rows().into_inner();
+            assert_eq!(inner, permuted);
+        }
+    }
+}
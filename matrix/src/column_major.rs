@@ -0,0 +1,158 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice;
+
+use p3_maybe_rayon::prelude::*;
+use tracing::instrument;
+
+use crate::dense::RowMajorMatrix;
+use crate::Matrix;
+
+/// A matrix stored as one `Vec` per column, as some provers generate their traces.
+///
+/// This lets column-major data be passed wherever a `Matrix` is accepted without first
+/// transposing it. Rows are read across the columns on demand, and `to_row_major_matrix` copies
+/// the whole matrix in a single parallel pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ColumnMajorMatrix<T> {
+    columns: Vec<Vec<T>>,
+    height: usize,
+}
+
+impl<T> ColumnMajorMatrix<T> {
+    /// Panics if the columns don't all have the same length.
+    pub fn new(columns: Vec<Vec<T>>) -> Self {
+        let height = columns.first().map_or(0, Vec::len);
+        assert!(
+            columns.iter().all(|col| col.len() == height),
+            "all columns must have the same height"
+        );
+        Self { columns, height }
+    }
+
+    pub fn columns(&self) -> &[Vec<T>] {
+        &self.columns
+    }
+
+    pub fn into_columns(self) -> Vec<Vec<T>> {
+        self.columns
+    }
+}
+
+impl<T: Clone + Send + Sync> Matrix<T> for ColumnMajorMatrix<T> {
+    fn width(&self) -> usize {
+        self.columns.len()
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get(&self, r: usize, c: usize) -> T {
+        self.columns[c][r].clone()
+    }
+
+    type Row<'a>
+        = ColumnMajorRow<'a, T>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        assert!(r < self.height, "row {r} out of bounds");
+        ColumnMajorRow {
+            columns: self.columns.iter(),
+            r,
+        }
+    }
+
+    fn column(&self, c: usize) -> impl Iterator<Item = T> {
+        self.columns[c].iter().cloned()
+    }
+
+    fn to_row_major_matrix(self) -> RowMajorMatrix<T>
+    where
+        Self: Sized,
+        T: Clone,
+    {
+        let height = self.height;
+        self.to_row_major_matrix_with_capacity(height)
+    }
+
+    #[instrument(level = "debug", skip_all, fields(dims = %self.dimensions()))]
+    fn to_row_major_matrix_with_capacity(self, capacity_rows: usize) -> RowMajorMatrix<T>
+    where
+        Self: Sized,
+        T: Clone,
+    {
+        let width = self.width();
+        let Some(first) = self.columns.first().and_then(|col| col.first()) else {
+            return RowMajorMatrix::new(vec![], width);
+        };
+        let mut values = Vec::with_capacity(width * capacity_rows.max(self.height));
+        values.resize(width * self.height, first.clone());
+        values
+            .par_chunks_exact_mut(width)
+            .enumerate()
+            .for_each(|(r, row)| {
+                for (x, col) in row.iter_mut().zip(&self.columns) {
+                    *x = col[r].clone();
+                }
+            });
+        RowMajorMatrix::new(values, width)
+    }
+}
+
+/// A row of a [`ColumnMajorMatrix`], read from each column in turn.
+#[derive(Clone, Debug)]
+pub struct ColumnMajorRow<'a, T> {
+    columns: slice::Iter<'a, Vec<T>>,
+    r: usize,
+}
+
+impl<T: Clone> Iterator for ColumnMajorRow<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        self.columns.next().map(|col| col[self.r].clone())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.columns.size_hint()
+    }
+}
+
+impl<T: Clone> ExactSizeIterator for ColumnMajorRow<'_, T> {}
+
+#[cfg(test)]
+mod tests {
+    use p3_baby_bear::BabyBear;
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn matches_row_major() {
+        let row_major = RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 9, 5);
+        let columns = (0..5)
+            .map(|c| (0..9).map(|r| row_major.get(r, c)).collect())
+            .collect();
+        let column_major = ColumnMajorMatrix::new(columns);
+
+        assert_eq!(column_major.dimensions(), row_major.dimensions());
+        for r in 0..9 {
+            assert!(column_major.row(r).eq(row_major.row(r)));
+        }
+        assert_eq!(column_major.to_row_major_matrix(), row_major);
+    }
+
+    #[test]
+    fn empty() {
+        let no_columns = ColumnMajorMatrix::<BabyBear>::new(vec![]);
+        assert_eq!(no_columns.height(), 0);
+        assert_eq!(no_columns.to_row_major_matrix().values, vec![]);
+
+        let no_rows = ColumnMajorMatrix::<BabyBear>::new(vec![vec![]; 3]);
+        assert_eq!(no_rows.width(), 3);
+        assert_eq!(no_rows.to_row_major_matrix().height(), 0);
+    }
+}
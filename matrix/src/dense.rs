@@ -2,21 +2,32 @@ use alloc::borrow::Cow;
 use alloc::vec;
 use alloc::vec::Vec;
 use core::borrow::{Borrow, BorrowMut};
+use core::fmt::{self, Debug};
 use core::marker::PhantomData;
-use core::ops::Deref;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::ops::{Deref, Range};
 use core::{iter, slice};
 
-use p3_field::{scale_slice_in_place, ExtensionField, Field, PackedValue};
+use p3_field::{
+    scale_slice_in_place, AbstractExtensionField, ContiguousExtensionField, ExtensionField, Field,
+    PackedValue,
+};
 use p3_maybe_rayon::prelude::*;
+use p3_util::convert_vec;
 use rand::distributions::{Distribution, Standard};
 use rand::Rng;
-use serde::{Deserialize, Serialize};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use tracing::instrument;
 
 use crate::Matrix;
 
 /// A dense matrix stored in row-major form.
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+///
+/// Serializes as its `values` and `width`, or in compact formats as the width, as a `u64`,
+/// followed by the values. Only owned matrices can be deserialized, and a matrix whose values
+/// don't fill a whole number of rows is rejected.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct DenseMatrix<T, V = Vec<T>> {
     pub values: V,
     pub width: usize,
@@ -53,6 +64,60 @@ impl<'a, T: Clone + Send + Sync> DenseStorage<T> for Cow<'a, [T]> {
     }
 }
 
+impl<T: Serialize, V: Borrow<[T]>> Serialize for DenseMatrix<T, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let values = self.values.borrow();
+        if serializer.is_human_readable() {
+            let mut state = serializer.serialize_struct("DenseMatrix", 2)?;
+            state.serialize_field("values", values)?;
+            state.serialize_field("width", &self.width)?;
+            state.end()
+        } else {
+            (self.width as u64, values).serialize(serializer)
+        }
+    }
+}
+
+/// The human-readable form of a serialized [`DenseMatrix`].
+#[derive(Deserialize)]
+#[serde(rename = "DenseMatrix")]
+struct DenseMatrixFields<T> {
+    values: Vec<T>,
+    width: usize,
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for DenseMatrix<T, Vec<T>> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (values, width) = if deserializer.is_human_readable() {
+            let DenseMatrixFields { values, width } = DenseMatrixFields::deserialize(deserializer)?;
+            (values, width)
+        } else {
+            let (width, values) = <(u64, Vec<T>)>::deserialize(deserializer)?;
+            let width = usize::try_from(width).map_err(|_| {
+                serde::de::Error::custom(format_args!("width {width} does not fit in a usize"))
+            })?;
+            (values, width)
+        };
+        // Checked here so that a malformed matrix is a deserialization error, not a panic later.
+        let fills_rows = if width == 0 {
+            values.is_empty()
+        } else {
+            values.len() % width == 0
+        };
+        if !fills_rows {
+            return Err(serde::de::Error::custom(format_args!(
+                "{} values do not fill rows of width {width}",
+                values.len()
+            )));
+        }
+        Ok(Self {
+            values,
+            width,
+            _phantom: PhantomData,
+        })
+    }
+}
+
 impl<T: Clone + Send + Sync + Default> DenseMatrix<T> {
     /// Create a new dense matrix of the given dimensions, backed by a `Vec`, and filled with
     /// default values.
@@ -62,6 +127,125 @@ impl<T: Clone + Send + Sync + Default> DenseMatrix<T> {
     }
 }
 
+impl<T: Clone + Send + Sync> DenseMatrix<T> {
+    /// Build a `height` by `width` matrix row by row. `f` is given each row index in turn, with a
+    /// writer to which it must push exactly `width` values.
+    ///
+    /// # Panics
+    /// Panics if `f` pushes fewer or more than `width` values to a row.
+    pub fn from_row_fn<F>(height: usize, width: usize, mut f: F) -> Self
+    where
+        F: FnMut(usize, &mut RowWriter<'_, T>),
+    {
+        let mut values = uninit_vec(width * height);
+        if width == 0 {
+            (0..height).for_each(|r| RowWriter::new(&mut []).fill(r, &mut f));
+        } else {
+            values
+                .chunks_exact_mut(width)
+                .enumerate()
+                .for_each(|(r, row)| RowWriter::new(row).fill(r, &mut f));
+        }
+        // SAFETY: each row's writer checked that every entry of the row was written.
+        Self::new(unsafe { assume_init_vec(values) }, width)
+    }
+
+    /// Like `from_row_fn`, but with the rows built in parallel. The result doesn't depend on how
+    /// the rows are scheduled.
+    ///
+    /// # Panics
+    /// Panics if `f` pushes fewer or more than `width` values to a row.
+    #[instrument(level = "debug", skip_all)]
+    pub fn from_par_row_fn<F>(height: usize, width: usize, f: F) -> Self
+    where
+        F: Fn(usize, &mut RowWriter<'_, T>) + Sync,
+    {
+        let mut values = uninit_vec(width * height);
+        if width == 0 {
+            (0..height)
+                .into_par_iter()
+                .for_each(|r| RowWriter::new(&mut []).fill(r, &f));
+        } else {
+            values
+                .par_chunks_exact_mut(width)
+                .enumerate()
+                .for_each(|(r, row)| RowWriter::new(row).fill(r, &f));
+        }
+        // SAFETY: each row's writer checked that every entry of the row was written.
+        Self::new(unsafe { assume_init_vec(values) }, width)
+    }
+}
+
+/// A row of a matrix under construction by `RowMajorMatrix::from_row_fn` or `from_par_row_fn`.
+/// Values are pushed in column order, and the row must be full when the closure returns.
+pub struct RowWriter<'a, T> {
+    row: &'a mut [MaybeUninit<T>],
+    len: usize,
+}
+
+impl<'a, T> RowWriter<'a, T> {
+    fn new(row: &'a mut [MaybeUninit<T>]) -> Self {
+        Self { row, len: 0 }
+    }
+
+    fn fill(mut self, r: usize, f: impl FnOnce(usize, &mut Self)) {
+        f(r, &mut self);
+        assert_eq!(
+            self.len,
+            self.row.len(),
+            "row {r} was given {} of its {} values",
+            self.len,
+            self.row.len()
+        );
+    }
+
+    /// Write the next entry of the row.
+    ///
+    /// # Panics
+    /// Panics if the row is already full.
+    #[inline]
+    pub fn push(&mut self, value: T) {
+        assert!(
+            self.len < self.row.len(),
+            "row of width {} is already full",
+            self.row.len()
+        );
+        self.row[self.len].write(value);
+        self.len += 1;
+    }
+
+    /// The number of entries written so far.
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn width(&self) -> usize {
+        self.row.len()
+    }
+}
+
+impl<T> Extend<T> for RowWriter<'_, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        iter.into_iter().for_each(|value| self.push(value));
+    }
+}
+
+fn uninit_vec<T>(len: usize) -> Vec<MaybeUninit<T>> {
+    iter::repeat_with(MaybeUninit::uninit).take(len).collect()
+}
+
+/// # Safety
+/// Every element of `values` must be initialized.
+unsafe fn assume_init_vec<T>(values: Vec<MaybeUninit<T>>) -> Vec<T> {
+    let mut values = ManuallyDrop::new(values);
+    // `MaybeUninit<T>` has the same layout as `T`.
+    Vec::from_raw_parts(values.as_mut_ptr().cast(), values.len(), values.capacity())
+}
+
 impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
     #[must_use]
     pub fn new(values: S, width: usize) -> Self {
@@ -125,6 +309,30 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
         RowMajorMatrix::new(values, width)
     }
 
+    /// View the entries as their coefficients over `F`, without copying. As with `flatten_to_base`,
+    /// the result is `D` times as wide, with each entry's coefficients in adjacent columns.
+    pub fn view_as_base<F: Field>(&self) -> RowMajorMatrixView<'_, F>
+    where
+        T: ContiguousExtensionField<F>,
+    {
+        assert_contiguous_repr::<F, T>();
+        let values = self.values.borrow();
+        // SAFETY: `T` is stored as `D` contiguous values of `F`, with the alignment of `F`.
+        let base_values =
+            unsafe { slice::from_raw_parts(values.as_ptr().cast::<F>(), values.len() * T::D) };
+        RowMajorMatrixView::new(base_values, self.width * T::D)
+    }
+
+    pub fn column_to_vec(&self, c: usize) -> Vec<T> {
+        self.column(c).collect()
+    }
+
+    /// The values making up whole rows. These are all the values, unless the width is 0, in which
+    /// case there are no rows, and this is empty.
+    fn full_rows(&self) -> &[T] {
+        &self.values.borrow()[..self.height() * self.width]
+    }
+
     pub fn row_slices(&self) -> impl Iterator<Item = &[T]> {
         self.values.borrow().chunks_exact(self.width)
     }
@@ -182,12 +390,55 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
         scale_slice_in_place(scale, self.values.borrow_mut());
     }
 
+    /// A view of the rows in `range`, or `None` if the range is decreasing or runs past the last
+    /// row.
+    pub fn try_row_window(&self, range: Range<usize>) -> Option<RowMajorMatrixView<'_, T>> {
+        if range.start > range.end || range.end > self.height() {
+            return None;
+        }
+        let values = &self.values.borrow()[range.start * self.width..range.end * self.width];
+        Some(RowMajorMatrixView::new(values, self.width))
+    }
+
+    /// A view of the rows in `range`.
+    ///
+    /// Panics if the range is decreasing or runs past the last row.
+    pub fn row_window(&self, range: Range<usize>) -> RowMajorMatrixView<'_, T> {
+        let height = self.height();
+        self.try_row_window(range.clone()).unwrap_or_else(|| {
+            panic!("row window {range:?} out of bounds for a matrix of height {height}")
+        })
+    }
+
+    /// Like `try_row_window`, but the view can modify the rows.
+    pub fn try_row_window_mut(
+        &mut self,
+        range: Range<usize>,
+    ) -> Option<RowMajorMatrixViewMut<'_, T>>
+    where
+        S: BorrowMut<[T]>,
+    {
+        if range.start > range.end || range.end > self.height() {
+            return None;
+        }
+        let width = self.width;
+        let values = &mut self.values.borrow_mut()[range.start * width..range.end * width];
+        Some(RowMajorMatrixViewMut::new(values, width))
+    }
+
+    /// Like `row_window`, but the view can modify the rows.
+    pub fn row_window_mut(&mut self, range: Range<usize>) -> RowMajorMatrixViewMut<'_, T>
+    where
+        S: BorrowMut<[T]>,
+    {
+        let height = self.height();
+        self.try_row_window_mut(range.clone()).unwrap_or_else(|| {
+            panic!("row window {range:?} out of bounds for a matrix of height {height}")
+        })
+    }
+
     pub fn split_rows(&self, r: usize) -> (RowMajorMatrixView<T>, RowMajorMatrixView<T>) {
-        let (lo, hi) = self.values.borrow().split_at(r * self.width);
-        (
-            DenseMatrix::new(lo, self.width),
-            DenseMatrix::new(hi, self.width),
-        )
+        (self.row_window(0..r), self.row_window(r..self.height()))
     }
 
     pub fn split_rows_mut(
@@ -230,6 +481,8 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
             .map(|slice| RowMajorMatrixView::new(slice, self.width))
     }
 
+    /// Split the matrix into consecutive views of `chunk_rows` rows, handed out in parallel. If
+    /// `chunk_rows` doesn't divide the height, the last view holds the remaining, fewer rows.
     pub fn par_row_chunks_mut(
         &mut self,
         chunk_rows: usize,
@@ -238,10 +491,12 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
         T: Send,
         S: BorrowMut<[T]>,
     {
+        assert_ne!(chunk_rows, 0, "chunks must have at least one row");
+        let width = self.width;
         self.values
             .borrow_mut()
-            .par_chunks_mut(self.width * chunk_rows)
-            .map(|slice| RowMajorMatrixViewMut::new(slice, self.width))
+            .par_chunks_mut(width.max(1) * chunk_rows)
+            .map(move |slice| RowMajorMatrixViewMut::new(slice, width))
     }
 
     pub fn row_chunks_exact_mut(
@@ -258,18 +513,53 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
             .map(|slice| RowMajorMatrixViewMut::new(slice, self.width))
     }
 
+    /// Split the matrix into `num_chunks` consecutive views, each with the same number of rows.
+    ///
+    /// Panics if `num_chunks` doesn't evenly divide the height.
+    pub fn split_into_equal_height_chunks(
+        &mut self,
+        num_chunks: usize,
+    ) -> impl Iterator<Item = RowMajorMatrixViewMut<T>>
+    where
+        T: Send,
+        S: BorrowMut<[T]>,
+    {
+        let h = self.height();
+        assert!(
+            num_chunks != 0 && h % num_chunks == 0,
+            "cannot split a matrix of height {h} into {num_chunks} chunks of equal height"
+        );
+        self.row_chunks_exact_mut(h / num_chunks)
+    }
+
+    /// Split the matrix into consecutive views of exactly `chunk_rows` rows, handed out in
+    /// parallel, and a view of the last `height % chunk_rows` rows, which don't fill a chunk. Like
+    /// `par_chunks_exact_mut` on slices, the remainder is only returned separately, so callers
+    /// must handle it themselves; `par_row_chunks_mut` hands it out as a final shorter chunk.
+    #[allow(clippy::type_complexity)]
     pub fn par_row_chunks_exact_mut(
         &mut self,
         chunk_rows: usize,
-    ) -> impl IndexedParallelIterator<Item = RowMajorMatrixViewMut<T>>
+    ) -> (
+        impl IndexedParallelIterator<Item = RowMajorMatrixViewMut<'_, T>>,
+        RowMajorMatrixViewMut<'_, T>,
+    )
     where
         T: Send,
         S: BorrowMut<[T]>,
     {
-        self.values
-            .borrow_mut()
-            .par_chunks_exact_mut(self.width * chunk_rows)
-            .map(|slice| RowMajorMatrixViewMut::new(slice, self.width))
+        assert_ne!(chunk_rows, 0, "chunks must have at least one row");
+        let width = self.width;
+        let chunk_len = width.max(1) * chunk_rows;
+        let values = self.values.borrow_mut();
+        let exact_len = values.len() - values.len() % chunk_len;
+        let (exact, remainder) = values.split_at_mut(exact_len);
+        (
+            exact
+                .par_chunks_exact_mut(chunk_len)
+                .map(move |slice| RowMajorMatrixViewMut::new(slice, width)),
+            RowMajorMatrixViewMut::new(remainder, width),
+        )
     }
 
     pub fn row_pair_mut(&mut self, row_1: usize, row_2: usize) -> (&mut [T], &mut [T])
@@ -323,8 +613,9 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> DenseMatrix<T, S> {
         let w = self.width;
         let mut padded =
             RowMajorMatrix::new(T::zero_vec(self.values.borrow().len() << added_bits), w);
-        padded
-            .par_row_chunks_exact_mut(1 << added_bits)
+        let (chunks, rest) = padded.par_row_chunks_exact_mut(1 << added_bits);
+        debug_assert_eq!(rest.height(), 0);
+        chunks
             .zip(self.par_row_slices())
             .for_each(|(mut ch, r)| ch.row_mut(0).copy_from_slice(r));
 
@@ -369,6 +660,34 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> Matrix<T> for DenseMatrix<T, S>
         &self.values.borrow()[r * self.width..(r + 1) * self.width]
     }
 
+    fn par_rows(&self) -> impl IndexedParallelIterator<Item = Self::Row<'_>> {
+        self.full_rows()
+            .par_chunks_exact(self.width.max(1))
+            .map(|row| row.iter().cloned())
+    }
+
+    fn par_row_chunks(
+        &self,
+        chunk_rows: usize,
+    ) -> impl IndexedParallelIterator<Item = impl Iterator<Item = Self::Row<'_>> + Send> {
+        assert_ne!(chunk_rows, 0, "chunks must have at least one row");
+        let width = self.width.max(1);
+        self.full_rows()
+            .par_chunks(width * chunk_rows)
+            .map(move |chunk| chunk.chunks_exact(width).map(|row| row.iter().cloned()))
+    }
+
+    #[inline]
+    fn column(&self, c: usize) -> impl Iterator<Item = T> {
+        assert!(c < self.width, "column {c} out of bounds");
+        self.values
+            .borrow()
+            .iter()
+            .skip(c)
+            .step_by(self.width)
+            .cloned()
+    }
+
     fn to_row_major_matrix(self) -> RowMajorMatrix<T>
     where
         Self: Sized,
@@ -377,6 +696,16 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> Matrix<T> for DenseMatrix<T, S>
         RowMajorMatrix::new(self.values.to_vec(), self.width)
     }
 
+    fn to_row_major_matrix_with_capacity(self, capacity_rows: usize) -> RowMajorMatrix<T>
+    where
+        Self: Sized,
+        T: Clone,
+    {
+        let mut values = self.values.to_vec();
+        values.reserve_exact((self.width * capacity_rows).saturating_sub(values.len()));
+        RowMajorMatrix::new(values, self.width)
+    }
+
     #[inline]
     fn horizontally_packed_row<'a, P>(
         &'a self,
@@ -411,6 +740,56 @@ impl<T: Clone + Send + Sync, S: DenseStorage<T>> Matrix<T> for DenseMatrix<T, S>
     }
 }
 
+impl<T: Clone + Send + Sync> DenseMatrix<T, Vec<T>> {
+    /// The owned counterpart of `view_as_base`, reusing the allocation.
+    pub fn into_base_matrix<F: Field>(self) -> RowMajorMatrix<F>
+    where
+        T: ContiguousExtensionField<F>,
+    {
+        assert_contiguous_repr::<F, T>();
+        let width = self.width * T::D;
+        // SAFETY: `T` is stored as `D` contiguous values of `F`, with the alignment of `F`.
+        RowMajorMatrix::new(unsafe { convert_vec(self.values) }, width)
+    }
+
+    /// Reinterpret each group of `EF::D` adjacent columns as one column of `EF`, the inverse of
+    /// `into_base_matrix`. The allocation is reused unless its capacity isn't a whole number of
+    /// `EF` values.
+    ///
+    /// # Panics
+    /// Panics if the width isn't a multiple of `EF::D`.
+    pub fn reinterpret_as_ext<EF>(self) -> RowMajorMatrix<EF>
+    where
+        T: Field,
+        EF: ContiguousExtensionField<T>,
+    {
+        assert_contiguous_repr::<T, EF>();
+        assert_eq!(
+            self.width % EF::D,
+            0,
+            "width {} is not a multiple of the extension degree {}",
+            self.width,
+            EF::D
+        );
+        let mut values = self.values;
+        if !values.capacity().is_multiple_of(EF::D) {
+            values = values.into_boxed_slice().into_vec();
+        }
+        // SAFETY: `EF` is stored as `D` contiguous values of `T`, with the alignment of `T`, and any
+        // `D` values of `T` make a valid `EF`. The length and capacity are multiples of `D`.
+        RowMajorMatrix::new(unsafe { convert_vec(values) }, self.width / EF::D)
+    }
+}
+
+/// Fails to compile for a `ContiguousExtensionField` impl whose size or alignment doesn't match
+/// its coefficients'.
+fn assert_contiguous_repr<F: Field, EF: ContiguousExtensionField<F>>() {
+    const {
+        assert!(size_of::<EF>() == <EF as AbstractExtensionField<F>>::D * size_of::<F>());
+        assert!(align_of::<EF>() == align_of::<F>());
+    }
+}
+
 impl<T: Clone + Default + Send + Sync> DenseMatrix<T, Vec<T>> {
     pub fn as_cow<'a>(self) -> RowMajorMatrixCow<'a, T> {
         RowMajorMatrixCow::new(Cow::Owned(self.values), self.width)
@@ -437,24 +816,146 @@ impl<T: Clone + Default + Send + Sync> DenseMatrix<T, Vec<T>> {
         Self::new(values, cols)
     }
 
-    pub fn pad_to_height(&mut self, new_height: usize, fill: T) {
-        assert!(new_height >= self.height());
-        self.values.resize(self.width * new_height, fill);
+    /// Append rows, filled as `fill` says, until the matrix has `new_height` rows.
+    ///
+    /// Does nothing if the matrix is already `new_height` rows tall, and panics if it is taller.
+    /// A matrix of width 0 has height 0 whatever its values, so it is left as it is.
+    pub fn pad_to_height(&mut self, new_height: usize, fill: Padding<'_, T>) {
+        let height = self.height();
+        assert!(
+            new_height >= height,
+            "cannot pad a matrix of height {height} to height {new_height}"
+        );
+        if self.width == 0 || new_height == height {
+            return;
+        }
+        self.values
+            .reserve_exact(self.width * (new_height - height));
+        match fill {
+            Padding::Zero => self.values.resize(self.width * new_height, T::default()),
+            Padding::RepeatLastRow => {
+                assert_ne!(height, 0, "an empty matrix has no last row to repeat");
+                let last_row = self.values.len() - self.width..self.values.len();
+                for _ in height..new_height {
+                    self.values.extend_from_within(last_row.clone());
+                }
+            }
+            Padding::WithFn(row_fn) => {
+                for r in height..new_height {
+                    let row = row_fn(r);
+                    assert_eq!(row.len(), self.width, "padding row {r} has the wrong width");
+                    self.values.extend(row);
+                }
+            }
+        }
+    }
+
+    /// Pad the matrix to the next power-of-two height, as `pad_to_height` does. An empty matrix
+    /// is padded to a single row.
+    pub fn pad_to_next_power_of_two(&mut self, fill: Padding<'_, T>) {
+        self.pad_to_height(self.height().next_power_of_two(), fill);
+    }
+}
+
+/// How [`RowMajorMatrix::pad_to_height`] fills the rows it appends.
+pub enum Padding<'a, T> {
+    /// Rows of `T::default()`, which is zero for fields.
+    Zero,
+    /// Copies of the last row. Panics if there is none.
+    RepeatLastRow,
+    /// The row returned for each new row's index, counting from the top of the padded matrix.
+    WithFn(&'a dyn Fn(usize) -> Vec<T>),
+}
+
+impl<T> Clone for Padding<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Padding<'_, T> {}
+
+impl<T> Debug for Padding<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Zero => f.write_str("Zero"),
+            Self::RepeatLastRow => f.write_str("RepeatLastRow"),
+            Self::WithFn(_) => f.write_str("WithFn(..)"),
+        }
     }
 }
 
+/// The side length of the square tiles that transposes work through, small enough that a tile of
+/// the source and one of the destination stay in cache together.
+const TRANSPOSE_BLOCK_SIZE: usize = 64;
+
 impl<T: Copy + Default + Send + Sync> DenseMatrix<T, Vec<T>> {
     pub fn transpose(&self) -> Self {
-        let nelts = self.height() * self.width();
-        let mut values = vec![T::default(); nelts];
-        transpose::transpose(&self.values, &mut values, self.width(), self.height());
-        Self::new(values, self.height())
+        let mut transposed = Self::default(self.height(), self.width());
+        self.transpose_into(&mut transposed);
+        transposed
     }
 
+    #[instrument(level = "debug", skip_all, fields(dims = %self.dimensions()))]
     pub fn transpose_into(&self, other: &mut Self) {
         assert_eq!(self.height(), other.width());
         assert_eq!(other.height(), self.width());
-        transpose::transpose(&self.values, &mut other.values, self.width(), self.height());
+        let (w, h) = (self.width(), self.height());
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        // Each task fills a band of rows of `other`, i.e. of columns of `self`, one tile at a time.
+        other
+            .values
+            .par_chunks_mut(TRANSPOSE_BLOCK_SIZE * h)
+            .enumerate()
+            .for_each(|(band, dst)| {
+                let c_start = band * TRANSPOSE_BLOCK_SIZE;
+                for r_start in (0..h).step_by(TRANSPOSE_BLOCK_SIZE) {
+                    let r_end = (r_start + TRANSPOSE_BLOCK_SIZE).min(h);
+                    for (c, dst_row) in dst.chunks_exact_mut(h).enumerate() {
+                        let c = c_start + c;
+                        for (x, r) in dst_row[r_start..r_end].iter_mut().zip(r_start..) {
+                            *x = self.values[r * w + c];
+                        }
+                    }
+                }
+            });
+    }
+
+    /// Transpose a square matrix without allocating.
+    ///
+    /// Panics if the matrix isn't square.
+    #[instrument(level = "debug", skip_all, fields(dims = %self.dimensions()))]
+    pub fn transpose_square_in_place(&mut self) {
+        let n = self.width();
+        assert_eq!(
+            self.height(),
+            n,
+            "only a square matrix can be transposed in place"
+        );
+        let num_blocks = n.div_ceil(TRANSPOSE_BLOCK_SIZE);
+        let values = self.values.as_mut_ptr() as usize;
+
+        // Each task swaps the tiles of one block row on and above the diagonal with their mirror
+        // images, so no two tasks touch the same element.
+        (0..num_blocks).into_par_iter().for_each(|block_row| {
+            let values = values as *mut T;
+            let r_start = block_row * TRANSPOSE_BLOCK_SIZE;
+            let r_end = (r_start + TRANSPOSE_BLOCK_SIZE).min(n);
+            for block_col in block_row..num_blocks {
+                let c_start = block_col * TRANSPOSE_BLOCK_SIZE;
+                let c_end = (c_start + TRANSPOSE_BLOCK_SIZE).min(n);
+                for r in r_start..r_end {
+                    for c in c_start.max(r + 1)..c_end {
+                        // SAFETY: r < c < n, so both elements are in bounds and distinct, and
+                        // only the task for r's block row swaps them.
+                        unsafe { core::ptr::swap(values.add(r * n + c), values.add(c * n + r)) };
+                    }
+                }
+            }
+        });
     }
 }
 
@@ -466,7 +967,368 @@ impl<'a, T: Clone + Default + Send + Sync> DenseMatrix<T, &'a [T]> {
 
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
+
+    use p3_baby_bear::BabyBear;
+    use p3_field::extension::BinomialExtensionField;
+    use p3_field::AbstractField;
+    use rand::thread_rng;
+
     use super::*;
+    use crate::Dimensions;
+
+    fn row_fn(r: usize, row: &mut RowWriter<'_, u64>) {
+        row.extend((0..row.width()).map(|c| (r * 31 + c * 7) as u64));
+    }
+
+    #[test]
+    fn from_row_fns() {
+        let expected = RowMajorMatrix::new(
+            (0..37)
+                .flat_map(|r| (0..5).map(move |c| (r * 31 + c * 7) as u64))
+                .collect(),
+            5,
+        );
+        assert_eq!(RowMajorMatrix::from_row_fn(37, 5, row_fn), expected);
+        assert_eq!(RowMajorMatrix::from_par_row_fn(37, 5, row_fn), expected);
+        assert_eq!(RowMajorMatrix::from_par_row_fn(0, 5, row_fn).height(), 0);
+        assert_eq!(RowMajorMatrix::from_par_row_fn(3, 0, row_fn).width(), 0);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn from_par_row_fn_independent_of_thread_count() {
+        let build_with_threads = |num_threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .unwrap();
+            pool.install(|| RowMajorMatrix::from_par_row_fn(1 << 12, 3, row_fn))
+        };
+        assert_eq!(build_with_threads(1), build_with_threads(4));
+    }
+
+    #[test]
+    #[should_panic(expected = "row 3 was given 4 of its 5 values")]
+    fn from_par_row_fn_skipping_an_entry() {
+        RowMajorMatrix::<u64>::from_par_row_fn(8, 5, |r, row| {
+            let width = if r == 3 { 4 } else { 5 };
+            row.extend((0..width).map(|c| c as u64));
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "row of width 2 is already full")]
+    fn from_row_fn_overfilling_a_row() {
+        RowMajorMatrix::<u64>::from_row_fn(2, 2, |_, row| row.extend([1, 2, 3]));
+    }
+
+    #[test]
+    fn base_field_reinterpretation_round_trip() {
+        type EF = BinomialExtensionField<BabyBear, 4>;
+        let ext = RowMajorMatrix::<EF>::rand(&mut thread_rng(), 5, 3);
+        let flat = ext.flatten_to_base::<BabyBear>();
+        assert_eq!(ext.view_as_base::<BabyBear>().to_row_major_matrix(), flat);
+        assert_eq!(ext.as_view().view_as_base::<BabyBear>().width(), 12);
+
+        let base = ext.clone().into_base_matrix::<BabyBear>();
+        assert_eq!(base, flat);
+        assert_eq!(base.reinterpret_as_ext::<EF>(), ext);
+    }
+
+    #[test]
+    fn reinterpret_as_ext_with_uneven_capacity() {
+        type EF = BinomialExtensionField<BabyBear, 4>;
+        let mut values = Vec::with_capacity(4 * 2 + 1);
+        values.extend((0..8).map(BabyBear::from_canonical_u32));
+        let ext = RowMajorMatrix::new(values, 8).reinterpret_as_ext::<EF>();
+        assert_eq!(
+            ext.dimensions(),
+            Dimensions {
+                width: 2,
+                height: 1
+            }
+        );
+        assert_eq!(
+            ext.get(0, 1),
+            EF::from_base_fn(|i| BabyBear::from_canonical_usize(4 + i))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "width 6 is not a multiple of the extension degree 4")]
+    fn reinterpret_as_ext_with_partial_entries() {
+        RowMajorMatrix::new(vec![BabyBear::ONE; 12], 6)
+            .reinterpret_as_ext::<BinomialExtensionField<BabyBear, 4>>();
+    }
+
+    #[test]
+    fn split_into_equal_height_chunks() {
+        let mut matrix = RowMajorMatrix::new((0..24).collect::<Vec<_>>(), 2);
+        let chunks = matrix
+            .split_into_equal_height_chunks(3)
+            .map(|chunk| chunk.to_row_major_matrix())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            chunks,
+            vec![
+                RowMajorMatrix::new((0..8).collect(), 2),
+                RowMajorMatrix::new((8..16).collect(), 2),
+                RowMajorMatrix::new((16..24).collect(), 2),
+            ]
+        );
+
+        // Writes through the chunks land in the original matrix.
+        for (i, mut chunk) in matrix.split_into_equal_height_chunks(4).enumerate() {
+            chunk.row_mut(0)[0] = 100 + i;
+        }
+        assert_eq!(
+            matrix.values.iter().step_by(6).copied().collect::<Vec<_>>(),
+            vec![100, 101, 102, 103]
+        );
+    }
+
+    #[test]
+    fn par_row_chunks_mut_with_remainder() {
+        for height in [0, 1, 6, 8, 9, 11] {
+            let values = (0..height * 2).collect::<Vec<_>>();
+
+            // Every row is visited, with the remainder as a final shorter chunk.
+            let mut matrix = RowMajorMatrix::new(values.clone(), 2);
+            let chunk_heights = matrix
+                .par_row_chunks_mut(4)
+                .map(|chunk| {
+                    chunk.values.iter_mut().for_each(|x| *x += 100);
+                    chunk.height()
+                })
+                .collect::<Vec<_>>();
+            let mut expected_heights = vec![4; height / 4];
+            if height % 4 != 0 {
+                expected_heights.push(height % 4);
+            }
+            assert_eq!(chunk_heights, expected_heights);
+            assert!(matrix
+                .values
+                .iter()
+                .zip(&values)
+                .all(|(&x, &v)| x == v + 100));
+
+            // The exact chunks leave the remainder to the caller.
+            let mut matrix = RowMajorMatrix::new(values.clone(), 2);
+            let (chunks, remainder) = matrix.par_row_chunks_exact_mut(4);
+            assert_eq!(
+                chunks.map(|chunk| chunk.height()).collect::<Vec<_>>(),
+                vec![4; height / 4]
+            );
+            assert_eq!(remainder.height(), height % 4);
+            assert_eq!(
+                &*remainder.values,
+                &values[values.len() - 2 * (height % 4)..]
+            );
+        }
+    }
+
+    #[test]
+    fn row_windows() {
+        let mut matrix = RowMajorMatrix::new((0..12).collect::<Vec<_>>(), 2);
+
+        assert_eq!(matrix.row_window(1..4).values, &[2, 3, 4, 5, 6, 7]);
+        assert_eq!(matrix.row_window(6..6).height(), 0);
+        // Windows of views index the view's rows.
+        let view = matrix.row_window(2..6);
+        assert_eq!(view.row_window(1..2).values, &[6, 7]);
+
+        assert!(matrix.try_row_window(5..7).is_none());
+        #[allow(clippy::reversed_empty_ranges)]
+        let decreasing = 3..2;
+        assert!(matrix.try_row_window(decreasing).is_none());
+        assert!(matrix.try_row_window_mut(0..7).is_none());
+
+        matrix.row_window_mut(4..6).values.fill(0);
+        assert_eq!(matrix.values, vec![0, 1, 2, 3, 4, 5, 6, 7, 0, 0, 0, 0]);
+
+        let (lo, hi) = matrix.split_rows(2);
+        assert_eq!(lo.values, &[0, 1, 2, 3]);
+        assert_eq!(hi.height(), 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "row window 4..7 out of bounds for a matrix of height 6")]
+    fn row_window_past_the_end() {
+        RowMajorMatrix::new((0..12).collect::<Vec<_>>(), 2).row_window(4..7);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot split a matrix of height 12 into 5 chunks of equal height")]
+    fn split_into_uneven_height_chunks() {
+        let mut matrix = RowMajorMatrix::new((0..24).collect::<Vec<_>>(), 2);
+        let _ = matrix.split_into_equal_height_chunks(5);
+    }
+
+    #[test]
+    fn to_row_major_matrix_moves_or_copies_once() {
+        let matrix = RowMajorMatrix::new((0..12).collect::<Vec<_>>(), 3);
+
+        // A view copies its rows out in one go.
+        let copied = matrix.as_view().to_row_major_matrix();
+        assert_eq!(copied, matrix);
+        assert_ne!(copied.values.as_ptr(), matrix.values.as_ptr());
+
+        // An owned matrix is returned as is, without reallocating.
+        let ptr = matrix.values.as_ptr();
+        let moved = matrix.to_row_major_matrix();
+        assert_eq!(moved.values.as_ptr(), ptr);
+        assert_eq!(moved, copied);
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let matrix = RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 5, 3);
+
+        let json = serde_json::to_string(&matrix).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RowMajorMatrix<BabyBear>>(&json).unwrap(),
+            matrix
+        );
+
+        let bytes = postcard::to_allocvec(&matrix).unwrap();
+        // The width comes first, then the values.
+        assert_eq!(bytes[0], 3);
+        assert_eq!(
+            postcard::from_bytes::<RowMajorMatrix<BabyBear>>(&bytes).unwrap(),
+            matrix
+        );
+
+        // A view serializes just like the matrix it borrows from.
+        assert_eq!(postcard::to_allocvec(&matrix.as_view()).unwrap(), bytes);
+        assert_eq!(serde_json::to_string(&matrix.as_view()).unwrap(), json);
+    }
+
+    #[test]
+    fn serde_rejects_partial_rows() {
+        let err = serde_json::from_str::<RowMajorMatrix<u32>>(r#"{"values":[1,2,3],"width":2}"#)
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("3 values do not fill rows of width 2"));
+        assert!(
+            serde_json::from_str::<RowMajorMatrix<u32>>(r#"{"values":[1],"width":0}"#).is_err()
+        );
+
+        // Corrupt the width of an otherwise valid encoding.
+        let mut bytes =
+            postcard::to_allocvec(&RowMajorMatrix::new(vec![1u32, 2, 3, 4], 2)).unwrap();
+        bytes[0] = 3;
+        assert!(postcard::from_bytes::<RowMajorMatrix<u32>>(&bytes).is_err());
+        bytes[0] = 4;
+        assert_eq!(
+            postcard::from_bytes::<RowMajorMatrix<u32>>(&bytes).unwrap(),
+            RowMajorMatrix::new(vec![1, 2, 3, 4], 4)
+        );
+    }
+
+    #[test]
+    fn pad_to_height() {
+        let matrix = RowMajorMatrix::new(vec![1, 2, 3, 4, 5, 6], 2);
+
+        let mut zeros = matrix.clone();
+        zeros.pad_to_height(5, Padding::Zero);
+        assert_eq!(zeros.values, vec![1, 2, 3, 4, 5, 6, 0, 0, 0, 0]);
+
+        let mut repeated = matrix.clone();
+        repeated.pad_to_height(5, Padding::RepeatLastRow);
+        assert_eq!(repeated.values, vec![1, 2, 3, 4, 5, 6, 5, 6, 5, 6]);
+
+        let mut from_fn = matrix.clone();
+        from_fn.pad_to_height(5, Padding::WithFn(&|r| vec![r, 10 * r]));
+        assert_eq!(from_fn.values, vec![1, 2, 3, 4, 5, 6, 3, 30, 4, 40]);
+        assert_eq!(from_fn.values.capacity(), 10);
+
+        // Already tall enough.
+        let mut same = matrix.clone();
+        same.pad_to_height(3, Padding::WithFn(&|_| unreachable!()));
+        assert_eq!(same, matrix);
+
+        let mut pow_2 = matrix.clone();
+        pow_2.pad_to_next_power_of_two(Padding::RepeatLastRow);
+        assert_eq!(pow_2.values, vec![1, 2, 3, 4, 5, 6, 5, 6]);
+    }
+
+    #[test]
+    fn pad_empty_matrices() {
+        // Width 0 means height 0, which padding can't change.
+        let mut no_columns = RowMajorMatrix::<u32>::new(vec![], 0);
+        no_columns.pad_to_height(0, Padding::RepeatLastRow);
+        no_columns.pad_to_height(4, Padding::Zero);
+        assert_eq!(
+            no_columns.dimensions(),
+            Dimensions {
+                width: 0,
+                height: 0
+            }
+        );
+
+        let mut no_rows = RowMajorMatrix::<u32>::new(vec![], 3);
+        no_rows.pad_to_height(0, Padding::RepeatLastRow);
+        assert_eq!(no_rows.height(), 0);
+        no_rows.pad_to_next_power_of_two(Padding::Zero);
+        assert_eq!(no_rows.values, vec![0, 0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot pad a matrix of height 3 to height 2")]
+    fn pad_to_smaller_height() {
+        RowMajorMatrix::new(vec![1, 2, 3, 4, 5, 6], 2).pad_to_height(2, Padding::Zero);
+    }
+
+    #[test]
+    #[should_panic(expected = "an empty matrix has no last row to repeat")]
+    fn repeat_last_row_of_empty_matrix() {
+        RowMajorMatrix::<u32>::new(vec![], 3).pad_to_height(2, Padding::RepeatLastRow);
+    }
+
+    #[test]
+    #[should_panic(expected = "padding row 3 has the wrong width")]
+    fn pad_with_wrong_width() {
+        RowMajorMatrix::new(vec![1, 2, 3, 4, 5, 6], 2)
+            .pad_to_height(4, Padding::WithFn(&|r| vec![r]));
+    }
+
+    #[test]
+    fn test_transpose_twice_is_identity() {
+        // Shapes on and off the tile size, including single rows and columns.
+        for (height, width) in [(1, 1), (1, 100), (100, 1), (64, 64), (65, 130), (200, 37)] {
+            let matrix = RowMajorMatrix::new((0..height * width).collect::<Vec<_>>(), width);
+            let transposed = matrix.transpose();
+            assert_eq!(
+                transposed.dimensions(),
+                Dimensions {
+                    width: height,
+                    height: width
+                }
+            );
+            for (r, c) in [(0, 0), (height - 1, width - 1), (height / 2, width / 3)] {
+                assert_eq!(transposed.get(c, r), matrix.get(r, c));
+            }
+            assert_eq!(transposed.transpose(), matrix);
+        }
+    }
+
+    #[test]
+    fn test_transpose_square_in_place() {
+        for n in [0, 1, 2, 63, 64, 65, 200] {
+            let matrix = RowMajorMatrix::new((0..n * n).collect::<Vec<_>>(), n);
+            let mut transposed = matrix.clone();
+            transposed.transpose_square_in_place();
+            assert_eq!(transposed, matrix.transpose(), "n = {n}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "only a square matrix can be transposed in place")]
+    fn test_transpose_non_square_in_place() {
+        RowMajorMatrix::new((0..6).collect::<Vec<_>>(), 2).transpose_square_in_place();
+    }
 
     #[test]
     fn test_transpose_square_matrix() {
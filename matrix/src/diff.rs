@@ -0,0 +1,181 @@
+//! Entry-by-entry comparison of matrices, for tests which would otherwise print every value of
+//! two large matrices when they differ, or compare them only as a whole.
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+
+use crate::{Dimensions, Matrix};
+
+/// The number of mismatching entries a `MatrixDiff` keeps.
+pub const MAX_REPORTED_MISMATCHES: usize = 8;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch<T> {
+    pub row: usize,
+    pub col: usize,
+    pub expected: T,
+    pub actual: T,
+}
+
+/// The differences between two matrices, as found by `matrix_diff`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MatrixDiff<T> {
+    pub expected_dims: Dimensions,
+    pub actual_dims: Dimensions,
+    /// The number of differing entries among the rows and columns both matrices have.
+    pub num_mismatches: usize,
+    /// The first differing entries in row-major order, at most `MAX_REPORTED_MISMATCHES` of them.
+    pub first_mismatches: Vec<Mismatch<T>>,
+}
+
+impl<T> MatrixDiff<T> {
+    /// Whether the matrices are equal: the same dimensions, and no differing entries.
+    pub fn is_empty(&self) -> bool {
+        self.expected_dims == self.actual_dims && self.num_mismatches == 0
+    }
+}
+
+impl<T: Debug> Display for MatrixDiff<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "matrices are equal ({})", self.expected_dims);
+        }
+        if self.expected_dims != self.actual_dims {
+            writeln!(
+                f,
+                "dimensions differ: expected {}, got {}",
+                self.expected_dims, self.actual_dims
+            )?;
+        }
+        write!(
+            f,
+            "{} mismatching entries among those both matrices have",
+            self.num_mismatches
+        )?;
+        if self.num_mismatches > self.first_mismatches.len() {
+            write!(f, ", the first {} being", self.first_mismatches.len())?;
+        }
+        for m in &self.first_mismatches {
+            write!(
+                f,
+                "\n  ({}, {}): expected {:?}, got {:?}",
+                m.row, m.col, m.expected, m.actual
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare `actual` with `expected` entry by entry, reading both a row at a time, so that any two
+/// `Matrix` implementations can be compared, e.g. a `BitReversedMatrixView` with a dense matrix.
+pub fn matrix_diff<T, A, B>(expected: &A, actual: &B) -> MatrixDiff<T>
+where
+    T: Clone + PartialEq + Send + Sync,
+    A: Matrix<T>,
+    B: Matrix<T>,
+{
+    let mut num_mismatches = 0;
+    let mut first_mismatches = Vec::new();
+    for row in 0..expected.height().min(actual.height()) {
+        for (col, (e, a)) in expected.row(row).zip(actual.row(row)).enumerate() {
+            if e != a {
+                num_mismatches += 1;
+                if first_mismatches.len() < MAX_REPORTED_MISMATCHES {
+                    first_mismatches.push(Mismatch {
+                        row,
+                        col,
+                        expected: e,
+                        actual: a,
+                    });
+                }
+            }
+        }
+    }
+    MatrixDiff {
+        expected_dims: expected.dimensions(),
+        actual_dims: actual.dimensions(),
+        num_mismatches,
+        first_mismatches,
+    }
+}
+
+/// Assert that two matrices, of any `Matrix` types, are equal, printing a `MatrixDiff` summary of
+/// the differences rather than every value if not. Takes an optional message, as `assert_eq!`
+/// does.
+#[macro_export]
+macro_rules! assert_matrices_eq {
+    ($expected:expr, $actual:expr $(,)?) => {{
+        let diff = $crate::diff::matrix_diff(&$expected, &$actual);
+        if !diff.is_empty() {
+            ::core::panic!("matrices differ: {diff}");
+        }
+    }};
+    ($expected:expr, $actual:expr, $($arg:tt)+) => {{
+        let diff = $crate::diff::matrix_diff(&$expected, &$actual);
+        if !diff.is_empty() {
+            ::core::panic!("matrices differ: {}: {diff}", ::core::format_args!($($arg)+));
+        }
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::bitrev::BitReversableMatrix;
+    use crate::dense::RowMajorMatrix;
+
+    #[test]
+    fn equal_across_matrix_types() {
+        let m = RowMajorMatrix::new((0..32).collect::<Vec<u32>>(), 4);
+        let twice_reversed = m.as_view().bit_reverse_rows().bit_reverse_rows();
+        assert!(matrix_diff(&m, &twice_reversed).is_empty());
+        assert_matrices_eq!(m, twice_reversed);
+    }
+
+    #[test]
+    fn reports_first_mismatches() {
+        let expected = RowMajorMatrix::new(vec![0u32; 30], 3);
+        let mut actual = expected.clone();
+        for r in 0..10 {
+            actual.row_mut(r)[r % 3] = r as u32 + 1;
+        }
+        let diff = matrix_diff(&expected, &actual);
+        assert_eq!(diff.num_mismatches, 10);
+        assert_eq!(diff.first_mismatches.len(), MAX_REPORTED_MISMATCHES);
+        assert_eq!(
+            diff.first_mismatches[2],
+            Mismatch {
+                row: 2,
+                col: 2,
+                expected: 0,
+                actual: 3
+            }
+        );
+        assert!(diff
+            .to_string()
+            .starts_with("10 mismatching entries among those both matrices have, the first 8"));
+    }
+
+    #[test]
+    fn reports_dimension_mismatches() {
+        let expected = RowMajorMatrix::new(vec![1u32; 6], 2);
+        let actual = RowMajorMatrix::new(vec![1u32; 8], 2);
+        let diff = matrix_diff(&expected, &actual);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.num_mismatches, 0);
+        assert!(diff
+            .to_string()
+            .starts_with("dimensions differ: expected 2x3, got 2x4"));
+    }
+
+    #[test]
+    #[should_panic(expected = "matrices differ: log_h = 3: 1 mismatching entries")]
+    fn assert_with_message() {
+        let expected = RowMajorMatrix::new(vec![1u32, 2], 1);
+        let actual = RowMajorMatrix::new(vec![1u32, 3], 1);
+        assert_matrices_eq!(expected, actual, "log_h = {}", 3);
+    }
+}
@@ -19,9 +19,12 @@ use tracing::instrument;
 use crate::dense::RowMajorMatrix;
 
 pub mod bitrev;
+pub mod column_major;
 pub mod dense;
+pub mod diff;
 pub mod extension;
 pub mod mul;
+pub mod periodic;
 pub mod row_index_mapped;
 pub mod sparse;
 pub mod stack;
@@ -71,10 +74,48 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
         (0..self.height()).map(move |r| self.row(r))
     }
 
+    /// The rows from `start` up to but excluding `end`. For an index-mapped view, such as a
+    /// `BitReversedMatrixView`, these are the view's rows, so the range applies to the mapped
+    /// indices.
+    fn rows_between(&self, start: usize, end: usize) -> impl Iterator<Item = Self::Row<'_>> {
+        assert!(
+            start <= end && end <= self.height(),
+            "rows {start}..{end} out of bounds for a matrix of height {}",
+            self.height()
+        );
+        (start..end).map(move |r| self.row(r))
+    }
+
     fn par_rows(&self) -> impl IndexedParallelIterator<Item = Self::Row<'_>> {
         (0..self.height()).into_par_iter().map(move |r| self.row(r))
     }
 
+    /// The rows in consecutive groups of `chunk_rows`, the last of which may be shorter, with the
+    /// groups handed out in parallel.
+    fn par_row_chunks(
+        &self,
+        chunk_rows: usize,
+    ) -> impl IndexedParallelIterator<Item = impl Iterator<Item = Self::Row<'_>> + Send> {
+        assert_ne!(chunk_rows, 0, "chunks must have at least one row");
+        let height = self.height();
+        (0..height.div_ceil(chunk_rows))
+            .into_par_iter()
+            .map(move |i| {
+                let start = i * chunk_rows;
+                (start..height.min(start + chunk_rows)).map(move |r| self.row(r))
+            })
+    }
+
+    /// The entries of column `c`, from the first row to the last.
+    fn column(&self, c: usize) -> impl Iterator<Item = T> {
+        assert!(c < self.width(), "column {c} out of bounds");
+        (0..self.height()).map(move |r| self.get(r, c))
+    }
+
+    fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = T>> {
+        (0..self.width()).map(move |c| self.column(c))
+    }
+
     // Opaque return type implicitly captures &'_ self
     fn row_slice(&self, r: usize) -> impl Deref<Target = [T]> {
         self.row(r).collect_vec()
@@ -99,6 +140,21 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
         )
     }
 
+    /// Like `to_row_major_matrix`, but with room for `capacity_rows` rows, so that the result can
+    /// be grown to that height (for example into an LDE) without reallocating. Rows are copied
+    /// straight into the larger buffer, so a lazily generated matrix is only materialized once.
+    fn to_row_major_matrix_with_capacity(self, capacity_rows: usize) -> RowMajorMatrix<T>
+    where
+        Self: Sized,
+        T: Clone,
+    {
+        let width = self.width();
+        let mut values = Vec::with_capacity(width * capacity_rows.max(self.height()));
+        values.extend((0..self.height()).flat_map(|r| self.row(r)));
+        RowMajorMatrix::new(values, width)
+    }
+
+    /// Row `r` as whole packings of `P::WIDTH` elements, followed by the elements left over.
     fn horizontally_packed_row<'a, P>(
         &'a self,
         r: usize,
@@ -111,8 +167,11 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
         T: Clone + 'a,
     {
         let num_packed = self.width() / P::WIDTH;
-        let packed = (0..num_packed).map(move |c| P::from_fn(|i| self.get(r, P::WIDTH * c + i)));
-        let sfx = (num_packed * P::WIDTH..self.width()).map(move |c| self.get(r, c));
+        // Read the row once rather than calling `get`, which by default walks the row each time.
+        // This relies on `P::from_fn` filling the lanes in order, as `array::from_fn` does.
+        let mut row = self.row(r);
+        let packed = (0..num_packed).map(move |_| P::from_fn(|_| row.next().unwrap()));
+        let sfx = self.row(r).skip(num_packed * P::WIDTH);
         (packed, sfx)
     }
 
@@ -208,6 +267,11 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
             .collect_vec()
     }
 
+    /// A view of every `stride`-th row, starting at row `offset`.
+    ///
+    /// The rows are those of `self` as it reads, so if `self` is itself an index-mapped view, such
+    /// as a `BitReversedMatrixView`, the stride applies after its map. To stride the underlying
+    /// rows instead, stride the inner matrix before wrapping it.
     fn vertically_strided(self, stride: usize, offset: usize) -> VerticallyStridedMatrixView<Self>
     where
         Self: Sized,
@@ -215,6 +279,22 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
         VerticallyStridedRowIndexMap::new_view(self, stride, offset)
     }
 
+    /// Views of the even and odd rows, as interleaved for folding.
+    fn interleaved_halves(
+        self,
+    ) -> (
+        VerticallyStridedMatrixView<Self>,
+        VerticallyStridedMatrixView<Self>,
+    )
+    where
+        Self: Sized + Clone,
+    {
+        (
+            self.clone().vertically_strided(2, 0),
+            self.vertically_strided(2, 1),
+        )
+    }
+
     /// Compute Mᵀv, aka premultiply this matrix by the given vector,
     /// aka scale each row by the corresponding entry in `v` and take the sum across rows.
     /// `v` can be a vector of extension elements.
@@ -262,24 +342,59 @@ pub trait Matrix<T: Send + Sync>: Send + Sync {
     {
         let powers_packed = base
             .ext_powers_packed()
-            .take(self.width().next_multiple_of(T::Packing::WIDTH))
+            .take(self.width().div_ceil(T::Packing::WIDTH))
             .collect_vec();
         self.par_padded_horizontally_packed_rows::<T::Packing>()
-            .map(move |row_packed| {
-                let packed_sum_of_packed: EF::ExtensionPacking =
-                    dot_product(powers_packed.iter().copied(), row_packed);
-                let sum_of_packed: EF = EF::from_base_fn(|i| {
-                    packed_sum_of_packed.as_base_slice()[i]
-                        .as_slice()
-                        .iter()
-                        .copied()
-                        .sum()
-                });
-                sum_of_packed
+            .map(move |row_packed| packed_row_dot_product(&powers_packed, row_packed))
+    }
+
+    /// Multiply this matrix by the vector `coeffs` of extension elements, giving one result per
+    /// row. Like `dot_ext_powers`, each row is read as packed base field values and multiplied by
+    /// the coefficients transposed into packed extension elements, so widths that aren't a
+    /// multiple of the packing width only cost a zero-padded final packing.
+    #[instrument(level = "debug", skip_all, fields(dims = %self.dimensions()))]
+    fn rowwise_packed_dot_product<EF>(&self, coeffs: &[EF]) -> Vec<EF>
+    where
+        T: Field,
+        EF: ExtensionField<T>,
+    {
+        assert_eq!(
+            coeffs.len(),
+            self.width(),
+            "expected one coefficient per column"
+        );
+        let coeffs_packed = coeffs
+            .chunks(T::Packing::WIDTH)
+            .map(|chunk| {
+                EF::ExtensionPacking::from_base_fn(|i| {
+                    T::Packing::from_fn(|j| chunk.get(j).map_or(T::ZERO, |c| c.as_base_slice()[i]))
+                })
             })
+            .collect_vec();
+        self.par_padded_horizontally_packed_rows::<T::Packing>()
+            .map(|row_packed| packed_row_dot_product(&coeffs_packed, row_packed))
+            .collect()
     }
 }
 
+/// The dot product of a row, read as packed base field values, with coefficients packed the same
+/// way, summed across the lanes of the packing.
+#[inline]
+fn packed_row_dot_product<F: Field, EF: ExtensionField<F>>(
+    coeffs_packed: &[EF::ExtensionPacking],
+    row_packed: impl Iterator<Item = F::Packing>,
+) -> EF {
+    let packed_sum_of_packed: EF::ExtensionPacking =
+        dot_product(coeffs_packed.iter().copied(), row_packed);
+    EF::from_base_fn(|i| {
+        packed_sum_of_packed.as_base_slice()[i]
+            .as_slice()
+            .iter()
+            .copied()
+            .sum()
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use alloc::vec;
@@ -288,9 +403,12 @@ mod tests {
     use p3_baby_bear::BabyBear;
     use p3_field::extension::BinomialExtensionField;
     use p3_field::AbstractField;
-    use rand::thread_rng;
+    use p3_util::reverse_bits_len;
+    use rand::{thread_rng, Rng};
 
     use super::*;
+    use crate::bitrev::BitReversalPerm;
+    use crate::column_major::ColumnMajorMatrix;
 
     #[test]
     fn test_columnwise_dot_product() {
@@ -309,4 +427,221 @@ mod tests {
 
         assert_eq!(m.columnwise_dot_product(&v), expected);
     }
+
+    #[test]
+    fn rowwise_dot_products() {
+        type F = BabyBear;
+        type EF = BinomialExtensionField<BabyBear, 4>;
+
+        let mut rng = thread_rng();
+        let base: EF = rng.gen();
+        // Widths on and off multiples of the packing width, including zero.
+        for width in [0, 1, 3, 4, 7, 8, 9, 16, 17, 33] {
+            let m = RowMajorMatrix::<F>::rand(&mut rng, 1 << 4, width);
+            let coeffs = RowMajorMatrix::<EF>::rand(&mut rng, width, 1).values;
+
+            let naive = |coeffs: &[EF]| {
+                m.rows()
+                    .map(|row| izip!(coeffs, row).map(|(&c, x)| c * x).sum::<EF>())
+                    .collect_vec()
+            };
+            assert_eq!(m.rowwise_packed_dot_product(&coeffs), naive(&coeffs));
+
+            let powers = base.powers().take(width).collect_vec();
+            assert_eq!(m.dot_ext_powers(base).collect::<Vec<_>>(), naive(&powers));
+            // The same through a view, whose rows are packed one element at a time. A matrix of
+            // width 0 has height 0, which has no bit reversal.
+            if width > 0 {
+                assert_eq!(
+                    BitReversalPerm::new_view(BitReversalPerm::new_view(m.as_view()))
+                        .rowwise_packed_dot_product(&coeffs),
+                    naive(&coeffs)
+                );
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "expected one coefficient per column")]
+    fn rowwise_dot_product_with_wrong_width() {
+        let m = RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 4, 3);
+        m.rowwise_packed_dot_product(&[BabyBear::ONE; 2]);
+    }
+
+    #[test]
+    fn rows_between_mapped_rows() {
+        let m = RowMajorMatrix::new((0..16).collect_vec(), 2);
+        assert!(m
+            .rows_between(2, 4)
+            .map(|row| row.collect_vec())
+            .eq([vec![4, 5], vec![6, 7]]));
+
+        // Through a bit-reversed view, the range selects the view's rows 2 and 3, which are the
+        // inner matrix's rows 2 and 6.
+        let bitrev = BitReversalPerm::new_view(m.as_view());
+        assert!(bitrev
+            .rows_between(2, 4)
+            .map(|row| row.collect_vec())
+            .eq([vec![4, 5], vec![12, 13]]));
+        assert_eq!(bitrev.rows_between(8, 8).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "rows 3..9 out of bounds for a matrix of height 8")]
+    fn rows_between_past_the_end() {
+        let m = RowMajorMatrix::new((0..16).collect_vec(), 2);
+        let _ = m.rows_between(3, 9);
+    }
+
+    #[test]
+    fn columns_follow_mapped_rows() {
+        let m = RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 1 << 4, 3);
+        let bitrev = BitReversalPerm::new_view(m.as_view());
+        for c in 0..m.width() {
+            let expected = (0..m.height())
+                .map(|r| m.get(reverse_bits_len(r, 4), c))
+                .collect_vec();
+            assert_eq!(bitrev.column(c).collect_vec(), expected);
+        }
+        let transposed = BitReversalPerm::new_view(m.as_view())
+            .to_row_major_matrix()
+            .transpose();
+        assert!(bitrev
+            .columns()
+            .map(|col| col.collect_vec())
+            .eq(transposed.rows().map(|row| row.collect_vec())));
+    }
+
+    #[test]
+    fn to_row_major_matrix_with_capacity() {
+        let m = RowMajorMatrix::new((0..12).collect_vec(), 3);
+        let column_major = ColumnMajorMatrix::new(
+            (0..3)
+                .map(|c| (0..4).map(|r| 3 * r + c).collect())
+                .collect(),
+        );
+
+        // Reversing twice reads the rows back in order, one at a time.
+        let from_view = BitReversalPerm::new_view(BitReversalPerm::new_view(m.as_view()))
+            .to_row_major_matrix_with_capacity(16);
+        let from_dense = m.clone().to_row_major_matrix_with_capacity(16);
+        let from_column_major = column_major.to_row_major_matrix_with_capacity(16);
+        for copy in [from_view, from_dense, from_column_major] {
+            assert_eq!(copy, m);
+            assert!(copy.values.capacity() >= 48);
+        }
+
+        // Asking for fewer rows than the matrix has still copies all of them.
+        assert_eq!(m.as_view().to_row_major_matrix_with_capacity(1), m);
+    }
+
+    #[test]
+    fn interleaved_halves() {
+        let m = RowMajorMatrix::new((0..16).collect_vec(), 2);
+        let (evens, odds) = m.as_view().interleaved_halves();
+        assert_eq!(
+            evens.to_row_major_matrix().values,
+            vec![0, 1, 4, 5, 8, 9, 12, 13]
+        );
+        assert_eq!(
+            odds.to_row_major_matrix().values,
+            vec![2, 3, 6, 7, 10, 11, 14, 15]
+        );
+
+        // Striding a bit-reversed view takes every other row of the bit-reversed order, while
+        // bit-reversing a strided matrix reorders the strided rows.
+        let (bitrev_evens, _) = BitReversalPerm::new_view(m.as_view()).interleaved_halves();
+        assert_eq!(
+            bitrev_evens.to_row_major_matrix().values,
+            vec![0, 1, 4, 5, 2, 3, 6, 7]
+        );
+        let evens_bitrev = BitReversalPerm::new_view(m.as_view().vertically_strided(2, 0));
+        assert_eq!(
+            evens_bitrev.to_row_major_matrix().values,
+            vec![0, 1, 8, 9, 4, 5, 12, 13]
+        );
+    }
+
+    #[test]
+    fn dense_columns() {
+        let m = RowMajorMatrix::new((0..12).collect_vec(), 3);
+        assert_eq!(m.column_to_vec(0), vec![0, 3, 6, 9]);
+        assert_eq!(m.as_view().column(2).collect_vec(), vec![2, 5, 8, 11]);
+        assert_eq!(m.columns().count(), 3);
+
+        let empty = RowMajorMatrix::<u32>::new(vec![], 3);
+        assert_eq!(empty.column(2).count(), 0);
+    }
+
+    fn packed_rows_match_rows<M: Matrix<BabyBear>>(m: &M) {
+        type P = <BabyBear as Field>::Packing;
+        for r in 0..m.height() {
+            let (packed, sfx) = m.horizontally_packed_row::<P>(r);
+            let unpacked = packed
+                .flat_map(|p| p.as_slice().to_vec())
+                .chain(sfx)
+                .collect_vec();
+            assert_eq!(unpacked, m.row(r).collect_vec(), "row {r}");
+            let (packed, sfx) = m.horizontally_packed_row::<P>(r);
+            assert_eq!(packed.count(), m.width() / P::WIDTH);
+            assert!(sfx.count() < P::WIDTH);
+        }
+    }
+
+    #[test]
+    fn horizontally_packed_rows() {
+        let width = <BabyBear as Field>::Packing::WIDTH;
+        for w in [0, 1, width, width + 3] {
+            let m = RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 4, w);
+            // Dense rows are reinterpreted in place; other matrices pack their row iterators.
+            packed_rows_match_rows(&m);
+            let columns = (0..w).map(|c| m.column_to_vec(c)).collect_vec();
+            packed_rows_match_rows(&ColumnMajorMatrix::new(columns));
+        }
+    }
+
+    fn par_rows_match_rows<M: Matrix<u32>>(m: &M) {
+        let rows = m.rows().map(|r| r.collect_vec()).collect_vec();
+        assert_eq!(
+            m.par_rows().map(|r| r.collect_vec()).collect::<Vec<_>>(),
+            rows
+        );
+        for chunk_rows in [1, 3, 4, 16] {
+            let chunks = m
+                .par_row_chunks(chunk_rows)
+                .map(|chunk| chunk.map(|r| r.collect_vec()).collect_vec())
+                .collect::<Vec<_>>();
+            assert_eq!(
+                chunks,
+                rows.chunks(chunk_rows).map(<[_]>::to_vec).collect_vec(),
+                "chunk_rows = {chunk_rows}"
+            );
+        }
+    }
+
+    #[test]
+    fn par_rows() {
+        let m = RowMajorMatrix::new((0..24).collect_vec(), 3);
+        // Dense matrices hand out slices; other matrices fall back to indexing rows.
+        par_rows_match_rows(&m);
+        par_rows_match_rows(&m.as_view());
+        par_rows_match_rows(&BitReversalPerm::new_view(m));
+
+        par_rows_match_rows(&RowMajorMatrix::<u32>::new(vec![], 3));
+        par_rows_match_rows(&RowMajorMatrix::<u32>::new(vec![], 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "chunks must have at least one row")]
+    fn par_row_chunks_of_zero_rows() {
+        let m = RowMajorMatrix::new((0..24).collect_vec(), 3);
+        // Through the trait, as `DenseMatrix` has an inherent `par_row_chunks` yielding views.
+        let _ = Matrix::par_row_chunks(&m, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "column 3 out of bounds")]
+    fn column_out_of_bounds() {
+        RowMajorMatrix::new((0..12).collect_vec(), 3).column_to_vec(3);
+    }
 }
@@ -0,0 +1,107 @@
+use alloc::vec::Vec;
+use core::{iter, slice};
+
+use crate::dense::RowMajorMatrix;
+use crate::Matrix;
+
+/// A short matrix, the pattern, repeated down to a greater height, as for periodic selectors.
+///
+/// Only the pattern is stored. Row `r` is row `r % pattern_height` of the pattern, and
+/// `to_row_major_matrix` tiles the pattern in a single pass.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeriodicMatrix<T> {
+    pattern: RowMajorMatrix<T>,
+    height: usize,
+}
+
+impl<T: Clone + Send + Sync> PeriodicMatrix<T> {
+    /// Repeat `pattern` until the matrix is `height` rows tall. The height needn't be a multiple of
+    /// the pattern's, in which case the last repetition is cut short.
+    pub fn new(pattern: RowMajorMatrix<T>, height: usize) -> Self {
+        assert!(
+            pattern.height() > 0 || height == 0,
+            "cannot repeat an empty pattern to height {height}"
+        );
+        Self { pattern, height }
+    }
+
+    pub const fn pattern(&self) -> &RowMajorMatrix<T> {
+        &self.pattern
+    }
+}
+
+impl<T: Clone + Send + Sync> Matrix<T> for PeriodicMatrix<T> {
+    fn width(&self) -> usize {
+        self.pattern.width()
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    type Row<'a>
+        = iter::Cloned<slice::Iter<'a, T>>
+    where
+        Self: 'a;
+
+    fn row(&self, r: usize) -> Self::Row<'_> {
+        assert!(r < self.height, "row {r} out of bounds");
+        let width = self.width();
+        let start = (r % self.pattern.height()) * width;
+        self.pattern.values[start..start + width].iter().cloned()
+    }
+
+    fn to_row_major_matrix(self) -> RowMajorMatrix<T>
+    where
+        Self: Sized,
+        T: Clone,
+    {
+        let width = self.width();
+        let values: Vec<T> = self
+            .pattern
+            .values
+            .iter()
+            .cycle()
+            .take(width * self.height)
+            .cloned()
+            .collect();
+        RowMajorMatrix::new(values, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn rows_repeat_the_pattern() {
+        let pattern = RowMajorMatrix::new(vec![1, 0, 0, 1, 0, 0], 2);
+        let periodic = PeriodicMatrix::new(pattern, 7);
+        assert_eq!(periodic.height(), 7);
+        assert_eq!(periodic.row(4).collect_vec(), vec![0, 1]);
+        assert_eq!(periodic.get(6, 0), 1);
+
+        let expected = RowMajorMatrix::new(vec![1, 0, 0, 1, 0, 0, 1, 0, 0, 1, 0, 0, 1, 0], 2);
+        // The tiled copy agrees with reading the rows one at a time.
+        assert_eq!(periodic.rows().flatten().collect_vec(), expected.values);
+        assert_eq!(periodic.to_row_major_matrix(), expected);
+    }
+
+    #[test]
+    fn empty_periodic_matrices() {
+        let empty = PeriodicMatrix::new(RowMajorMatrix::<u32>::new(vec![], 2), 0);
+        assert_eq!(empty.to_row_major_matrix().height(), 0);
+        let no_rows = PeriodicMatrix::new(RowMajorMatrix::new(vec![1, 2], 2), 0);
+        assert_eq!(no_rows.to_row_major_matrix().values, Vec::<u32>::new());
+    }
+
+    #[test]
+    #[should_panic(expected = "cannot repeat an empty pattern to height 3")]
+    fn repeat_empty_pattern() {
+        PeriodicMatrix::new(RowMajorMatrix::<u32>::new(vec![], 2), 3);
+    }
+}
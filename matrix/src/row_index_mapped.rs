@@ -1,6 +1,9 @@
+use alloc::vec;
+use alloc::vec::Vec;
 use core::ops::Deref;
 
 use p3_field::PackedValue;
+use p3_maybe_rayon::prelude::*;
 
 use crate::dense::RowMajorMatrix;
 use crate::Matrix;
@@ -31,6 +34,62 @@ pub struct RowIndexMappedView<IndexMap, Inner> {
     pub inner: Inner,
 }
 
+impl<OuterMap: RowIndexMap, InnerMap: RowIndexMap, Inner>
+    RowIndexMappedView<OuterMap, RowIndexMappedView<InnerMap, Inner>>
+{
+    /// Fuse two stacked index maps into one, so that each row access makes a single lookup into
+    /// the innermost matrix, and `to_row_major_matrix` moves every row into place in one pass
+    /// rather than permuting once per map. Deeper stacks can be composed repeatedly.
+    pub fn compose(self) -> RowIndexMappedView<ComposedRowIndexMap, Inner> {
+        let outer = self.index_map;
+        let inner = self.inner.index_map;
+        let rows = (0..outer.height())
+            .into_par_iter()
+            .map(|r| inner.map_row_index(outer.map_row_index(r)))
+            .collect();
+        RowIndexMappedView {
+            index_map: ComposedRowIndexMap { rows },
+            inner: self.inner.inner,
+        }
+    }
+}
+
+/// The composition of several row index maps, as a table of the inner row index for each row.
+#[derive(Clone, Debug)]
+pub struct ComposedRowIndexMap {
+    rows: Vec<usize>,
+}
+
+impl RowIndexMap for ComposedRowIndexMap {
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn map_row_index(&self, r: usize) -> usize {
+        self.rows[r]
+    }
+
+    /// Copy each row straight from the inner matrix to its final position.
+    fn to_row_major_matrix<T: Clone + Send + Sync, Inner: Matrix<T>>(
+        &self,
+        inner: Inner,
+    ) -> RowMajorMatrix<T> {
+        let width = inner.width();
+        let Some(&first) = self.rows.first() else {
+            return RowMajorMatrix::new(vec![], width);
+        };
+        if width == 0 {
+            return RowMajorMatrix::new(vec![], width);
+        }
+        let mut values = vec![inner.get(first, 0); width * self.rows.len()];
+        values
+            .par_chunks_exact_mut(width)
+            .zip(self.rows.par_iter())
+            .for_each(|(row, &r)| row.clone_from_slice(&inner.row_slice(r)));
+        RowMajorMatrix::new(values, width)
+    }
+}
+
 impl<T: Send + Sync, IndexMap: RowIndexMap, Inner: Matrix<T>> Matrix<T>
     for RowIndexMappedView<IndexMap, Inner>
 {
@@ -96,3 +155,43 @@ impl<T: Send + Sync, IndexMap: RowIndexMap, Inner: Matrix<T>> Matrix<T>
             .padded_horizontally_packed_row(self.index_map.map_row_index(r))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use p3_baby_bear::BabyBear;
+    use rand::thread_rng;
+
+    use super::*;
+    use crate::bitrev::BitReversalPerm;
+
+    #[test]
+    fn composed_view_matches_stack() {
+        let m = RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 1 << 6, 3);
+        let stack = || BitReversalPerm::new_view(m.as_view().vertically_strided(2, 1));
+        let composed = stack().compose();
+        assert_eq!(composed.dimensions(), stack().dimensions());
+        for r in 0..composed.height() {
+            assert!(composed.row(r).eq(stack().row(r)));
+        }
+        assert_eq!(
+            composed.to_row_major_matrix(),
+            stack().to_row_major_matrix()
+        );
+
+        // A deeper stack composes one layer at a time.
+        let deep = BitReversalPerm::new_view(stack()).compose().compose();
+        assert_eq!(
+            deep.to_row_major_matrix(),
+            m.as_view().vertically_strided(2, 1).to_row_major_matrix()
+        );
+    }
+
+    #[test]
+    fn inverse_maps_compose_to_identity() {
+        let m = RowMajorMatrix::<BabyBear>::rand(&mut thread_rng(), 1 << 5, 2);
+        let twice = BitReversalPerm::new_view(BitReversalPerm::new_view(m.as_view())).compose();
+        assert_eq!(twice.index_map.rows, (0..1 << 5).collect_vec());
+        assert_eq!(twice.to_row_major_matrix(), m);
+    }
+}
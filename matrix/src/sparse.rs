@@ -37,6 +37,39 @@ impl<T: Clone + Default + Send + Sync> CsrMatrix<T> {
         &mut self.nonzero_values[range]
     }
 
+    /// A `height` by `width` matrix which is `T::default()` everywhere except at the given
+    /// `(row, col, value)` triples. If a position is given more than once, the last value wins.
+    pub fn from_triples(
+        width: usize,
+        height: usize,
+        triples: impl IntoIterator<Item = (usize, usize, T)>,
+    ) -> Self {
+        let mut triples: Vec<_> = triples.into_iter().collect();
+        for &(r, c, _) in &triples {
+            assert!(
+                r < height && c < width,
+                "entry ({r}, {c}) is outside a {height}x{width} matrix"
+            );
+        }
+        // A stable sort keeps repeated positions in their given order, so `row` writes the last one
+        // over the others.
+        triples.sort_by_key(|&(r, _, _)| r);
+
+        let mut row_indices = vec![0; height + 1];
+        for &(r, _, _) in &triples {
+            row_indices[r + 1] += 1;
+        }
+        for r in 0..height {
+            row_indices[r + 1] += row_indices[r];
+        }
+        let nonzero_values = triples.into_iter().map(|(_, c, v)| (c, v)).collect();
+        Self {
+            width,
+            nonzero_values,
+            row_indices,
+        }
+    }
+
     pub fn rand_fixed_row_weight<R: Rng>(
         rng: &mut R,
         rows: usize,
@@ -71,6 +104,7 @@ impl<T: Clone + Default + Send + Sync> Matrix<T> for CsrMatrix<T> {
     fn get(&self, r: usize, c: usize) -> T {
         self.sparse_row(r)
             .iter()
+            .rev()
             .find(|(col, _)| *col == c)
             .map(|(_, val)| val.clone())
             .unwrap_or_default()
@@ -89,3 +123,29 @@ impl<T: Clone + Default + Send + Sync> Matrix<T> for CsrMatrix<T> {
         row.into_iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+
+    #[test]
+    fn from_triples() {
+        let m = CsrMatrix::from_triples(3, 4, [(2, 1, 5u32), (0, 2, 1), (2, 0, 7), (2, 1, 6)]);
+        assert_eq!(m.height(), 4);
+        assert!(m.sparse_row(1).is_empty());
+        assert_eq!(m.sparse_row(2).len(), 3);
+        assert_eq!(
+            m.rows().flatten().collect_vec(),
+            vec![0, 0, 1, 0, 0, 0, 7, 6, 0, 0, 0, 0]
+        );
+        assert_eq!(m.get(2, 1), 6);
+    }
+
+    #[test]
+    #[should_panic(expected = "entry (4, 0) is outside a 4x3 matrix")]
+    fn from_triples_out_of_bounds() {
+        CsrMatrix::from_triples(3, 4, [(4, 0, 1u32)]);
+    }
+}
@@ -24,7 +24,11 @@ impl<First, Second> VerticalPair<First, Second> {
         First: Matrix<T>,
         Second: Matrix<T>,
     {
-        assert_eq!(first.width(), second.width());
+        assert_eq!(
+            first.width(),
+            second.width(),
+            "vertically stacked matrices must have the same width"
+        );
         Self { first, second }
     }
 }
@@ -36,7 +40,11 @@ impl<First, Second> HorizontalPair<First, Second> {
         First: Matrix<T>,
         Second: Matrix<T>,
     {
-        assert_eq!(first.height(), second.height());
+        assert_eq!(
+            first.height(),
+            second.height(),
+            "horizontally stacked matrices must have the same height"
+        );
         Self { first, second }
     }
 }
@@ -146,3 +154,76 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::bitrev::BitReversalPerm;
+    use crate::dense::RowMajorMatrix;
+
+    #[test]
+    fn vertical_pair() {
+        let top = RowMajorMatrix::new((0..6).collect_vec(), 2);
+        let bottom = RowMajorMatrix::new((6..10).collect_vec(), 2);
+        let pair = VerticalPair::new(top.as_view(), bottom.as_view());
+        let expected = RowMajorMatrix::new((0..10).collect_vec(), 2);
+
+        assert_eq!(pair.dimensions(), expected.dimensions());
+        for r in 0..5 {
+            assert!(pair.row(r).eq(expected.row(r)));
+            assert_eq!(&*pair.row_slice(r), &*expected.row_slice(r));
+            assert_eq!(pair.get(r, 1), expected.get(r, 1));
+        }
+        assert_eq!(pair.to_row_major_matrix(), expected);
+    }
+
+    #[test]
+    fn horizontal_pair() {
+        let left = RowMajorMatrix::new(vec![0, 1, 3, 4], 2);
+        let right = RowMajorMatrix::new(vec![2, 5], 1);
+        let pair = HorizontalPair::new(left, right);
+        let expected = RowMajorMatrix::new((0..6).collect_vec(), 3);
+
+        assert_eq!(pair.dimensions(), expected.dimensions());
+        for (r, c) in (0..2).cartesian_product(0..3) {
+            assert_eq!(pair.get(r, c), expected.get(r, c));
+        }
+        assert_eq!(pair.to_row_major_matrix(), expected);
+    }
+
+    #[test]
+    fn pairs_compose_with_bit_reversal() {
+        let top = RowMajorMatrix::new((0..12).collect_vec(), 2);
+        let bottom = RowMajorMatrix::new((12..16).collect_vec(), 2);
+        let pair = VerticalPair::new(top.as_view(), bottom.as_view());
+        let concatenated = RowMajorMatrix::new((0..16).collect_vec(), 2);
+
+        let pair = BitReversalPerm::new_view(pair);
+        let concatenated = BitReversalPerm::new_view(concatenated);
+        for r in 0..8 {
+            assert!(pair.row(r).eq(concatenated.row(r)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "vertically stacked matrices must have the same width")]
+    fn vertical_pair_mismatched_widths() {
+        VerticalPair::new(
+            RowMajorMatrix::new(vec![0; 4], 2),
+            RowMajorMatrix::new(vec![0; 3], 3),
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "horizontally stacked matrices must have the same height")]
+    fn horizontal_pair_mismatched_heights() {
+        HorizontalPair::new(
+            RowMajorMatrix::new(vec![0; 4], 2),
+            RowMajorMatrix::new(vec![0; 3], 1),
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use alloc::vec::Vec;
-use core::cell::RefCell;
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
 use itertools::Itertools;
 use p3_commit::Mmcs;
@@ -25,8 +25,8 @@ use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
 /// (`P::Value`) is at least the target security parameter.
 ///
 /// `R` should be an appropriately seeded cryptographically secure pseudorandom number generator
-/// (CSPRNG). Something like `ThreadRng` may work, although it relies on the operating system to
-/// provide sufficient entropy.
+/// (CSPRNG), such as `StdRng::from_entropy()`. It sits behind a mutex, so that the MMCS is `Sync`,
+/// as provers answering queries in parallel require, whenever `R` is `Send`. `ThreadRng` isn't.
 ///
 /// Generics:
 /// - `P`: a leaf value
@@ -34,11 +34,25 @@ use crate::{MerkleTree, MerkleTreeError, MerkleTreeMmcs};
 /// - `H`: the leaf hasher
 /// - `C`: the digest compression function
 /// - `R`: a random number generator for blinding leaves
-#[derive(Clone, Debug)]
+#[derive(Debug)]
 pub struct MerkleTreeHidingMmcs<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
 {
     inner: MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>,
-    rng: RefCell<R>,
+    rng: Mutex<R>,
+}
+
+impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize> Clone
+    for MerkleTreeHidingMmcs<P, PW, H, C, R, DIGEST_ELEMS, SALT_ELEMS>
+where
+    MerkleTreeMmcs<P, PW, H, C, DIGEST_ELEMS>: Clone,
+    R: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            rng: Mutex::new(self.lock_rng().clone()),
+        }
+    }
 }
 
 impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
@@ -48,9 +62,13 @@ impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize>
         let inner = MerkleTreeMmcs::new(hash, compress);
         Self {
             inner,
-            rng: rng.into(),
+            rng: Mutex::new(rng),
         }
     }
+
+    fn lock_rng(&self) -> MutexGuard<'_, R> {
+        self.rng.lock().unwrap_or_else(PoisonError::into_inner)
+    }
 }
 
 impl<P, PW, H, C, R, const DIGEST_ELEMS: usize, const SALT_ELEMS: usize> Mmcs<P::Value>
@@ -75,6 +93,9 @@ where
     type Commitment = Hash<P::Value, PW::Value, DIGEST_ELEMS>;
     /// The first item is salts; the second is the usual Merkle proof (sibling digests).
     type Proof = (Vec<Vec<P::Value>>, Vec<[PW::Value; DIGEST_ELEMS]>);
+    /// The first item is salts, indexed by query and then matrix; the second is the inner
+    /// multi-proof.
+    type MultiProof = (Vec<Vec<Vec<P::Value>>>, Vec<[PW::Value; DIGEST_ELEMS]>);
     type Error = MerkleTreeError;
 
     fn commit<M: Matrix<P::Value>>(
@@ -84,8 +105,7 @@ where
         let salted_inputs = inputs
             .into_iter()
             .map(|mat| {
-                let salts =
-                    RowMajorMatrix::rand(&mut *self.rng.borrow_mut(), mat.height(), SALT_ELEMS);
+                let salts = RowMajorMatrix::rand(&mut *self.lock_rng(), mat.height(), SALT_ELEMS);
                 HorizontalPair::new(mat, salts)
             })
             .collect();
@@ -111,6 +131,26 @@ where
         (openings, (salts, siblings))
     }
 
+    fn open_multi_batch<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &Self::ProverData<M>,
+    ) -> (Vec<Vec<Vec<P::Value>>>, Self::MultiProof) {
+        let (salted_openings, siblings) = self.inner.open_multi_batch(indices, prover_data);
+        let (openings, salts): (Vec<_>, Vec<_>) = salted_openings
+            .into_iter()
+            .map(|rows| -> (Vec<_>, Vec<_>) {
+                rows.into_iter()
+                    .map(|row| {
+                        let (a, b) = row.split_at(row.len() - SALT_ELEMS);
+                        (a.to_vec(), b.to_vec())
+                    })
+                    .unzip()
+            })
+            .unzip();
+        (openings, (salts, siblings))
+    }
+
     fn get_matrices<'a, M: Matrix<P::Value>>(
         &self,
         prover_data: &'a Self::ProverData<M>,
@@ -137,6 +177,34 @@ where
         self.inner
             .verify_batch(commit, dimensions, index, &opened_salted_values, siblings)
     }
+
+    fn verify_multi_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<P::Value>>],
+        proof: &Self::MultiProof,
+    ) -> Result<(), Self::Error> {
+        let (salts, siblings) = proof;
+        if salts.len() != opened_values.len() {
+            return Err(MerkleTreeError::WrongBatchSize);
+        }
+
+        let opened_salted_values = opened_values
+            .iter()
+            .zip(salts.iter())
+            .map(|(rows, row_salts)| {
+                rows.iter()
+                    .zip(row_salts.iter())
+                    .map(|(opened, salt)| opened.iter().chain(salt.iter()).copied().collect_vec())
+                    .collect_vec()
+            })
+            .collect_vec();
+
+        self.inner
+            .verify_multi_batch(commit, dimensions, indices, &opened_salted_values, siblings)
+    }
 }
 
 #[cfg(test)]
@@ -208,4 +276,24 @@ mod tests {
         let (opened_values, proof) = mmcs.open_batch(17, &prover_data);
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
     }
+
+    #[test]
+    fn multi_open() -> Result<(), MerkleTreeError> {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress, thread_rng());
+
+        let mats = vec![
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 32, 3),
+            RowMajorMatrix::<F>::rand(&mut thread_rng(), 4, 5),
+        ];
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+
+        let (commit, prover_data) = mmcs.commit(mats);
+        let indices = [17, 2, 17, 30];
+        let (opened_values, proof) = mmcs.open_multi_batch(&indices, &prover_data);
+        mmcs.verify_multi_batch(&commit, &dims, &indices, &opened_values, &proof)
+    }
 }
@@ -1,6 +1,7 @@
 #![no_std]
 
 extern crate alloc;
+extern crate std;
 
 mod hiding_mmcs;
 mod merkle_tree;
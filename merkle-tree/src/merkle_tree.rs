@@ -33,12 +33,15 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
 {
     /// Matrix heights need not be powers of two. However, if the heights of two given matrices
     /// round up to the same power of two, they must be equal.
+    ///
+    /// If no matrices are given, the tree consists of a single root with the default digest.
     #[instrument(name = "build merkle tree", level = "debug", skip_all,
                  fields(dimensions = alloc::format!("{:?}", leaves.iter().map(|l| l.dimensions()).collect::<Vec<_>>())))]
     pub fn new<P, PW, H, C>(h: &H, c: &C, leaves: Vec<M>) -> Self
     where
         P: PackedValue<Value = F>,
         PW: PackedValue<Value = W>,
+        W: Default,
         H: CryptographicHasher<F, [W; DIGEST_ELEMS]>,
         H: CryptographicHasher<P, [PW; DIGEST_ELEMS]>,
         H: Sync,
@@ -46,7 +49,13 @@ impl<F: Clone + Send + Sync, W: Clone, M: Matrix<F>, const DIGEST_ELEMS: usize>
         C: PseudoCompressionFunction<[PW; DIGEST_ELEMS], 2>,
         C: Sync,
     {
-        assert!(!leaves.is_empty(), "No matrices given?");
+        if leaves.is_empty() {
+            return Self {
+                leaves,
+                digest_layers: vec![vec![array::from_fn(|_| W::default())]],
+                _phantom: PhantomData,
+            };
+        }
 
         assert_eq!(P::WIDTH, PW::WIDTH, "Packing widths must match");
 
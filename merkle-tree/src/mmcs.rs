@@ -1,3 +1,6 @@
+use alloc::collections::btree_map::Entry;
+use alloc::collections::BTreeMap;
+use alloc::vec;
 use alloc::vec::Vec;
 use core::cmp::Reverse;
 use core::marker::PhantomData;
@@ -65,6 +68,9 @@ where
     type ProverData<M> = MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS>;
     type Commitment = Hash<P::Value, PW::Value, DIGEST_ELEMS>;
     type Proof = Vec<[PW::Value; DIGEST_ELEMS]>;
+    /// The sibling digests which can't be derived from other opened paths, ordered by layer
+    /// (leaves first) and then by node index.
+    type MultiProof = Vec<[PW::Value; DIGEST_ELEMS]>;
     type Error = MerkleTreeError;
 
     fn commit<M: Matrix<P::Value>>(
@@ -102,6 +108,47 @@ where
         (openings, proof)
     }
 
+    fn open_multi_batch<M: Matrix<P::Value>>(
+        &self,
+        indices: &[usize],
+        prover_data: &MerkleTree<P::Value, PW::Value, M, DIGEST_ELEMS>,
+    ) -> (Vec<Vec<Vec<P::Value>>>, Vec<[PW::Value; DIGEST_ELEMS]>) {
+        let max_height = self.get_max_height(prover_data);
+        let log_max_height = log2_ceil_usize(max_height);
+
+        let openings = indices
+            .iter()
+            .map(|&index| {
+                prover_data
+                    .leaves
+                    .iter()
+                    .map(|matrix| {
+                        let log2_height = log2_ceil_usize(matrix.height());
+                        let bits_reduced = log_max_height - log2_height;
+                        let reduced_index = index >> bits_reduced;
+                        matrix.row(reduced_index).collect()
+                    })
+                    .collect()
+            })
+            .collect_vec();
+
+        // Walk up the tree a layer at a time. A sibling only needs to be sent if it isn't itself
+        // on the path of another opened index, as the verifier will compute it in that case.
+        let mut nodes = indices.iter().copied().sorted().dedup().collect_vec();
+        let mut proof = vec![];
+        for layer in 0..log_max_height {
+            for &node in &nodes {
+                let sibling = node ^ 1;
+                if nodes.binary_search(&sibling).is_err() {
+                    proof.push(prover_data.digest_layers[layer][sibling]);
+                }
+            }
+            nodes = nodes.into_iter().map(|node| node >> 1).dedup().collect();
+        }
+
+        (openings, proof)
+    }
+
     fn get_matrices<'a, M: Matrix<P::Value>>(
         &self,
         prover_data: &'a Self::ProverData<M>,
@@ -121,6 +168,9 @@ where
         if dimensions.len() != opened_values.len() {
             return Err(WrongBatchSize);
         }
+        if dimensions.is_empty() {
+            return verify_empty_batch(commit, proof.len());
+        }
 
         // TODO: Disabled for now since TwoAdicFriPcs and CirclePcs currently pass 0 for width.
         // for (dims, opened_vals) in dimensions.iter().zip(opened_values) {
@@ -192,17 +242,162 @@ where
             Err(RootMismatch)
         }
     }
+
+    fn verify_multi_batch(
+        &self,
+        commit: &Self::Commitment,
+        dimensions: &[Dimensions],
+        indices: &[usize],
+        opened_values: &[Vec<Vec<P::Value>>],
+        proof: &Self::MultiProof,
+    ) -> Result<(), Self::Error> {
+        // Check that the openings have the correct shape.
+        if indices.len() != opened_values.len()
+            || opened_values
+                .iter()
+                .any(|openings| openings.len() != dimensions.len())
+        {
+            return Err(WrongBatchSize);
+        }
+        if dimensions.is_empty() {
+            return verify_empty_batch(commit, proof.len());
+        }
+        if indices.is_empty() {
+            return if proof.is_empty() {
+                Ok(())
+            } else {
+                Err(WrongBatchSize)
+            };
+        }
+
+        let max_height = dimensions.iter().map(|dim| dim.height).max().unwrap();
+        let log_max_height = log2_ceil_usize(max_height);
+        let wrong_height = || WrongHeight {
+            max_height,
+            num_siblings: proof.len(),
+        };
+
+        // Group the matrices by their padded height, tallest first. Each group is hashed into the
+        // layer of the tree whose size matches the group's padded height.
+        let mut heights_tallest_first = dimensions
+            .iter()
+            .enumerate()
+            .sorted_by_key(|(_, dims)| Reverse(dims.height))
+            .peekable();
+        let mut groups = vec![];
+        while let Some(&(_, dims)) = heights_tallest_first.peek() {
+            let height_padded = dims.height.next_power_of_two();
+            let group = heights_tallest_first
+                .peeking_take_while(|(_, dims)| dims.height.next_power_of_two() == height_padded)
+                .map(|(i, _)| i)
+                .collect_vec();
+            groups.push((log2_ceil_usize(height_padded), group));
+        }
+        let mut groups = groups.into_iter().peekable();
+
+        let hash_group = |query: usize, group: &[usize]| -> [PW::Value; DIGEST_ELEMS] {
+            self.hash
+                .hash_iter_slices(group.iter().map(|&i| opened_values[query][i].as_slice()))
+        };
+
+        let (_, tallest_group) = groups.next().unwrap();
+        let mut nodes = BTreeMap::new();
+        for (query, &index) in indices.iter().enumerate() {
+            insert_consistent(&mut nodes, index, hash_group(query, &tallest_group))?;
+        }
+
+        let mut siblings = proof.iter();
+        for layer in 0..log_max_height {
+            let mut next_nodes = BTreeMap::new();
+            for (&node, &digest) in &nodes {
+                let sibling = match nodes.get(&(node ^ 1)) {
+                    // Both children are known, so the pair is compressed once, at the left child.
+                    Some(_) if node & 1 == 1 => continue,
+                    Some(&sibling) => sibling,
+                    None => *siblings.next().ok_or_else(wrong_height)?,
+                };
+                let (left, right) = if node & 1 == 0 {
+                    (digest, sibling)
+                } else {
+                    (sibling, digest)
+                };
+                next_nodes.insert(node >> 1, self.compress.compress([left, right]));
+            }
+            nodes = next_nodes;
+
+            let log_layer_height = log_max_height - layer - 1;
+            if let Some((_, group)) = groups.next_if(|(log_h, _)| *log_h == log_layer_height) {
+                let mut injected = BTreeMap::new();
+                for (query, &index) in indices.iter().enumerate() {
+                    insert_consistent(
+                        &mut injected,
+                        index >> (layer + 1),
+                        hash_group(query, &group),
+                    )?;
+                }
+                for (node, digest) in nodes.iter_mut() {
+                    *digest = self.compress.compress([*digest, injected[node]]);
+                }
+            }
+        }
+
+        if siblings.next().is_some() {
+            return Err(wrong_height());
+        }
+
+        match nodes.get(&0) {
+            Some(root) if nodes.len() == 1 && commit == root => Ok(()),
+            _ => Err(RootMismatch),
+        }
+    }
+}
+
+/// Check an opening of an empty batch, whose tree is a lone root with the default digest.
+fn verify_empty_batch<F, W: Default + Copy + PartialEq, const DIGEST_ELEMS: usize>(
+    commit: &Hash<F, W, DIGEST_ELEMS>,
+    num_siblings: usize,
+) -> Result<(), MerkleTreeError> {
+    if num_siblings != 0 {
+        return Err(WrongHeight {
+            max_height: 0,
+            num_siblings,
+        });
+    }
+    if *commit == [W::default(); DIGEST_ELEMS] {
+        Ok(())
+    } else {
+        Err(RootMismatch)
+    }
+}
+
+/// Record `digest` as the digest of `node`, checking that it agrees with any digest previously
+/// recorded for the same node.
+fn insert_consistent<D: Eq>(
+    nodes: &mut BTreeMap<usize, D>,
+    node: usize,
+    digest: D,
+) -> Result<(), MerkleTreeError> {
+    match nodes.entry(node) {
+        Entry::Vacant(entry) => {
+            entry.insert(digest);
+            Ok(())
+        }
+        Entry::Occupied(entry) if *entry.get() == digest => Ok(()),
+        Entry::Occupied(_) => Err(RootMismatch),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use alloc::vec;
+    use alloc::vec::Vec;
 
     use itertools::Itertools;
     use p3_baby_bear::{BabyBear, Poseidon2BabyBear};
     use p3_commit::Mmcs;
     use p3_field::{AbstractField, Field};
-    use p3_matrix::dense::RowMajorMatrix;
+    use p3_matrix::dense::{Padding, RowMajorMatrix};
+    use p3_matrix::stack::VerticalPair;
     use p3_matrix::{Dimensions, Matrix};
     use p3_symmetric::{
         CryptographicHasher, PaddingFreeSponge, PseudoCompressionFunction, TruncatedPermutation,
@@ -425,6 +620,25 @@ mod tests {
         assert_eq!(commit_1_2, commit_2_1);
     }
 
+    #[test]
+    fn commit_vertical_pair() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        // A trace stacked above its padding rows commits just like the two copied together.
+        let trace = RowMajorMatrix::<F>::rand(&mut rng, 6, 5);
+        let padding = RowMajorMatrix::new(vec![F::ZERO; 2 * 5], 5);
+        let mut concatenated = trace.clone();
+        concatenated.pad_to_height(8, Padding::Zero);
+
+        let (pair_commit, _) = mmcs.commit_matrix(VerticalPair::new(trace, padding));
+        let (concatenated_commit, _) = mmcs.commit_matrix(concatenated);
+        assert_eq!(pair_commit, concatenated_commit);
+    }
+
     #[test]
     #[should_panic]
     fn mismatched_heights() {
@@ -558,4 +772,99 @@ mod tests {
         mmcs.verify_batch(&commit, &dims, 17, &opened_values, &proof)
             .expect("expected verification to succeed");
     }
+
+    #[test]
+    fn commit_empty() {
+        let perm = Perm::new_from_rng_128(&mut thread_rng());
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let (commit, prover_data) = mmcs.commit(Vec::<RowMajorMatrix<F>>::new());
+        assert_eq!(commit, [F::ZERO; 8]);
+        assert_eq!(mmcs.get_max_height(&prover_data), 0);
+
+        let (opened_values, proof) = mmcs.open_batch(0, &prover_data);
+        assert!(opened_values.is_empty() && proof.is_empty());
+        mmcs.verify_batch(&commit, &[], 0, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        let (opened_values, proof) = mmcs.open_multi_batch(&[0, 3], &prover_data);
+        assert_eq!(opened_values, vec![Vec::<Vec<F>>::new(); 2]);
+        mmcs.verify_multi_batch(&commit, &[], &[0, 3], &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        // A non-empty commitment can't be passed off as an empty one.
+        let (other_commit, _) =
+            mmcs.commit(vec![RowMajorMatrix::<F>::rand(&mut thread_rng(), 4, 1)]);
+        mmcs.verify_batch(&other_commit, &[], 0, &[], &Vec::new())
+            .expect_err("expected verification to fail");
+    }
+
+    #[test]
+    fn multi_open_size_gaps() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let mats = [1000, 70, 8, 1]
+            .into_iter()
+            .map(|height| RowMajorMatrix::<F>::rand(&mut thread_rng(), height, 8))
+            .collect_vec();
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let indices = [6, 7, 500, 6, 559, 0];
+        let (opened_values, proof) = mmcs.open_multi_batch(&indices, &prover_data);
+        for (&index, openings) in indices.iter().zip(&opened_values) {
+            assert_eq!(openings, &mmcs.open_batch(index, &prover_data).0);
+        }
+        mmcs.verify_multi_batch(&commit, &dims, &indices, &opened_values, &proof)
+            .expect("expected verification to succeed");
+
+        // Siblings shared between paths are only sent once.
+        let individual_len: usize = indices
+            .iter()
+            .map(|&index| mmcs.open_batch(index, &prover_data).1.len())
+            .sum();
+        assert!(proof.len() < individual_len);
+
+        // Opening the same index repeatedly costs no more than opening it once.
+        let (_, proof) = mmcs.open_multi_batch(&[9, 9, 9], &prover_data);
+        assert_eq!(proof.len(), 10);
+    }
+
+    #[test]
+    fn multi_open_tampered_fails() {
+        let mut rng = thread_rng();
+        let perm = Perm::new_from_rng_128(&mut rng);
+        let hash = MyHash::new(perm.clone());
+        let compress = MyCompress::new(perm);
+        let mmcs = MyMmcs::new(hash, compress);
+
+        let mats = [32, 8]
+            .into_iter()
+            .map(|height| RowMajorMatrix::<F>::rand(&mut thread_rng(), height, 3))
+            .collect_vec();
+        let dims = mats.iter().map(|m| m.dimensions()).collect_vec();
+        let (commit, prover_data) = mmcs.commit(mats);
+
+        let indices = [3, 17, 18];
+        let (opened_values, proof) = mmcs.open_multi_batch(&indices, &prover_data);
+
+        let mut bad_proof = proof.clone();
+        bad_proof[0][0] += F::ONE;
+        mmcs.verify_multi_batch(&commit, &dims, &indices, &opened_values, &bad_proof)
+            .expect_err("expected verification to fail");
+
+        let mut bad_values = opened_values.clone();
+        bad_values[1][1][2] += F::ONE;
+        mmcs.verify_multi_batch(&commit, &dims, &indices, &bad_values, &proof)
+            .expect_err("expected verification to fail");
+
+        mmcs.verify_multi_batch(&commit, &dims, &[3, 17, 19], &opened_values, &proof)
+            .expect_err("expected verification to fail");
+    }
 }
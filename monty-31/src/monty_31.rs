@@ -153,7 +153,14 @@ impl<FP: FieldParameters> Serialize for MontyField31<FP> {
 impl<'de, FP: FieldParameters> Deserialize<'de> for MontyField31<FP> {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
         let val = u32::deserialize(d)?;
-        Ok(MontyField31::from_canonical_u32(val))
+        // Reject non-canonical values, so that each element has exactly one encoding.
+        if val < FP::PRIME {
+            Ok(MontyField31::from_canonical_u32(val))
+        } else {
+            Err(serde::de::Error::custom(format_args!(
+                "{val} is not a canonical field element"
+            )))
+        }
     }
 }
 
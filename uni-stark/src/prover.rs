@@ -82,9 +82,10 @@ where
     let quotient_chunks = quotient_domain.split_evals(quotient_degree, quotient_flat);
     let qc_domains = quotient_domain.split_domains(quotient_degree);
 
-    let (quotient_commit, quotient_data) = info_span!("commit to quotient poly chunks")
-        .in_scope(|| pcs.commit(izip!(qc_domains, quotient_chunks).collect_vec()));
-    challenger.observe(quotient_commit.clone());
+    let (quotient_commit, quotient_data) =
+        info_span!("commit to quotient poly chunks").in_scope(|| {
+            pcs.commit_and_observe(izip!(qc_domains, quotient_chunks).collect_vec(), challenger)
+        });
 
     let commitments = Commitments {
         trace: trace_commit,
@@ -122,8 +122,10 @@ fn test_public_value_impl(n: usize, x: u64) {
     let trace = generate_trace_rows::<Val>(0, 1, n);
     let fri_config = FriConfig {
         log_blowup: 2,
+        log_final_poly_len: 0,
         num_queries: 28,
         proof_of_work_bits: 8,
+        omit_zero_bit_pow: false,
         mmcs: challenge_mmcs,
     };
     let pcs = Pcs::new(dft, val_mmcs, fri_config);
@@ -161,8 +163,10 @@ fn test_incorrect_public_value() {
     let dft = Dft::default();
     let fri_config = FriConfig {
         log_blowup: 2,
+        log_final_poly_len: 0,
         num_queries: 28,
         proof_of_work_bits: 8,
+        omit_zero_bit_pow: false,
         mmcs: challenge_mmcs,
     };
     let trace = generate_trace_rows::<Val>(0, 1, 1 << 3);